@@ -324,6 +324,12 @@ pub struct ExperimentalConfig {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub use_environment_activation_cache: Option<bool>,
+
+    /// When an environment cannot be solved, additionally solve subsets of its
+    /// features to find the combination of features that conflicts.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature_solve_diagnostics: Option<bool>,
 }
 
 impl ExperimentalConfig {
@@ -332,14 +338,21 @@ impl ExperimentalConfig {
             use_environment_activation_cache: other
                 .use_environment_activation_cache
                 .or(self.use_environment_activation_cache),
+            feature_solve_diagnostics: other
+                .feature_solve_diagnostics
+                .or(self.feature_solve_diagnostics),
         }
     }
     pub fn use_environment_activation_cache(&self) -> bool {
         self.use_environment_activation_cache.unwrap_or(false)
     }
 
+    pub fn feature_solve_diagnostics(&self) -> bool {
+        self.feature_solve_diagnostics.unwrap_or(false)
+    }
+
     pub fn is_default(&self) -> bool {
-        self.use_environment_activation_cache.is_none()
+        self.use_environment_activation_cache.is_none() && self.feature_solve_diagnostics.is_none()
     }
 }
 
@@ -914,6 +927,7 @@ impl Config {
             "pypi-config.extra-index-urls",
             "pypi-config.keyring-provider",
             "experimental.use-environment-activation-cache",
+            "experimental.feature-solve-diagnostics",
         ]
     }
 
@@ -1011,6 +1025,12 @@ impl Config {
         self.experimental.use_environment_activation_cache()
     }
 
+    /// Whether to solve subsets of the features of an unsolvable environment
+    /// to pinpoint the conflicting features.
+    pub fn experimental_feature_solve_diagnostics(&self) -> bool {
+        self.experimental.feature_solve_diagnostics()
+    }
+
     /// Retrieve the value for the max_concurrent_solves field.
     pub fn max_concurrent_solves(&self) -> usize {
         self.concurrency.solves
@@ -1102,6 +1122,10 @@ impl Config {
                         self.repodata_config.default.disable_sharded =
                             value.map(|v| v.parse()).transpose().into_diagnostic()?;
                     }
+                    "feature-solve-diagnostics" => {
+                        self.experimental.feature_solve_diagnostics =
+                            value.map(|v| v.parse()).transpose().into_diagnostic()?;
+                    }
                     _ => return Err(err),
                 }
             }
@@ -1632,6 +1656,14 @@ UNUSED = "unused"
 
         assert_eq!(config.max_concurrent_downloads(), 1);
 
+        config
+            .set(
+                "experimental.feature-solve-diagnostics",
+                Some("true".to_string()),
+            )
+            .unwrap();
+        assert!(config.experimental_feature_solve_diagnostics());
+
         config.set("unknown-key", None).unwrap_err();
     }
 
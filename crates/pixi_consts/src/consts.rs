@@ -30,6 +30,7 @@ pub const CONDA_REPODATA_CACHE_DIR: &str = rattler_cache::REPODATA_CACHE_DIR;
 pub const CONDA_META_DIR: &str = "conda-meta";
 pub const PYPI_CACHE_DIR: &str = "uv-cache";
 pub const CONDA_PYPI_MAPPING_CACHE_DIR: &str = "conda-pypi-mapping";
+pub const REMOTE_INCLUDES_CACHE_DIR: &str = "remote-includes-v0";
 pub const CACHED_ENVS_DIR: &str = "cached-envs-v0";
 pub const CACHED_BUILD_ENVS_DIR: &str = "cached-build-envs-v0";
 
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::glob_set::{self, GlobSet};
+
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+pub enum GlobFilesError {
+    #[error(transparent)]
+    GlobSet(#[from] glob_set::GlobSetError),
+}
+
+/// Returns the files in `root_dir` that match the given glob patterns, sorted
+/// by path. Patterns that start with `!` exclude the files they match.
+pub fn find_files<'a>(
+    root_dir: &Path,
+    globs: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<PathBuf>, GlobFilesError> {
+    // If the root is not a directory or does not exist, nothing matches.
+    if !root_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let glob_set = GlobSet::create(globs)?;
+    let mut files = glob_set
+        .filter_directory(root_dir)
+        .map(|entry| entry.map(|entry| entry.path().to_path_buf()))
+        .collect::<Result<Vec<_>, _>>()?;
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, File};
+
+    use tempfile::tempdir;
+
+    use super::find_files;
+
+    #[test]
+    fn test_find_files() {
+        let temp_dir = tempdir().unwrap();
+        let root_path = temp_dir.path();
+
+        for dir in ["packages/a", "packages/b", "packages/legacy", "docs"] {
+            create_dir_all(root_path.join(dir)).unwrap();
+            File::create(root_path.join(dir).join("pixi.toml")).unwrap();
+        }
+
+        let files = find_files(
+            root_path,
+            ["packages/*/pixi.toml", "!packages/legacy/pixi.toml"],
+        )
+        .unwrap()
+        .into_iter()
+        .map(|path| path.strip_prefix(root_path).unwrap().to_path_buf())
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            files,
+            vec![
+                "packages/a/pixi.toml"
+                    .parse::<std::path::PathBuf>()
+                    .unwrap(),
+                "packages/b/pixi.toml".parse().unwrap(),
+            ]
+        );
+    }
+}
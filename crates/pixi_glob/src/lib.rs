@@ -2,11 +2,13 @@
 //! A crate to deal with glob patterns in Pixi.
 //! And the caching thereof.
 
+mod glob_files;
 mod glob_hash;
 mod glob_hash_cache;
 mod glob_mtime;
 mod glob_set;
 
+pub use glob_files::{find_files, GlobFilesError};
 pub use glob_hash::{GlobHash, GlobHashError};
 pub use glob_hash_cache::{GlobHashCache, GlobHashKey};
 pub use glob_mtime::{GlobModificationTime, GlobModificationTimeError};
@@ -2,9 +2,227 @@ use indexmap::IndexMap;
 use serde::Deserialize;
 
 #[derive(Default, Clone, Deserialize, Debug)]
-#[serde(deny_unknown_fields)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct Activation {
     pub scripts: Option<Vec<String>>,
+    /// Scripts that are executed with the cross-platform shell that is also
+    /// used to run tasks. The variables they export are added to the
+    /// activation of every shell.
+    pub task_shell_scripts: Option<Vec<String>>,
     /// Environment variables to set before running the scripts.
-    pub env: Option<IndexMap<String, String>>,
+    pub env: Option<IndexMap<String, EnvValue>>,
+}
+
+/// How an environment variable of the activation is modified.
+///
+/// Values can reference other variables with `$NAME` or `${NAME}`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EnvValue {
+    /// Sets the variable to the value, this is what a plain string means.
+    Set(String),
+
+    /// Adds paths in front of the list of paths in the variable.
+    PrependPath(Vec<String>),
+
+    /// Adds paths at the end of the list of paths in the variable.
+    AppendPath(Vec<String>),
+
+    /// Removes the variable.
+    Unset,
+}
+
+impl<'de> Deserialize<'de> for EnvValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields, rename_all = "kebab-case")]
+        struct TomlEnvValue {
+            set: Option<String>,
+            prepend_path: Option<Vec<String>>,
+            append_path: Option<Vec<String>>,
+            unset: Option<bool>,
+        }
+
+        serde_untagged::UntaggedEnumVisitor::new()
+            .string(|value| Ok(EnvValue::Set(value.to_string())))
+            .map(|map| {
+                let value: TomlEnvValue = map.deserialize()?;
+                match value {
+                    TomlEnvValue {
+                        set: Some(value),
+                        prepend_path: None,
+                        append_path: None,
+                        unset: None,
+                    } => Ok(EnvValue::Set(value)),
+                    TomlEnvValue {
+                        set: None,
+                        prepend_path: Some(paths),
+                        append_path: None,
+                        unset: None,
+                    } => Ok(EnvValue::PrependPath(paths)),
+                    TomlEnvValue {
+                        set: None,
+                        prepend_path: None,
+                        append_path: Some(paths),
+                        unset: None,
+                    } => Ok(EnvValue::AppendPath(paths)),
+                    TomlEnvValue {
+                        set: None,
+                        prepend_path: None,
+                        append_path: None,
+                        unset: Some(true),
+                    } => Ok(EnvValue::Unset),
+                    TomlEnvValue {
+                        unset: Some(false), ..
+                    } => Err(serde::de::Error::custom(
+                        "`unset` can only be `true`, remove the variable to keep it",
+                    )),
+                    _ => Err(serde::de::Error::custom(
+                        "expected exactly one of `set`, `prepend-path`, `append-path` or `unset`",
+                    )),
+                }
+            })
+            .expecting(
+                "a string or a table with one of `set`, `prepend-path`, `append-path` or `unset`",
+            )
+            .deserialize(deserializer)
+    }
+}
+
+impl EnvValue {
+    /// Applies the modification of the variable `name` to `env`.
+    ///
+    /// The current value of a variable that isn't in `env` is taken from
+    /// `lookup`. Paths are joined with `separator`. An unset variable is kept
+    /// in `env` with an empty value so it overrides the value from `lookup`.
+    pub fn apply(
+        &self,
+        name: &str,
+        env: &mut IndexMap<String, String>,
+        lookup: &dyn Fn(&str) -> Option<String>,
+        separator: &str,
+    ) {
+        let get = |key: &str| env.get(key).cloned().or_else(|| lookup(key));
+        let paths = |paths: &[String]| {
+            paths
+                .iter()
+                .map(|path| expand_env_references(path, &get))
+                .collect::<Vec<_>>()
+        };
+        let current = get(name).filter(|value| !value.is_empty());
+        let value = match self {
+            EnvValue::Set(value) => expand_env_references(value, &get),
+            EnvValue::PrependPath(new) => paths(new)
+                .into_iter()
+                .chain(current)
+                .collect::<Vec<_>>()
+                .join(separator),
+            EnvValue::AppendPath(new) => current
+                .into_iter()
+                .chain(paths(new))
+                .collect::<Vec<_>>()
+                .join(separator),
+            EnvValue::Unset => String::new(),
+        };
+        env.insert(name.to_string(), value);
+    }
+}
+
+/// Replaces the references to other variables in `value`, `$NAME` and
+/// `${NAME}`, with their values. References to variables that `lookup` doesn't
+/// know are kept as-is so the shell can still resolve them.
+pub fn expand_env_references(value: &str, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, reference_len) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+
+        let reference = &rest[start..start + 1 + reference_len];
+        match (!name.is_empty()).then(|| lookup(name)).flatten() {
+            Some(replacement) => result.push_str(&replacement),
+            None => result.push_str(reference),
+        }
+        rest = &rest[start + 1 + reference_len..];
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_env_value() {
+        let env: IndexMap<String, EnvValue> = toml_edit::de::from_str(
+            r#"
+            FOO = "bar"
+            BAR = { set = "baz" }
+            PATH = { prepend-path = ["$PIXI_PROJECT_ROOT/bin"] }
+            LD_LIBRARY_PATH = { append-path = ["/opt/lib"] }
+            OLD = { unset = true }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            env.into_values().collect::<Vec<_>>(),
+            vec![
+                EnvValue::Set("bar".to_string()),
+                EnvValue::Set("baz".to_string()),
+                EnvValue::PrependPath(vec!["$PIXI_PROJECT_ROOT/bin".to_string()]),
+                EnvValue::AppendPath(vec!["/opt/lib".to_string()]),
+                EnvValue::Unset,
+            ]
+        );
+
+        assert!(
+            toml_edit::de::from_str::<IndexMap<String, EnvValue>>("OLD = { unset = false }")
+                .is_err()
+        );
+        assert!(toml_edit::de::from_str::<IndexMap<String, EnvValue>>(
+            "FOO = { set = \"a\", unset = true }"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_apply_env_value() {
+        let lookup = |name: &str| (name == "LD_LIBRARY_PATH").then(|| String::from("/usr/lib"));
+        let mut env = IndexMap::from([(String::from("ROOT"), String::from("/project"))]);
+
+        EnvValue::Set(String::from("${ROOT}/data:$UNKNOWN")).apply("DATA", &mut env, &lookup, ":");
+        EnvValue::PrependPath(vec![String::from("$ROOT/lib")]).apply(
+            "LD_LIBRARY_PATH",
+            &mut env,
+            &lookup,
+            ":",
+        );
+        EnvValue::AppendPath(vec![String::from("/opt/lib")]).apply(
+            "LD_LIBRARY_PATH",
+            &mut env,
+            &lookup,
+            ":",
+        );
+        EnvValue::AppendPath(vec![String::from("/a"), String::from("/b")])
+            .apply("NEW_PATH", &mut env, &lookup, ":");
+
+        assert_eq!(env["DATA"], "/project/data:$UNKNOWN");
+        assert_eq!(env["LD_LIBRARY_PATH"], "/project/lib:/usr/lib:/opt/lib");
+        assert_eq!(env["NEW_PATH"], "/a:/b");
+    }
 }
@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+/// The policy used by `pixi audit` to decide which locked packages are
+/// acceptable. Defined in the `[workspace.audit]` table of the manifest.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct AuditPolicy {
+    /// The licenses that are allowed. If this list is empty, every license
+    /// that is not denied is allowed.
+    #[serde(default)]
+    pub allow_licenses: Vec<String>,
+
+    /// The licenses that are never allowed.
+    #[serde(default)]
+    pub deny_licenses: Vec<String>,
+
+    /// Whether packages without (valid) license information are allowed.
+    #[serde(default = "default_allow_unknown_licenses")]
+    pub allow_unknown_licenses: bool,
+
+    /// The identifiers (or aliases) of advisories that should not be reported.
+    #[serde(default)]
+    pub ignore_advisories: Vec<String>,
+
+    /// The path to a directory with OSV advisories (relative to the workspace
+    /// root).
+    pub advisory_db: Option<PathBuf>,
+}
+
+fn default_allow_unknown_licenses() -> bool {
+    true
+}
+
+impl Default for AuditPolicy {
+    fn default() -> Self {
+        Self {
+            allow_licenses: Vec::new(),
+            deny_licenses: Vec::new(),
+            allow_unknown_licenses: default_allow_unknown_licenses(),
+            ignore_advisories: Vec::new(),
+            advisory_db: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::toml::{ExternalWorkspaceProperties, TomlManifest};
+
+    #[test]
+    fn test_parse_audit_policy() {
+        let contents = r#"
+        [workspace]
+        name = "foo"
+        channels = []
+        platforms = []
+
+        [workspace.audit]
+        allow-licenses = ["MIT", "BSD-3-Clause"]
+        deny-licenses = ["GPL-3.0-only"]
+        allow-unknown-licenses = false
+        ignore-advisories = ["GHSA-xxxx-xxxx-xxxx"]
+        advisory-db = "advisories"
+        "#;
+
+        let (manifest, _) = TomlManifest::from_toml_str(contents)
+            .unwrap()
+            .into_manifests(ExternalWorkspaceProperties::default())
+            .unwrap();
+        let policy = manifest.workspace.audit.unwrap();
+        assert_eq!(policy.allow_licenses, vec!["MIT", "BSD-3-Clause"]);
+        assert_eq!(policy.deny_licenses, vec!["GPL-3.0-only"]);
+        assert!(!policy.allow_unknown_licenses);
+        assert_eq!(policy.ignore_advisories, vec!["GHSA-xxxx-xxxx-xxxx"]);
+        assert_eq!(policy.advisory_db, Some(PathBuf::from("advisories")));
+    }
+}
@@ -5,13 +5,18 @@ use std::{
     str::FromStr,
 };
 
+use indexmap::IndexSet;
 use miette::Diagnostic;
+use rattler_conda_types::Platform;
 use regex::Regex;
 use serde::{self, Deserialize, Deserializer};
 use serde_with::SerializeDisplay;
 use thiserror::Error;
 
-use crate::{consts::DEFAULT_ENVIRONMENT_NAME, solve_group::SolveGroupIdx};
+use crate::{
+    consts::DEFAULT_ENVIRONMENT_NAME, solve_group::SolveGroupIdx, utils::PixiSpanned,
+    PrioritizedChannel,
+};
 
 #[derive(Debug, Clone, Error, Diagnostic, PartialEq)]
 #[error("Failed to parse environment name '{attempted_parse}', please use only lowercase letters, numbers and dashes")]
@@ -147,6 +152,23 @@ pub struct Environment {
 
     /// Whether to include the default feature in that environment
     pub no_default_feature: bool,
+
+    /// The environment that this environment extends. The features,
+    /// platforms and channels of that environment have already been merged
+    /// into this environment.
+    pub extends: Option<EnvironmentName>,
+
+    /// An abstract environment is never locked or installed, it only exists
+    /// to be extended by other environments.
+    pub is_abstract: bool,
+
+    /// The platforms of the environment. When set these restrict the
+    /// platforms that are supported by the features of the environment.
+    pub platforms: Option<PixiSpanned<IndexSet<Platform>>>,
+
+    /// The channels of the environment. When set these are used instead of
+    /// the channels of the features of the environment.
+    pub channels: Option<IndexSet<PrioritizedChannel>>,
 }
 
 #[cfg(test)]
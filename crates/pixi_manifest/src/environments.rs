@@ -13,6 +13,10 @@ pub struct Environments {
 
     /// A map of all environments, indexed by their name.
     pub(crate) by_name: IndexMap<EnvironmentName, EnvironmentIdx>,
+
+    /// The abstract environments, these are only extended by other
+    /// environments and are never locked or installed.
+    pub(crate) abstract_environments: IndexMap<EnvironmentName, Environment>,
 }
 
 impl Environments {
@@ -31,6 +35,11 @@ impl Environments {
         self.environments.iter().flat_map(Option::as_ref)
     }
 
+    /// Returns an iterator over the abstract environments in the project.
+    pub fn abstract_environments(&self) -> impl Iterator<Item = &Environment> + '_ {
+        self.abstract_environments.values()
+    }
+
     /// Adds a new environment to the set of environments. If the environment
     /// already exists it is overwritten.
     pub fn add(&mut self, environment: Environment) -> EnvironmentIdx {
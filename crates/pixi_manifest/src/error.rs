@@ -2,15 +2,21 @@ use std::{
     borrow::{Borrow, Cow},
     fmt::Display,
     ops::Range,
+    path::PathBuf,
 };
 
 use itertools::Itertools;
-use miette::{Diagnostic, LabeledSpan, SourceOffset, SourceSpan};
-use rattler_conda_types::{version_spec::ParseVersionSpecError, InvalidPackageNameError};
+use miette::{Diagnostic, LabeledSpan, NamedSource, SourceOffset, SourceSpan};
+use rattler_conda_types::{
+    version_spec::ParseVersionSpecError, InvalidPackageNameError, Version, VersionSpec,
+};
 use thiserror::Error;
 
 use super::pypi::pypi_requirement::Pep508ToPyPiRequirementError;
-use crate::{KnownPreviewFeature, WorkspaceManifest};
+use crate::{
+    fix::{did_you_mean, similar_names, unambiguous_name, unknown_name, ManifestFix},
+    KnownPreviewFeature, RemoteInclude, WorkspaceManifest,
+};
 
 #[derive(Error, Debug, Clone, Diagnostic)]
 pub enum DependencyError {
@@ -38,6 +44,12 @@ pub enum RequirementConversionError {
 pub enum TomlError {
     #[error("{}", .0.message())]
     Error(toml_edit::TomlError),
+    #[error("{}", .error.message())]
+    UnknownName {
+        error: toml_edit::TomlError,
+        name: String,
+        candidates: Vec<String>,
+    },
     #[error("Missing table `[tool.pixi.project]`. Try running `pixi init`")]
     NoPixiTable,
     #[error("Missing field `{0}`")]
@@ -109,7 +121,9 @@ impl Diagnostic for FeatureNotEnabled {
 impl Diagnostic for TomlError {
     fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
         let span = match self {
-            TomlError::Error(err) => err.span().map(SourceSpan::from),
+            TomlError::Error(err) | TomlError::UnknownName { error: err, .. } => {
+                err.span().map(SourceSpan::from)
+            }
             TomlError::NoPixiTable => Some(SourceSpan::new(SourceOffset::from(0), 1)),
             TomlError::Generic(_, span) | TomlError::MissingField(_, span) => {
                 span.clone().map(SourceSpan::from)
@@ -141,6 +155,12 @@ impl Diagnostic for TomlError {
             }
             TomlError::FeatureNotEnabled(err) => err.help(),
             TomlError::InvalidNonPackageDependencies(err) => err.help(),
+            TomlError::UnknownName {
+                name, candidates, ..
+            } => {
+                let similar = similar_names(name, candidates.iter().map(String::as_str));
+                did_you_mean(&similar).map(|help| Box::new(help) as Box<dyn Display>)
+            }
             _ => None,
         }
     }
@@ -160,6 +180,42 @@ impl TomlError {
             table_name: table_name.into(),
         }
     }
+
+    /// Converts an error of deserializing `source`. Errors about unknown keys
+    /// or names record the name in the source that the error points at.
+    pub fn from_deserialize(err: toml_edit::de::Error, source: &str) -> Self {
+        let error = toml_edit::TomlError::from(err);
+        let unknown = error
+            .span()
+            .and_then(|span| unknown_name(source, span, error.message()));
+        match unknown {
+            Some((name, candidates)) => TomlError::UnknownName {
+                error,
+                name,
+                candidates,
+            },
+            None => TomlError::Error(error),
+        }
+    }
+
+    /// Returns the fix for a misspelled key or name that caused this error, if
+    /// there is exactly one likely correction.
+    pub fn fix(&self) -> Option<ManifestFix> {
+        let TomlError::UnknownName {
+            error,
+            name,
+            candidates,
+        } = self
+        else {
+            return None;
+        };
+        let replacement = unambiguous_name(name, candidates.iter().map(String::as_str))?;
+        Some(ManifestFix {
+            span: error.span()?,
+            replacement: replacement.to_string(),
+            original: name.clone(),
+        })
+    }
 }
 impl From<toml_edit::de::Error> for TomlError {
     fn from(e: toml_edit::de::Error) -> Self {
@@ -240,3 +296,30 @@ impl Diagnostic for InvalidNonPackageDependencies {
         )))
     }
 }
+
+/// Error for when the `requires-pixi` field of the manifest doesn't match the
+/// version of pixi that is running.
+#[derive(Debug, Error, Diagnostic)]
+#[error("the workspace requires pixi {requirement}, but this is pixi {current}")]
+#[diagnostic(help(
+    "update pixi with `pixi self-update`, or to a specific version with `pixi self-update --version <VERSION>`"
+))]
+pub struct UnsupportedPixiVersion {
+    pub requirement: VersionSpec,
+    pub current: Version,
+    #[source_code]
+    pub source_code: NamedSource<String>,
+    #[label("required here")]
+    pub span: Option<SourceSpan>,
+}
+
+/// Error for when a manifest includes a remote file whose contents were not
+/// provided. The caller is responsible for fetching the file and loading the
+/// manifest again with its contents.
+#[derive(Debug, Clone, Error, Diagnostic)]
+#[error("the included file '{}' has not been fetched", .include.url)]
+pub struct UnresolvedRemoteInclude {
+    /// The directory of the manifest that includes the file.
+    pub root: PathBuf,
+    pub include: RemoteInclude,
+}
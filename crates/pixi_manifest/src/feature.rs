@@ -18,7 +18,7 @@ use crate::{
     pypi::{pypi_options::PypiOptions, PyPiPackageName},
     target::Targets,
     utils::PixiSpanned,
-    PyPiRequirement, SpecType, SystemRequirements, WorkspaceTarget,
+    EnvValue, PyPiRequirement, SpecType, SystemRequirements, WorkspaceTarget,
 };
 
 /// The name of a feature. This is either a string or default for the default
@@ -142,6 +142,19 @@ pub struct Feature {
     /// Pypi-related options
     pub pypi_options: Option<PypiOptions>,
 
+    /// Constraints on the versions of conda packages. A constraint only
+    /// applies when the package is part of the environment, it does not add
+    /// the package as a dependency.
+    pub constraints: Option<IndexMap<PackageName, PixiSpec>>,
+
+    /// Requirements that replace all requirements on the same pypi package,
+    /// including those of transitive dependencies.
+    pub pypi_overrides: Option<IndexMap<PyPiPackageName, PyPiRequirement>>,
+
+    /// Constraints on the versions of pypi packages, like `constraints` these
+    /// do not add the packages as dependencies.
+    pub pypi_constraints: Option<IndexMap<PyPiPackageName, PyPiRequirement>>,
+
     /// Target specific configuration.
     pub targets: Targets<WorkspaceTarget>,
 }
@@ -156,6 +169,9 @@ impl Feature {
             channel_priority: None,
             system_requirements: SystemRequirements::default(),
             pypi_options: None,
+            constraints: None,
+            pypi_overrides: None,
+            pypi_constraints: None,
 
             targets: <Targets<WorkspaceTarget> as Default>::default(),
         }
@@ -234,13 +250,29 @@ impl Feature {
     ///
     /// If the `platform` is `None` no platform specific dependencies are taken
     /// into consideration.
+    ///
+    /// Targets that select on a virtual package are matched against the system
+    /// requirements of this feature.
     pub fn dependencies(
         &self,
         spec_type: SpecType,
         platform: Option<Platform>,
+    ) -> Option<Cow<'_, IndexMap<PackageName, PixiSpec>>> {
+        self.dependencies_with_system_requirements(spec_type, platform, &self.system_requirements)
+    }
+
+    /// Returns the dependencies of the feature for a given `spec_type` and
+    /// `platform` in an environment with the given `system_requirements`.
+    ///
+    /// See [`Self::dependencies`] for more information.
+    pub fn dependencies_with_system_requirements(
+        &self,
+        spec_type: SpecType,
+        platform: Option<Platform>,
+        system_requirements: &SystemRequirements,
     ) -> Option<Cow<'_, IndexMap<PackageName, PixiSpec>>> {
         self.targets
-            .resolve(platform)
+            .resolve_with_system_requirements(platform, system_requirements)
             // Get the targets in reverse order, from least specific to most specific.
             // This is required because the extent function will overwrite existing keys.
             .rev()
@@ -270,12 +302,27 @@ impl Feature {
     ///
     /// If the `platform` is `None` no platform specific dependencies are taken
     /// into consideration.
+    ///
+    /// Targets that select on a virtual package are matched against the system
+    /// requirements of this feature.
     pub fn combined_dependencies(
         &self,
         platform: Option<Platform>,
+    ) -> Option<Cow<'_, IndexMap<PackageName, PixiSpec>>> {
+        self.combined_dependencies_with_system_requirements(platform, &self.system_requirements)
+    }
+
+    /// Returns the combined dependencies of the feature and `platform` in an
+    /// environment with the given `system_requirements`.
+    ///
+    /// See [`Self::combined_dependencies`] for more information.
+    pub fn combined_dependencies_with_system_requirements(
+        &self,
+        platform: Option<Platform>,
+        system_requirements: &SystemRequirements,
     ) -> Option<Cow<'_, IndexMap<PackageName, PixiSpec>>> {
         self.targets
-            .resolve(platform)
+            .resolve_with_system_requirements(platform, system_requirements)
             // Get the targets in reverse order, from least specific to most specific.
             // This is required because the extent function will overwrite existing keys.
             .rev()
@@ -304,12 +351,27 @@ impl Feature {
     ///
     /// Returns `None` if this feature does not define any target that has any
     /// of the requested dependencies.
+    ///
+    /// Targets that select on a virtual package are matched against the system
+    /// requirements of this feature.
     pub fn pypi_dependencies(
         &self,
         platform: Option<Platform>,
+    ) -> Option<Cow<'_, IndexMap<PyPiPackageName, PyPiRequirement>>> {
+        self.pypi_dependencies_with_system_requirements(platform, &self.system_requirements)
+    }
+
+    /// Returns the PyPi dependencies of the feature for a given `platform` in
+    /// an environment with the given `system_requirements`.
+    ///
+    /// See [`Self::pypi_dependencies`] for more information.
+    pub fn pypi_dependencies_with_system_requirements(
+        &self,
+        platform: Option<Platform>,
+        system_requirements: &SystemRequirements,
     ) -> Option<Cow<'_, IndexMap<PyPiPackageName, PyPiRequirement>>> {
         self.targets
-            .resolve(platform)
+            .resolve_with_system_requirements(platform, system_requirements)
             // Get the targets in reverse order, from least specific to most specific.
             // This is required because the extend function will overwrite existing keys.
             .rev()
@@ -340,24 +402,33 @@ impl Feature {
             .next()
     }
 
-    /// Returns the activation environment for the most specific target that
-    /// matches the given `platform`.
+    /// Returns the task shell activation scripts for the most specific target
+    /// that defines them.
     ///
-    /// Returns `None` if this feature does not define any target with an
-    /// activation.
-    pub fn activation_env(&self, platform: Option<Platform>) -> IndexMap<String, String> {
+    /// Returns `None` if this feature does not define any target with task
+    /// shell activation scripts.
+    pub fn activation_task_shell_scripts(
+        &self,
+        platform: Option<Platform>,
+    ) -> Option<&Vec<String>> {
+        self.targets
+            .resolve(platform)
+            .filter_map(|t| t.activation.as_ref())
+            .filter_map(|a| a.task_shell_scripts.as_ref())
+            .next()
+    }
+
+    /// Returns the environment variables of the activation of all targets
+    /// that match the given `platform`, in the order in which they are
+    /// applied. The variables of the most specific target are applied last.
+    pub fn activation_env(&self, platform: Option<Platform>) -> Vec<(String, EnvValue)> {
         self.targets
             .resolve(platform)
+            .rev()
             .filter_map(|t| t.activation.as_ref())
             .filter_map(|a| a.env.as_ref())
-            .fold(IndexMap::new(), |mut acc, x| {
-                for (k, v) in x {
-                    if !acc.contains_key(k) {
-                        acc.insert(k.clone(), v.clone());
-                    }
-                }
-                acc
-            })
+            .flat_map(|env| env.iter().map(|(k, v)| (k.clone(), v.clone())))
+            .collect()
     }
 
     /// Returns true if the feature contains any reference to a pypi
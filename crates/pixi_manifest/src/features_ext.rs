@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{borrow::Cow, collections::HashSet};
 
 use indexmap::IndexSet;
 use rattler_conda_types::{
@@ -36,8 +36,13 @@ pub trait FeaturesExt<'source>: HasManifestRef<'source> + HasFeaturesIter<'sourc
     /// the order they are defined in the manifest.
     ///
     /// If a feature does not specify any channel the default channels from the
-    /// project metadata are used instead.
+    /// project metadata are used instead. Channels that are set on the
+    /// environment replace the channels of the features.
     fn channels(&self) -> IndexSet<&'source NamedChannelOrUrl> {
+        if let Some(channels) = self.channels_override() {
+            return PrioritizedChannel::sort_channels_by_priority(channels).collect();
+        }
+
         // Collect all the channels from the features in one set,
         // deduplicate them and sort them on feature index, default feature comes last.
         let channels = self.features().flat_map(|feature| match &feature.channels {
@@ -90,9 +95,11 @@ pub trait FeaturesExt<'source>: HasManifestRef<'source> + HasFeaturesIter<'sourc
     ///
     /// Features can specify which platforms they support through the
     /// `platforms` key. If a feature does not specify any platforms the
-    /// features defined by the project are used.
+    /// features defined by the project are used. Platforms that are set on
+    /// the environment further restrict the supported platforms.
     fn platforms(&self) -> HashSet<Platform> {
-        self.features()
+        let platforms = self
+            .features()
             .map(|feature| {
                 match &feature.platforms {
                     Some(platforms) => &platforms.value,
@@ -105,7 +112,15 @@ pub trait FeaturesExt<'source>: HasManifestRef<'source> + HasFeaturesIter<'sourc
             .reduce(|accumulated_platforms, feat| {
                 accumulated_platforms.intersection(&feat).copied().collect()
             })
-            .unwrap_or_default()
+            .unwrap_or_default();
+
+        match self.platforms_override() {
+            Some(environment_platforms) => platforms
+                .intersection(&environment_platforms)
+                .copied()
+                .collect(),
+            None => platforms,
+        }
     }
 
     /// Returns the system requirements for this collection.
@@ -136,8 +151,11 @@ pub trait FeaturesExt<'source>: HasManifestRef<'source> + HasFeaturesIter<'sourc
     /// requirements are returned. The different requirements per package
     /// are sorted in the same order as the features they came from.
     fn pypi_dependencies(&self, platform: Option<Platform>) -> PyPiDependencies {
+        let system_requirements = self.target_system_requirements();
         self.features()
-            .filter_map(|f| f.pypi_dependencies(platform))
+            .filter_map(|f| {
+                f.pypi_dependencies_with_system_requirements(platform, &system_requirements)
+            })
             .into()
     }
 
@@ -149,10 +167,14 @@ pub trait FeaturesExt<'source>: HasManifestRef<'source> + HasFeaturesIter<'sourc
     /// are sorted in the same order as the features they came from.
     ///
     /// If the `platform` is `None` no platform specific dependencies are taken
-    /// into consideration.
+    /// into consideration. Targets that select on a virtual package are
+    /// matched against [`HasFeaturesIter::target_system_requirements`].
     fn dependencies(&self, kind: SpecType, platform: Option<Platform>) -> CondaDependencies {
+        let system_requirements = self.target_system_requirements();
         self.features()
-            .filter_map(|f| f.dependencies(kind, platform))
+            .filter_map(|f| {
+                f.dependencies_with_system_requirements(kind, platform, &system_requirements)
+            })
             .into()
     }
 
@@ -167,10 +189,42 @@ pub trait FeaturesExt<'source>: HasManifestRef<'source> + HasFeaturesIter<'sourc
     /// are sorted in the same order as the features they came from.
     ///
     /// If the `platform` is `None` no platform specific dependencies are taken
-    /// into consideration.
+    /// into consideration. Targets that select on a virtual package are
+    /// matched against [`HasFeaturesIter::target_system_requirements`].
     fn combined_dependencies(&self, platform: Option<Platform>) -> CondaDependencies {
+        let system_requirements = self.target_system_requirements();
+        self.features()
+            .filter_map(|f| {
+                f.combined_dependencies_with_system_requirements(platform, &system_requirements)
+            })
+            .into()
+    }
+
+    /// Returns the constraints on conda packages for this collection.
+    ///
+    /// The constraints of all features are combined, a package that is part of
+    /// the solution has to satisfy all of them. Constraints don't add
+    /// packages to the solution.
+    fn constraints(&self) -> CondaDependencies {
+        self.features()
+            .filter_map(|f| f.constraints.as_ref().map(Cow::Borrowed))
+            .into()
+    }
+
+    /// Returns the overrides of pypi requirements for this collection.
+    ///
+    /// An override replaces every requirement on the same package, including
+    /// the requirements of transitive dependencies.
+    fn pypi_overrides(&self) -> PyPiDependencies {
+        self.features()
+            .filter_map(|f| f.pypi_overrides.as_ref().map(Cow::Borrowed))
+            .into()
+    }
+
+    /// Returns the constraints on pypi packages for this collection.
+    fn pypi_constraints(&self) -> PyPiDependencies {
         self.features()
-            .filter_map(|f| f.combined_dependencies(platform))
+            .filter_map(|f| f.pypi_constraints.as_ref().map(Cow::Borrowed))
             .into()
     }
 
@@ -0,0 +1,290 @@
+//! Suggestions for misspelled keys and names in a manifest and the fixes that
+//! correct them.
+//!
+//! A fix replaces the misspelled name in the source of the manifest, so the
+//! rest of the document, including comments and formatting, is kept as-is.
+
+use std::ops::Range;
+
+use itertools::Itertools;
+use miette::{Diagnostic, NamedSource, SourceSpan};
+use rattler_conda_types::Platform;
+use thiserror::Error;
+use toml_edit::DocumentMut;
+
+/// Returns the candidates that are close to `name`, the closest first.
+pub fn similar_names<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    scored_names(name, candidates)
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Returns the candidate that is closer to `name` than all others, if there is
+/// one.
+pub fn unambiguous_name<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    match scored_names(name, candidates).as_slice() {
+        [(_, candidate)] => Some(*candidate),
+        [(closest, candidate), (next, _), ..] if closest < next => Some(*candidate),
+        _ => None,
+    }
+}
+
+/// Formats a help message that suggests the given names.
+pub fn did_you_mean(names: &[&str]) -> Option<String> {
+    match names {
+        [] => None,
+        [name] => Some(format!("Did you mean '{name}'?")),
+        names => Some(format!(
+            "Did you mean one of {}?",
+            names
+                .iter()
+                .format_with(", ", |name, f| f(&format_args!("'{name}'")))
+        )),
+    }
+}
+
+/// Computes the edit distance of the candidates that are close enough to
+/// `name` to be a likely typo, sorted by distance. Longer names allow more
+/// edits, up to three.
+fn scored_names<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<(usize, &'a str)> {
+    let max_distance = (name.chars().count() / 3).clamp(1, 3);
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .unique()
+        .map(|candidate| (strsim::damerau_levenshtein(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .sorted_by_key(|(distance, _)| *distance)
+        .collect()
+}
+
+/// Returns the unknown key or name a deserialization error points at, and the
+/// names that would have been accepted instead.
+///
+/// The span of the error covers the unknown key or value in the source, so the
+/// name is taken from the source itself. The accepted names are the ones serde
+/// lists after "expected" for unknown fields and variants.
+pub(crate) fn unknown_name(
+    source: &str,
+    span: Range<usize>,
+    message: &str,
+) -> Option<(String, Vec<String>)> {
+    let candidates =
+        if message.starts_with("unknown field") || message.starts_with("unknown variant") {
+            let (_, expected) = message.split_once(", expected ")?;
+            expected
+                .split('`')
+                .skip(1)
+                .step_by(2)
+                .map(ToString::to_string)
+                .collect()
+        } else if message.contains(" is not a known platform") {
+            Platform::all()
+                .map(|platform| platform.as_str())
+                .chain(["linux", "unix", "win", "osx"])
+                .map(ToString::to_string)
+                .collect()
+        } else {
+            return None;
+        };
+
+    let spanned = source.get(span)?.trim();
+    let name = spanned
+        .strip_prefix(['"', '\''])
+        .and_then(|name| name.strip_suffix(['"', '\'']))
+        .unwrap_or(spanned);
+    Some((name.to_string(), candidates))
+}
+
+/// A fix for a misspelled name in the source of a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestFix {
+    /// The part of the source that contains the misspelled name.
+    pub span: Range<usize>,
+
+    /// The misspelled name.
+    pub original: String,
+
+    /// The name that replaces it.
+    pub replacement: String,
+}
+
+impl ManifestFix {
+    /// Returns the source with the misspelled name replaced, or `None` if the
+    /// name is not found in the span or the result is not valid toml.
+    pub fn apply(&self, source: &str) -> Option<String> {
+        let is_name_char = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+        let spanned = source.get(self.span.clone())?;
+        let (offset, _) = spanned.match_indices(&self.original).find(|(index, _)| {
+            let before = spanned[..*index].chars().next_back();
+            let after = spanned[index + self.original.len()..].chars().next();
+            !before.is_some_and(is_name_char) && !after.is_some_and(is_name_char)
+        })?;
+
+        let start = self.span.start + offset;
+        let fixed = format!(
+            "{}{}{}",
+            &source[..start],
+            self.replacement,
+            &source[start + self.original.len()..]
+        );
+        fixed.parse::<DocumentMut>().ok()?;
+        Some(fixed)
+    }
+}
+
+impl std::fmt::Display for ManifestFix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "replaced '{}' with '{}'",
+            self.original, self.replacement
+        )
+    }
+}
+
+/// An error in a manifest that can be fixed automatically.
+#[derive(Debug, Error, Diagnostic)]
+#[error("{message}")]
+pub struct FixableError {
+    message: String,
+    #[source_code]
+    source_code: Option<NamedSource<String>>,
+    #[label("{label}")]
+    span: SourceSpan,
+    label: String,
+    #[help]
+    help: String,
+    pub fix: ManifestFix,
+}
+
+impl FixableError {
+    pub fn new(message: impl Into<String>, label: impl Into<String>, fix: ManifestFix) -> Self {
+        Self {
+            message: message.into(),
+            source_code: None,
+            span: SourceSpan::from(fix.span.clone()),
+            label: label.into(),
+            help: format!("Did you mean '{}'?", fix.replacement),
+            fix,
+        }
+    }
+
+    pub fn with_source_code(self, source_code: NamedSource<String>) -> Self {
+        Self {
+            source_code: Some(source_code),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{toml::TomlManifest, Manifest};
+
+    #[test]
+    fn test_similar_names() {
+        let candidates = ["depends-on", "cmd", "cwd", "description"];
+        assert_eq!(similar_names("depend-on", candidates), vec!["depends-on"]);
+        assert_eq!(similar_names("cdw", candidates), vec!["cwd"]);
+        assert_eq!(
+            unambiguous_name("depend-on", candidates),
+            Some("depends-on")
+        );
+        assert_eq!(unambiguous_name("cmw", candidates), None);
+        assert_eq!(unambiguous_name("env", candidates), None);
+    }
+
+    #[test]
+    fn test_unknown_name() {
+        let source = r#"depend-on = ["build"]
+platforms = ["linux-46"]"#;
+        assert_eq!(
+            unknown_name(
+                source,
+                0..9,
+                "unknown field `depend-on`, expected `cmd` or `depends-on`"
+            ),
+            Some((
+                "depend-on".to_string(),
+                vec!["cmd".to_string(), "depends-on".to_string()]
+            ))
+        );
+
+        let (name, candidates) =
+            unknown_name(source, 35..45, "'linux-46' is not a known platform").unwrap();
+        assert_eq!(name, "linux-46");
+        assert!(candidates.contains(&"linux-64".to_string()));
+
+        assert_eq!(unknown_name(source, 0..9, "invalid type: integer"), None);
+    }
+
+    #[test]
+    fn test_fix_toml_errors() {
+        let source = r#"
+        [workspace]
+        name = "foo"
+        channels = []
+        # Keep this comment
+        platforms = ["linux-46"]
+
+        [tasks]
+        test = { cmd = "pytest", depend-on = ["build"] }
+        build = "make"
+        "#;
+
+        let mut fixed = source.to_string();
+        let mut fixes = Vec::new();
+        while let Err(err) = TomlManifest::from_toml_str(&fixed) {
+            let fix = err.fix().expect("the error can be fixed");
+            fixed = fix.apply(&fixed).unwrap();
+            fixes.push(fix.to_string());
+        }
+        assert_eq!(
+            fixes,
+            vec![
+                "replaced 'linux-46' with 'linux-64'",
+                "replaced 'depend-on' with 'depends-on'"
+            ]
+        );
+        assert_eq!(
+            fixed,
+            source
+                .replace("linux-46", "linux-64")
+                .replace("depend-on", "depends-on")
+        );
+
+        // A feature in an environment can be fixed as well.
+        let err = Manifest::from_str(
+            Path::new("pixi.toml"),
+            r#"
+            [workspace]
+            name = "foo"
+            channels = []
+            platforms = []
+
+            [feature.test.dependencies]
+
+            [environments]
+            test = ["tset"]
+            "#,
+        )
+        .unwrap_err();
+        let err = err.downcast::<FixableError>().unwrap();
+        assert_eq!(err.fix.original, "tset");
+        assert_eq!(err.fix.replacement, "test");
+    }
+}
@@ -1,4 +1,9 @@
-use crate::Feature;
+use std::collections::HashSet;
+
+use indexmap::IndexSet;
+use rattler_conda_types::Platform;
+
+use crate::{Feature, PrioritizedChannel, SystemRequirements};
 
 /// This trait is implemented by types that contain a collection of Features.
 /// So that an abstraction can be made over these features and de-coupled from how
@@ -9,4 +14,32 @@ use crate::Feature;
 pub trait HasFeaturesIter<'source> {
     /// Returns an iterator to all Features in this collection
     fn features(&self) -> impl DoubleEndedIterator<Item = &'source Feature> + 'source;
+
+    /// Returns the system requirements that are used to select the targets of
+    /// the features that match a virtual package.
+    ///
+    /// By default these are the combined system requirements of all the
+    /// features in this collection.
+    fn target_system_requirements(&self) -> SystemRequirements {
+        self.features()
+            .map(|feature| &feature.system_requirements)
+            .fold(SystemRequirements::default(), |acc, req| {
+                acc.union(req)
+                    .expect("system requirements should have been validated upfront")
+            })
+    }
+
+    /// Returns the platforms that restrict the platforms of the features in
+    /// this collection, `None` if the platforms of the features are used
+    /// as-is.
+    fn platforms_override(&self) -> Option<HashSet<Platform>> {
+        None
+    }
+
+    /// Returns the channels that are used instead of the channels of the
+    /// features in this collection, `None` if the channels of the features
+    /// are used.
+    fn channels_override(&self) -> Option<&'source IndexSet<PrioritizedChannel>> {
+        None
+    }
 }
@@ -1,4 +1,5 @@
 mod activation;
+mod audit;
 mod build_system;
 pub(crate) mod channel;
 mod dependencies;
@@ -7,6 +8,7 @@ mod environments;
 mod error;
 mod feature;
 mod features_ext;
+mod fix;
 mod has_features_iter;
 mod has_manifest_ref;
 mod manifests;
@@ -24,25 +26,27 @@ pub mod utils;
 mod validation;
 mod workspace;
 
-pub use activation::Activation;
+pub use activation::{expand_env_references, Activation, EnvValue};
+pub use audit::AuditPolicy;
 pub use build_system::BuildSystem;
 pub use channel::PrioritizedChannel;
 pub use dependencies::{CondaDependencies, Dependencies, PyPiDependencies};
 pub use environment::{Environment, EnvironmentName};
-pub use error::TomlError;
+pub use error::{TomlError, UnresolvedRemoteInclude, UnsupportedPixiVersion};
 pub use feature::{Feature, FeatureName};
 pub use features_ext::FeaturesExt;
+pub use fix::{did_you_mean, similar_names, unambiguous_name, FixableError, ManifestFix};
 pub use has_features_iter::HasFeaturesIter;
 pub use has_manifest_ref::HasManifestRef;
 use itertools::Itertools;
-pub use manifests::{Manifest, ManifestKind, WorkspaceManifest};
+pub use manifests::{Manifest, ManifestKind, RemoteInclude, RemoteIncludes, WorkspaceManifest};
 use miette::Diagnostic;
 pub use preview::{KnownPreviewFeature, Preview, PreviewFeature};
 pub use pypi::pypi_requirement::PyPiRequirement;
 use rattler_conda_types::Platform;
 pub use spec_type::SpecType;
 pub use system_requirements::{LibCSystemRequirement, SystemRequirements};
-pub use target::{TargetSelector, Targets, WorkspaceTarget};
+pub use target::{TargetSelector, Targets, VirtualPackageSelector, WorkspaceTarget};
 pub use task::{Task, TaskName};
 use thiserror::Error;
 pub use workspace::Workspace;
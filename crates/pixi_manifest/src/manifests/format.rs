@@ -0,0 +1,301 @@
+//! Formatting of the pixi tables of a manifest.
+//!
+//! Edits through [`super::ManifestSource`] keep the layout that already
+//! existed, formatting brings a manifest back to a canonical layout: the
+//! tables are put in a fixed order, dependencies are sorted by name and specs
+//! are written in their shortest form.
+
+use toml_edit::{Item, Table, TableLike, Value};
+
+/// The order of the keys of a manifest, a feature and a target. Keys that are
+/// not in this list keep their order and are put after the known keys.
+const SECTION_ORDER: &[&str] = &[
+    "$schema",
+    "include",
+    "project",
+    "workspace",
+    "package",
+    "platforms",
+    "channels",
+    "channel-priority",
+    "system-requirements",
+    "dependencies",
+    "host-dependencies",
+    "build-dependencies",
+    "run-dependencies",
+    "pypi-dependencies",
+    "constraints",
+    "pypi-overrides",
+    "pypi-constraints",
+    "pypi-options",
+    "activation",
+    "target",
+    "feature",
+    "environments",
+    "tasks",
+];
+
+/// The tables that contain dependencies or requirements on packages.
+const DEPENDENCY_TABLES: &[&str] = &[
+    "dependencies",
+    "host-dependencies",
+    "build-dependencies",
+    "run-dependencies",
+    "pypi-dependencies",
+    "constraints",
+    "pypi-overrides",
+    "pypi-constraints",
+];
+
+/// Formats the pixi tables of a manifest. `table` is either the root of a
+/// `pixi.toml` or the `[tool.pixi]` table of a `pyproject.toml`, in which case
+/// `is_document_root` is false and the tables outside of it are not touched.
+pub(crate) fn format_pixi_table(table: &mut Table, is_document_root: bool) {
+    // The order in which tables are written is defined by their position in
+    // the document, the positions of the formatted tables are reassigned in
+    // the new order so the other tables of the document keep their place.
+    let mut positions = Vec::new();
+    visit_tables(table, &mut |table| positions.extend(table.position()));
+    positions.sort_unstable();
+
+    format_section(table);
+
+    let mut positions = positions.into_iter();
+    let mut is_first = is_document_root && !table.iter().any(|(_, item)| item.is_value());
+    visit_tables(table, &mut |table| {
+        if let Some(position) = table.position().and_then(|_| positions.next()) {
+            table.set_position(position);
+        }
+        if table.is_implicit() {
+            return;
+        }
+
+        // Separate the tables by a single empty line.
+        let prefix = table
+            .decor()
+            .prefix()
+            .and_then(|prefix| prefix.as_str())
+            .unwrap_or_default()
+            .trim_start();
+        let prefix = if is_first {
+            prefix.to_string()
+        } else {
+            format!("\n{prefix}")
+        };
+        table.decor_mut().set_prefix(prefix);
+        is_first = false;
+    });
+}
+
+/// Calls `visit` for all the tables nested in `table` in the order in which
+/// they are defined.
+fn visit_tables(table: &mut Table, visit: &mut dyn FnMut(&mut Table)) {
+    for (_, item) in table.iter_mut() {
+        match item {
+            Item::Table(child) => {
+                visit(child);
+                visit_tables(child, visit);
+            }
+            Item::ArrayOfTables(array) => {
+                for child in array.iter_mut() {
+                    visit(child);
+                    visit_tables(child, visit);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Formats the manifest, a feature, a target or the package section.
+fn format_section(table: &mut Table) {
+    let rank = |key: &str| {
+        SECTION_ORDER
+            .iter()
+            .position(|known| *known == key)
+            .unwrap_or(SECTION_ORDER.len())
+    };
+    table.sort_values_by(|a, _, b, _| rank(a.get()).cmp(&rank(b.get())));
+
+    for (key, item) in table.iter_mut() {
+        match key.get() {
+            name if DEPENDENCY_TABLES.contains(&name) => {
+                if let Some(dependencies) = item.as_table_like_mut() {
+                    format_dependencies(dependencies);
+                }
+            }
+            "package" => {
+                if let Some(package) = item.as_table_mut() {
+                    format_section(package);
+                }
+            }
+            "feature" | "target" => {
+                if let Some(sections) = item.as_table_mut() {
+                    for (_, section) in sections.iter_mut() {
+                        if let Some(section) = section.as_table_mut() {
+                            format_section(section);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Writes the specs in their shortest form and sorts them by name.
+fn format_dependencies(dependencies: &mut dyn TableLike) {
+    for (mut key, item) in dependencies.iter_mut() {
+        // A spec written as a table is written as an inline table instead, the
+        // comments above its header are moved to the key.
+        if let Item::Table(table) = item {
+            key.fmt();
+            if let Some(comment) = table
+                .decor()
+                .prefix()
+                .and_then(|prefix| prefix.as_str())
+                .filter(|prefix| prefix.contains('#'))
+            {
+                key.leaf_decor_mut()
+                    .set_prefix(comment.trim_start().to_string());
+            }
+            let mut spec = std::mem::take(table).into_inline_table();
+            spec.fmt();
+            *item = Item::Value(Value::InlineTable(spec));
+        }
+
+        let Some(value) = item.as_value_mut() else {
+            continue;
+        };
+        let decor = value.decor().clone();
+        let formatted = match value {
+            Value::String(spec) => Value::from(spec.value().as_str()),
+            Value::InlineTable(spec) => {
+                let version = spec
+                    .get("version")
+                    .and_then(Value::as_str)
+                    .filter(|_| spec.len() == 1)
+                    .map(str::to_string);
+                match version {
+                    Some(version) => Value::from(version),
+                    None => {
+                        spec.fmt();
+                        continue;
+                    }
+                }
+            }
+            _ => continue,
+        };
+        *value = formatted;
+        *value.decor_mut() = decor;
+    }
+    dependencies.sort_values();
+}
+
+#[cfg(test)]
+mod tests {
+    use toml_edit::DocumentMut;
+
+    use super::*;
+
+    fn format(source: &str, is_pyproject: bool) -> String {
+        let mut document = source.parse::<DocumentMut>().unwrap();
+        if is_pyproject {
+            let pixi = document["tool"]["pixi"].as_table_mut().unwrap();
+            format_pixi_table(pixi, false);
+        } else {
+            format_pixi_table(document.as_table_mut(), true);
+        }
+        document.to_string()
+    }
+
+    #[test]
+    fn test_format_manifest() {
+        let source = r#"[tasks]
+test = "pytest"
+
+[dependencies]
+python = { version = ">=3.11" }
+numpy = '*'
+# Pinned for reproducibility
+attrs = {version="23.1", build="py*"}
+
+[workspace]
+name = "foo"
+channels = []
+platforms = []
+[feature.test.tasks]
+cov = "pytest --cov"
+
+[feature.test.dependencies]
+pytest = "*"
+
+[dependencies.scipy]
+version = "*"
+channel = "conda-forge"
+"#;
+        let formatted = format(source, false);
+        assert_eq!(
+            formatted,
+            r#"[workspace]
+name = "foo"
+channels = []
+platforms = []
+
+[dependencies]
+# Pinned for reproducibility
+attrs = { version = "23.1", build = "py*" }
+numpy = "*"
+python = ">=3.11"
+scipy = { version = "*", channel = "conda-forge" }
+
+[feature.test.dependencies]
+pytest = "*"
+
+[feature.test.tasks]
+cov = "pytest --cov"
+
+[tasks]
+test = "pytest"
+"#
+        );
+
+        // Formatting is idempotent.
+        assert_eq!(format(&formatted, false), formatted);
+    }
+
+    #[test]
+    fn test_format_pyproject() {
+        let source = r#"[project]
+name = "foo"
+dependencies = ["requests", "attrs"]
+
+[tool.pixi.tasks]
+test = "pytest"
+
+[tool.ruff]
+line-length = 100
+
+[tool.pixi.dependencies]
+python = "*"
+attrs = "*"
+"#;
+        assert_eq!(
+            format(source, true),
+            r#"[project]
+name = "foo"
+dependencies = ["requests", "attrs"]
+
+[tool.pixi.dependencies]
+attrs = "*"
+python = "*"
+
+[tool.ruff]
+line-length = 100
+
+[tool.pixi.tasks]
+test = "pytest"
+"#
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! A manifest can include features that are shared between projects from
+//! other files with its `include` field. The included files are local paths
+//! relative to the manifest or urls pinned by the sha256 hash of the file.
+//! Remote files are not fetched while loading the manifest, the caller
+//! provides their contents as [`RemoteIncludes`].
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+use miette::{Context, IntoDiagnostic, NamedSource, Report};
+use rattler_digest::Sha256Hash;
+use toml_edit::DocumentMut;
+use url::Url;
+
+use crate::{
+    error::UnresolvedRemoteInclude,
+    manifests::ManifestSource,
+    toml::{TomlDocument, TomlInclude, TomlIncludeLocation},
+    Feature, FeatureName, Preview, TomlError,
+};
+
+/// A remote file that is included by a manifest, pinned by the sha256 hash of
+/// its contents.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RemoteInclude {
+    pub url: Url,
+    pub sha256: Sha256Hash,
+}
+
+/// The contents of the remote included files, keyed by their hash. The
+/// contents are expected to match the hash.
+pub type RemoteIncludes = HashMap<Sha256Hash, String>;
+
+/// A file that is included by a manifest.
+#[derive(Debug, Clone)]
+pub struct IncludedManifest {
+    /// The location of the file as written in the `include` field.
+    pub location: String,
+
+    /// The path of a local file, remote files don't have a path and can't be
+    /// edited.
+    pub path: Option<PathBuf>,
+
+    /// The raw contents of the file, used to provide better error messages.
+    pub contents: String,
+
+    /// Editable toml document
+    pub document: ManifestSource,
+
+    /// The names of the features defined in the file.
+    pub features: Vec<FeatureName>,
+}
+
+impl IncludedManifest {
+    /// Loads the included file at `location` and returns it together with the
+    /// features it defines. A remote file is taken from `remote_includes`,
+    /// an [`UnresolvedRemoteInclude`] error is returned if it isn't there.
+    pub(crate) fn load(
+        root: &Path,
+        location: &TomlIncludeLocation,
+        remote_includes: &RemoteIncludes,
+        preview: &Preview,
+    ) -> miette::Result<(Self, IndexMap<FeatureName, Feature>)> {
+        let (path, contents) = match location {
+            TomlIncludeLocation::Path(path) => {
+                let path = root.join(path);
+                let contents = std::fs::read_to_string(&path)
+                    .into_diagnostic()
+                    .wrap_err_with(|| format!("failed to read the included file '{location}'"))?;
+                (Some(path), contents)
+            }
+            TomlIncludeLocation::Url { url, sha256: None } => {
+                miette::bail!(
+                    help =
+                        "pin the file by its hash, use `{{ url = \"{url}\", sha256 = \"...\" }}`",
+                    "the remote file '{url}' must be pinned by the sha256 hash of its contents"
+                );
+            }
+            TomlIncludeLocation::Url {
+                url,
+                sha256: Some(sha256),
+            } => match remote_includes.get(sha256) {
+                Some(contents) => (None, contents.clone()),
+                None => {
+                    return Err(UnresolvedRemoteInclude {
+                        root: root.to_path_buf(),
+                        include: RemoteInclude {
+                            url: url.clone(),
+                            sha256: *sha256,
+                        },
+                    }
+                    .into())
+                }
+            },
+        };
+        let location = location.to_string();
+
+        let with_source = |e: TomlError| {
+            Report::from(e).with_source_code(NamedSource::new(&location, contents.clone()))
+        };
+        let document = contents
+            .parse::<DocumentMut>()
+            .map_err(TomlError::from)
+            .map_err(with_source)?;
+        let features = TomlInclude::from_toml_str(&contents)
+            .and_then(|include| {
+                include
+                    .feature
+                    .into_iter()
+                    .map(|(name, feature)| {
+                        let feature = feature.into_feature(name.clone(), preview)?;
+                        Ok((name, feature))
+                    })
+                    .collect::<Result<IndexMap<_, _>, TomlError>>()
+            })
+            .map_err(with_source)?;
+
+        Ok((
+            Self {
+                location,
+                path,
+                features: features.keys().cloned().collect(),
+                contents,
+                document: ManifestSource::PixiToml(TomlDocument::new(document)),
+            },
+            features,
+        ))
+    }
+
+    /// Returns an error if the file can't be edited because it is fetched
+    /// from a url.
+    pub(crate) fn ensure_editable(&self) -> miette::Result<()> {
+        if self.path.is_none() {
+            miette::bail!(
+                help = "copy the file into the project to edit it",
+                "the included file '{}' is fetched from a url and can't be edited",
+                self.location
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the document to edit the features of this file.
+    pub(crate) fn document_mut(&mut self) -> miette::Result<&mut ManifestSource> {
+        self.ensure_editable()?;
+        Ok(&mut self.document)
+    }
+
+    /// Saves the file if it was edited.
+    pub(crate) fn save(&mut self) -> miette::Result<()> {
+        let contents = self.document.to_string();
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if contents == self.contents {
+            return Ok(());
+        }
+        std::fs::write(path, &contents).into_diagnostic()?;
+        self.contents = contents;
+        Ok(())
+    }
+
+    /// Returns the source of the file to report errors in its features.
+    pub(crate) fn source(&self) -> NamedSource<String> {
+        NamedSource::new(&self.location, self.contents.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rattler_conda_types::{ChannelConfig, MatchSpec, ParseStrictness, Platform};
+    use rattler_digest::{compute_bytes_digest, Sha256};
+
+    use super::RemoteIncludes;
+    use crate::{
+        DependencyOverwriteBehavior, Manifest, SpecType, TargetSelector, UnresolvedRemoteInclude,
+    };
+
+    #[test]
+    fn test_included_features() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("shared")).unwrap();
+        std::fs::write(
+            dir.path().join("shared/lint.toml"),
+            r#"
+            [feature.lint.dependencies]
+            ruff = "*"
+
+            [feature.lint.tasks]
+            lint = "ruff check"
+            "#,
+        )
+        .unwrap();
+
+        let manifest_path = dir.path().join("pixi.toml");
+        let mut manifest = Manifest::from_str(
+            &manifest_path,
+            r#"
+            include = ["shared/lint.toml"]
+
+            [workspace]
+            name = "foo"
+            channels = []
+            platforms = []
+
+            [environments]
+            lint = ["lint"]
+            "#,
+        )
+        .unwrap();
+        assert!(manifest.feature("lint").is_some());
+        assert_eq!(manifest.includes[0].features, vec!["lint".into()]);
+
+        // Editing the feature edits the included file.
+        manifest
+            .add_task(
+                "fix".into(),
+                crate::Task::Plain("ruff check --fix".into()),
+                None,
+                &"lint".into(),
+            )
+            .unwrap();
+        assert!(manifest.includes[0]
+            .document
+            .to_string()
+            .contains("fix = \"ruff check --fix\""));
+        assert!(!manifest.document.to_string().contains("fix"));
+
+        // A feature can't be defined twice.
+        let err = Manifest::from_str(
+            &manifest_path,
+            r#"
+            include = ["shared/lint.toml"]
+
+            [workspace]
+            name = "foo"
+            channels = []
+            platforms = []
+
+            [feature.lint.dependencies]
+            black = "*"
+            "#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("is defined in both"));
+    }
+
+    #[test]
+    fn test_included_feature_errors_point_into_the_included_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("shared")).unwrap();
+        std::fs::write(
+            dir.path().join("shared/lint.toml"),
+            r#"
+            [feature.lint.target.win-64.dependencies]
+            ruff = "*"
+            "#,
+        )
+        .unwrap();
+
+        let err = Manifest::from_str(
+            &dir.path().join("pixi.toml"),
+            r#"
+            include = ["shared/lint.toml"]
+
+            [workspace]
+            name = "foo"
+            channels = []
+            platforms = ["linux-64"]
+            "#,
+        )
+        .unwrap_err();
+        let span = err
+            .source_code()
+            .unwrap()
+            .read_span(&(0..0).into(), 0, 0)
+            .unwrap();
+        assert_eq!(span.name(), Some("shared/lint.toml"));
+    }
+
+    #[test]
+    fn test_remote_include() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = dir.path().join("pixi.toml");
+        let contents = r#"
+            [feature.lint.dependencies]
+            ruff = "*"
+            "#;
+        let sha256 = compute_bytes_digest::<Sha256>(contents);
+        let source = format!(
+            r#"
+            include = [{{ url = "https://example.invalid/lint.toml", sha256 = "{sha256:x}" }}]
+
+            [workspace]
+            name = "foo"
+            channels = []
+            platforms = ["linux-64"]
+            "#
+        );
+
+        // The remote file is not fetched while loading the manifest.
+        let err = Manifest::from_str(&manifest_path, source.clone()).unwrap_err();
+        let unresolved = err.downcast_ref::<UnresolvedRemoteInclude>().unwrap();
+        assert_eq!(
+            unresolved.include.url.as_str(),
+            "https://example.invalid/lint.toml"
+        );
+        assert_eq!(unresolved.include.sha256, sha256);
+
+        let remote_includes = RemoteIncludes::from([(sha256, contents.to_string())]);
+        let mut manifest =
+            Manifest::from_str_with_remote_includes(&manifest_path, source, &remote_includes)
+                .unwrap();
+        assert!(manifest.feature("lint").is_some());
+        assert!(manifest.includes[0].path.is_none());
+        assert_eq!(
+            manifest.includes[0].location,
+            "https://example.invalid/lint.toml"
+        );
+
+        // A remote file can't be edited, and nothing is changed by trying.
+        let err = manifest
+            .add_dependency(
+                &MatchSpec::from_str("black", ParseStrictness::Strict).unwrap(),
+                SpecType::Run,
+                &[Platform::Linux64],
+                &"lint".into(),
+                DependencyOverwriteBehavior::Overwrite,
+                &ChannelConfig::default_with_root_dir(dir.path().to_path_buf()),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("can't be edited"));
+        assert!(manifest
+            .feature("lint")
+            .unwrap()
+            .targets
+            .for_target(&TargetSelector::Platform(Platform::Linux64))
+            .is_none());
+    }
+
+    #[test]
+    fn test_remote_include_requires_a_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = Manifest::from_str(
+            &dir.path().join("pixi.toml"),
+            r#"
+            include = ["https://example.invalid/lint.toml"]
+
+            [workspace]
+            name = "foo"
+            channels = []
+            platforms = []
+            "#,
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("must be pinned by the sha256 hash of its contents"));
+    }
+}
@@ -10,20 +10,25 @@ use std::{
 
 use indexmap::{Equivalent, IndexSet};
 use itertools::Itertools;
-use miette::{miette, IntoDiagnostic, NamedSource, Report, WrapErr};
+use miette::{miette, IntoDiagnostic, NamedSource, Report, SourceSpan, WrapErr};
 use pixi_spec::PixiSpec;
-use rattler_conda_types::{ChannelConfig, MatchSpec, PackageName, Platform, Version};
+use rattler_conda_types::{
+    ChannelConfig, MatchSpec, PackageName, ParseStrictness, Platform, Version, VersionSpec,
+};
 use toml_edit::{DocumentMut, Value};
 
+use super::members;
 use crate::{
     consts,
-    error::{DependencyError, TomlError, UnknownFeature},
-    manifests::{ManifestSource, PackageManifest},
+    error::{DependencyError, TomlError, UnknownFeature, UnsupportedPixiVersion},
+    fix::FixableError,
+    manifests::{IncludedManifest, ManifestSource, PackageManifest, RemoteIncludes},
     preview::Preview,
     pypi::PyPiPackageName,
     pyproject::{PyProjectManifest, PyProjectToManifestError},
     to_options,
-    toml::{ExternalWorkspaceProperties, TomlDocument, TomlManifest},
+    toml::{ExternalWorkspaceProperties, TomlDocument, TomlManifest, TomlWorkspace},
+    utils::PixiSpanned,
     BuildSystem, DependencyOverwriteBehavior, Environment, EnvironmentName, Feature, FeatureName,
     GetFeatureError, PrioritizedChannel, PypiDependencyLocation, SpecType, TargetSelector, Task,
     TaskName, WorkspaceManifest, WorkspaceTarget,
@@ -72,6 +77,13 @@ pub struct Manifest {
 
     /// Optionally a package manifest
     pub package: Option<PackageManifest>,
+
+    /// The manifests of the members of the workspace, discovered from the
+    /// `members` globs of the `[workspace]` section.
+    pub members: Vec<Manifest>,
+
+    /// The files with features that are included in the manifest.
+    pub includes: Vec<IncludedManifest>,
 }
 
 impl Borrow<WorkspaceManifest> for Manifest {
@@ -83,9 +95,18 @@ impl Borrow<WorkspaceManifest> for Manifest {
 impl Manifest {
     /// Create a new manifest from a path
     pub fn from_path(path: impl AsRef<Path>) -> miette::Result<Self> {
+        Self::from_path_with_remote_includes(path, &RemoteIncludes::default())
+    }
+
+    /// Create a new manifest from a path, taking the contents of the remote
+    /// files it includes from `remote_includes`.
+    pub fn from_path_with_remote_includes(
+        path: impl AsRef<Path>,
+        remote_includes: &RemoteIncludes,
+    ) -> miette::Result<Self> {
         let manifest_path = dunce::canonicalize(path.as_ref()).into_diagnostic()?;
         let contents = std::fs::read_to_string(path.as_ref()).into_diagnostic()?;
-        Self::from_str(manifest_path.as_ref(), contents)
+        Self::from_str_with_remote_includes(manifest_path.as_ref(), contents, remote_includes)
     }
 
     /// Return the toml manifest file name ('pixi.toml' or 'pyproject.toml')
@@ -98,6 +119,30 @@ impl Manifest {
 
     /// Create a new manifest from a string
     pub fn from_str(manifest_path: &Path, contents: impl Into<String>) -> miette::Result<Self> {
+        Self::from_str_with_remote_includes(manifest_path, contents, &RemoteIncludes::default())
+    }
+
+    /// Create a new manifest from a string, taking the contents of the remote
+    /// files it includes from `remote_includes`. An
+    /// [`crate::UnresolvedRemoteInclude`] error is returned for a remote file
+    /// that isn't provided.
+    pub fn from_str_with_remote_includes(
+        manifest_path: &Path,
+        contents: impl Into<String>,
+        remote_includes: &RemoteIncludes,
+    ) -> miette::Result<Self> {
+        Self::load(manifest_path, contents.into(), remote_includes, None)
+    }
+
+    /// Loads a manifest from a string. The manifest of a member of a
+    /// workspace uses `inherited_workspace` if it is given, otherwise the
+    /// workspace is discovered from the parent directories.
+    fn load(
+        manifest_path: &Path,
+        contents: String,
+        remote_includes: &RemoteIncludes,
+        inherited_workspace: Option<&PixiSpanned<TomlWorkspace>>,
+    ) -> miette::Result<Self> {
         let manifest_kind = ManifestKind::try_from_path(manifest_path).ok_or_else(|| {
             miette::miette!("unrecognized manifest file: {}", manifest_path.display())
         })?;
@@ -105,14 +150,42 @@ impl Manifest {
             .parent()
             .expect("manifest_path should always have a parent");
 
-        let contents = contents.into();
+        check_requires_pixi(&manifest_kind, &contents)?;
+
+        let mut is_member = false;
+        let mut includes = None;
+        let mut members_workspace = None;
         let (parsed, file_name) = match manifest_kind {
-            ManifestKind::Pixi => (
-                TomlManifest::from_toml_str(&contents).and_then(|manifest| {
-                    manifest.into_manifests(ExternalWorkspaceProperties::default())
-                }),
-                "pixi.toml",
-            ),
+            ManifestKind::Pixi => {
+                // The manifest of a member of a workspace inherits the `[workspace]`
+                // section of the workspace.
+                let manifest = match TomlManifest::from_toml_str(&contents).map(|mut manifest| {
+                    includes = manifest.include.take().map(|include| include.value);
+                    members_workspace = members::inheritable_workspace(&manifest);
+                    manifest
+                }) {
+                    Ok(manifest) if manifest.workspace.is_none() => {
+                        let workspace = match inherited_workspace {
+                            Some(workspace) => Some(workspace.clone()),
+                            None => members::find_inherited_workspace(root)?,
+                        };
+                        match workspace {
+                            Some(workspace) => {
+                                is_member = true;
+                                Ok(manifest.with_inherited_workspace(workspace))
+                            }
+                            None => Ok(manifest),
+                        }
+                    }
+                    result => result,
+                };
+                (
+                    manifest.and_then(|manifest| {
+                        manifest.into_manifests(ExternalWorkspaceProperties::default())
+                    }),
+                    "pixi.toml",
+                )
+            }
             ManifestKind::Pyproject => {
                 let manifest = match PyProjectManifest::from_toml_str(&contents)
                     .and_then(|m| m.ensure_pixi())
@@ -136,14 +209,70 @@ impl Manifest {
         }) {
             Ok(result) => result,
             Err(e) => {
-                return Err(
-                    Report::from(e).with_source_code(NamedSource::new(file_name, contents.clone()))
-                );
+                let source = NamedSource::new(file_name, contents.clone());
+                return Err(match e.fix() {
+                    Some(fix) => Report::from(
+                        FixableError::new(
+                            e.to_string(),
+                            format!("unknown '{}'", fix.original),
+                            fix,
+                        )
+                        .with_source_code(source),
+                    ),
+                    None => Report::from(e).with_source_code(source),
+                });
             }
         };
 
+        // Add the features of the included files
+        let mut workspace_manifest = workspace_manifest;
+        let mut included_manifests: Vec<IncludedManifest> = Vec::new();
+        for location in includes.into_iter().flatten() {
+            let (included, features) = IncludedManifest::load(
+                root,
+                &location,
+                remote_includes,
+                &workspace_manifest.workspace.preview,
+            )?;
+            for (name, feature) in features {
+                if workspace_manifest.features.contains_key(&name) {
+                    miette::bail!(
+                        help = "define the feature in only one file",
+                        "the feature '{name}' is defined in both '{}' and '{}'",
+                        included_manifests
+                            .iter()
+                            .find(|other| other.features.contains(&name))
+                            .map_or(file_name, |other| other.location.as_str()),
+                        included.location,
+                    );
+                }
+                // Errors in the feature point into the included file.
+                workspace_manifest.validate_feature(&feature, included.source())?;
+                workspace_manifest.features.insert(name, feature);
+            }
+            included_manifests.push(included);
+        }
+
         // Validate the contents of the manifest
         workspace_manifest.validate(NamedSource::new(file_name, contents.to_owned()), root)?;
+        if is_member {
+            members::validate_member(&workspace_manifest).map_err(|e| {
+                Report::from(e).with_source_code(NamedSource::new(file_name, contents.clone()))
+            })?;
+        }
+
+        let members = match &members_workspace {
+            Some(workspace) => Self::load_members(
+                root,
+                &workspace_manifest.workspace.members,
+                workspace,
+                remote_includes,
+            )?,
+            None => Vec::new(),
+        };
+        for member in &members {
+            members::merge_dependencies(&mut workspace_manifest, member)?;
+        }
 
         let source = match manifest_kind {
             ManifestKind::Pixi => ManifestSource::PixiToml(TomlDocument::new(document)),
@@ -156,17 +285,98 @@ impl Manifest {
             document: source,
             workspace: workspace_manifest,
             package: package_manifest,
+            members,
+            includes: included_manifests,
         })
     }
 
+    /// Loads the manifests of the members of the workspace at
+    /// `workspace_root` that match the `members` globs. The members inherit
+    /// `workspace`.
+    fn load_members(
+        workspace_root: &Path,
+        members: &[String],
+        workspace: &PixiSpanned<TomlWorkspace>,
+        remote_includes: &RemoteIncludes,
+    ) -> miette::Result<Vec<Manifest>> {
+        let mut manifests: Vec<Manifest> = Vec::new();
+        for path in members::member_manifest_paths(workspace_root, members)? {
+            let contents = std::fs::read_to_string(&path).into_diagnostic()?;
+            let mut member = Manifest::load(&path, contents, remote_includes, Some(workspace))?;
+            let name = members::member_name(&member);
+            if let Some(other) = manifests
+                .iter()
+                .find(|other| members::member_name(other) == name)
+            {
+                miette::bail!(
+                    "the workspace members '{}' and '{}' have the same name '{name}'",
+                    other.path.display(),
+                    member.path.display()
+                );
+            }
+
+            let member_dir = path
+                .parent()
+                .and_then(|dir| dir.strip_prefix(workspace_root).ok())
+                .unwrap_or(Path::new(""))
+                .to_path_buf();
+            members::relocate_tasks(&mut member.workspace, &member_dir);
+            members::relocate_dependencies(&mut member.workspace, &member_dir);
+            manifests.push(member);
+        }
+        Ok(manifests)
+    }
+
+    /// Returns the member of the workspace with the given name.
+    pub fn member(&self, name: &str) -> Option<&Manifest> {
+        self.members
+            .iter()
+            .find(|member| members::member_name(member) == name)
+    }
+
+    /// Returns the names of the members of the workspace.
+    pub fn member_names(&self) -> impl Iterator<Item = String> + '_ {
+        self.members.iter().map(members::member_name)
+    }
+
     /// Save the manifest to the file and update the contents
     pub fn save(&mut self) -> miette::Result<()> {
         let contents = self.document.to_string();
         std::fs::write(&self.path, &contents).into_diagnostic()?;
         self.contents = Some(contents);
+        for include in &mut self.includes {
+            include.save()?;
+        }
         Ok(())
     }
 
+    /// Returns an error if the given feature is defined in a file that can't
+    /// be edited. This is checked before anything is changed, so a failed
+    /// edit leaves the manifest untouched.
+    fn ensure_editable(&self, feature_name: &FeatureName) -> miette::Result<()> {
+        match self
+            .includes
+            .iter()
+            .find(|include| include.features.contains(feature_name))
+        {
+            Some(include) => include.ensure_editable(),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the document that defines the given feature, this is the
+    /// document of an included file if the feature is defined there.
+    fn document_mut(&mut self, feature_name: &FeatureName) -> miette::Result<&mut ManifestSource> {
+        match self
+            .includes
+            .iter_mut()
+            .find(|include| include.features.contains(feature_name))
+        {
+            Some(include) => include.document_mut(),
+            None => Ok(&mut self.document),
+        }
+    }
+
     /// Returns a hashmap of the tasks that should run only the given platform.
     /// If the platform is `None`, only the default targets tasks are
     /// returned.
@@ -195,6 +405,8 @@ impl Manifest {
         platform: Option<Platform>,
         feature_name: &FeatureName,
     ) -> miette::Result<()> {
+        self.ensure_editable(feature_name)?;
+
         // Check if the task already exists
         if let Ok(tasks) = self.tasks(platform, feature_name) {
             if tasks.contains_key(&name) {
@@ -203,8 +415,12 @@ impl Manifest {
         }
 
         // Add the task to the Toml manifest
-        self.document
-            .add_task(name.as_str(), task.clone(), platform, feature_name)?;
+        self.document_mut(feature_name)?.add_task(
+            name.as_str(),
+            task.clone(),
+            platform,
+            feature_name,
+        )?;
 
         // Add the task to the manifest
         self.get_or_insert_target_mut(platform, Some(feature_name))
@@ -243,6 +459,7 @@ impl Manifest {
             features_source_loc: None,
             solve_group: None,
             no_default_feature,
+            ..Environment::default()
         });
 
         if let Some(solve_group) = solve_group {
@@ -262,12 +479,14 @@ impl Manifest {
         }
 
         // Remove the environment from the internal manifest
-        let environment_idx = self
-            .workspace
-            .environments
-            .by_name
-            .shift_remove(name)
-            .expect("environment should exist");
+        let Some(environment_idx) = self.workspace.environments.by_name.shift_remove(name) else {
+            self.workspace
+                .environments
+                .abstract_environments
+                .shift_remove(name)
+                .expect("environment should exist");
+            return Ok(true);
+        };
 
         // Remove the environment from the solve groups
         self.workspace
@@ -285,13 +504,15 @@ impl Manifest {
         platform: Option<Platform>,
         feature_name: &FeatureName,
     ) -> miette::Result<()> {
+        self.ensure_editable(feature_name)?;
+
         // Check if the task exists
         self.tasks(platform, feature_name)?
             .get(&name)
             .ok_or_else(|| miette::miette!("task {} does not exist", name))?;
 
         // Remove the task from the Toml manifest
-        self.document
+        self.document_mut(feature_name)?
             .remove_task(name.as_str(), platform, feature_name)?;
 
         // Remove the task from the internal manifest
@@ -309,6 +530,8 @@ impl Manifest {
         platforms: impl Iterator<Item = &'a Platform> + Clone,
         feature_name: &FeatureName,
     ) -> miette::Result<()> {
+        self.ensure_editable(feature_name)?;
+
         // Get current and new platforms for the feature
         let current = match feature_name {
             FeatureName::Default => self.workspace.workspace.platforms.get_mut(),
@@ -321,7 +544,9 @@ impl Manifest {
         current.extend(new.clone());
 
         // Then to the TOML document
-        let platforms = self.document.get_array_mut("platforms", feature_name)?;
+        let platforms = self
+            .document_mut(feature_name)?
+            .get_array_mut("platforms", feature_name)?;
         for platform in new.iter() {
             platforms.push(platform.to_string());
         }
@@ -335,6 +560,8 @@ impl Manifest {
         platforms: impl IntoIterator<Item = Platform>,
         feature_name: &FeatureName,
     ) -> miette::Result<()> {
+        self.ensure_editable(feature_name)?;
+
         // Get current platforms and platform to remove for the feature
         let current = match feature_name {
             FeatureName::Default => self.workspace.workspace.platforms.get_mut(),
@@ -359,7 +586,9 @@ impl Manifest {
 
         // And from the TOML document
         let retained = retained.iter().map(|p| p.to_string()).collect_vec();
-        let platforms = self.document.get_array_mut("platforms", feature_name)?;
+        let platforms = self
+            .document_mut(feature_name)?
+            .get_array_mut("platforms", feature_name)?;
         platforms.retain(|x| retained.contains(&x.to_string()));
 
         Ok(())
@@ -375,6 +604,8 @@ impl Manifest {
         overwrite_behavior: DependencyOverwriteBehavior,
         channel_config: &ChannelConfig,
     ) -> miette::Result<bool> {
+        self.ensure_editable(feature_name)?;
+
         // Determine the name of the package to add
         let (Some(name), spec) = spec.clone().into_nameless() else {
             miette::bail!("pixi does not support wildcard dependencies")
@@ -388,7 +619,7 @@ impl Manifest {
                 .try_add_dependency(&name, &spec, spec_type, overwrite_behavior)
             {
                 Ok(true) => {
-                    self.document.add_dependency(
+                    self.document_mut(feature_name)?.add_dependency(
                         &name,
                         &spec,
                         spec_type,
@@ -414,6 +645,8 @@ impl Manifest {
         overwrite_behavior: DependencyOverwriteBehavior,
         location: &Option<PypiDependencyLocation>,
     ) -> miette::Result<bool> {
+        self.ensure_editable(feature_name)?;
+
         let mut any_added = false;
         for platform in crate::to_options(platforms) {
             // Add the pypi dependency to the manifest
@@ -422,7 +655,7 @@ impl Manifest {
                 .try_add_pep508_dependency(requirement, editable, overwrite_behavior)
             {
                 Ok(true) => {
-                    self.document.add_pypi_dependency(
+                    self.document_mut(feature_name)?.add_pypi_dependency(
                         requirement,
                         platform,
                         feature_name,
@@ -446,6 +679,8 @@ impl Manifest {
         platforms: &[Platform],
         feature_name: &FeatureName,
     ) -> miette::Result<()> {
+        self.ensure_editable(feature_name)?;
+
         for platform in crate::to_options(platforms) {
             // Remove the dependency from the manifest
             match self
@@ -466,8 +701,12 @@ impl Manifest {
                 Err(e) => return Err(e.into()),
             };
             // Remove the dependency from the TOML document
-            self.document
-                .remove_dependency(dep, spec_type, platform, feature_name)?;
+            self.document_mut(feature_name)?.remove_dependency(
+                dep,
+                spec_type,
+                platform,
+                feature_name,
+            )?;
         }
         Ok(())
     }
@@ -479,6 +718,8 @@ impl Manifest {
         platforms: &[Platform],
         feature_name: &FeatureName,
     ) -> miette::Result<()> {
+        self.ensure_editable(feature_name)?;
+
         for platform in crate::to_options(platforms) {
             // Remove the dependency from the manifest
             match self
@@ -499,7 +740,7 @@ impl Manifest {
                 Err(e) => return Err(e.into()),
             };
             // Remove the dependency from the TOML document
-            self.document
+            self.document_mut(feature_name)?
                 .remove_pypi_dependency(dep, platform, feature_name)?;
         }
         Ok(())
@@ -555,6 +796,8 @@ impl Manifest {
         feature_name: &FeatureName,
         prepend: bool,
     ) -> miette::Result<()> {
+        self.ensure_editable(feature_name)?;
+
         // First collect all the new channels
         let to_add: IndexSet<_> = channels.into_iter().collect();
 
@@ -589,7 +832,9 @@ impl Manifest {
         *current = final_channels.clone();
 
         // Update the TOML document
-        let channels = self.document.get_array_mut("channels", feature_name)?;
+        let channels = self
+            .document_mut(feature_name)?
+            .get_array_mut("channels", feature_name)?;
         channels.clear();
         for channel in final_channels {
             channels.push(Value::from(channel));
@@ -604,6 +849,8 @@ impl Manifest {
         channels: impl IntoIterator<Item = PrioritizedChannel>,
         feature_name: &FeatureName,
     ) -> miette::Result<()> {
+        self.ensure_editable(feature_name)?;
+
         // Get current channels and channels to remove for the feature
         let current = match feature_name {
             FeatureName::Default => &mut self.workspace.workspace.channels,
@@ -632,7 +879,9 @@ impl Manifest {
         let current_clone = current.clone();
 
         // And from the TOML document
-        let channels = self.document.get_array_mut("channels", feature_name)?;
+        let channels = self
+            .document_mut(feature_name)?
+            .get_array_mut("channels", feature_name)?;
         // clear and recreate from current list
         channels.clear();
         for channel in current_clone.iter() {
@@ -762,6 +1011,54 @@ impl Manifest {
     }
 }
 
+/// Checks the `requires-pixi` field of the manifest before the rest of the
+/// manifest is parsed, so a manifest that uses features of a newer pixi reports
+/// the version it requires instead of a parse error.
+pub(crate) fn check_requires_pixi(
+    manifest_kind: &ManifestKind,
+    contents: &str,
+) -> miette::Result<()> {
+    let Ok(document) = toml_edit::ImDocument::parse(contents) else {
+        return Ok(());
+    };
+    let (pixi_table, file_name) = match manifest_kind {
+        ManifestKind::Pixi => (Some(document.as_item()), consts::PROJECT_MANIFEST),
+        ManifestKind::Pyproject => (
+            document.get("tool").and_then(|tool| tool.get("pixi")),
+            consts::PYPROJECT_MANIFEST,
+        ),
+    };
+    let Some(requires_pixi) = pixi_table.and_then(|table| {
+        ["workspace", "project"]
+            .into_iter()
+            .find_map(|section| table.get(section)?.get("requires-pixi"))
+    }) else {
+        return Ok(());
+    };
+
+    // An invalid requirement is reported when the manifest is parsed.
+    let Some(requirement) = requires_pixi
+        .as_str()
+        .and_then(|spec| VersionSpec::from_str(spec, ParseStrictness::Lenient).ok())
+    else {
+        return Ok(());
+    };
+    let Ok(current) = Version::from_str(consts::PIXI_VERSION) else {
+        return Ok(());
+    };
+    if requirement.matches(&current) {
+        return Ok(());
+    }
+
+    Err(UnsupportedPixiVersion {
+        requirement,
+        current,
+        source_code: NamedSource::new(file_name, contents.to_string()),
+        span: requires_pixi.span().map(SourceSpan::from),
+    }
+    .into())
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -780,7 +1077,7 @@ mod tests {
     use tempfile::tempdir;
 
     use super::*;
-    use crate::channel::PrioritizedChannel;
+    use crate::{channel::PrioritizedChannel, EnvValue};
 
     const PROJECT_BOILERPLATE: &str = r#"
         [project]
@@ -876,6 +1173,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_activation_task_shell_scripts() {
+        let contents = r#"
+            [project]
+            name = "foo"
+            channels = []
+            platforms = ["win-64", "linux-64"]
+
+            [activation]
+            scripts = ["setup.sh"]
+            task-shell-scripts = ["setup.task-shell"]
+
+            [target.win-64.activation]
+            task-shell-scripts = ["windows.task-shell"]
+            "#;
+
+        let manifest = Manifest::from_str(Path::new("pixi.toml"), contents).unwrap();
+        let feature = manifest.default_feature();
+        assert_eq!(
+            feature.activation_task_shell_scripts(Some(Platform::Linux64)),
+            Some(&vec![String::from("setup.task-shell")])
+        );
+        assert_eq!(
+            feature.activation_task_shell_scripts(Some(Platform::Win64)),
+            Some(&vec![String::from("windows.task-shell")])
+        );
+        assert_eq!(
+            feature.activation_scripts(Some(Platform::Win64)),
+            Some(&vec![String::from("setup.sh")])
+        );
+    }
+
     #[test]
     fn test_activation_env() {
         let contents = r#"
@@ -927,21 +1256,21 @@ mod tests {
             default_activation_env,
             Some(&IndexMap::from([(
                 String::from("FOO"),
-                String::from("main")
+                EnvValue::Set(String::from("main"))
             )]))
         );
         assert_eq!(
             win64_activation_env,
             Some(&IndexMap::from([(
                 String::from("FOO"),
-                String::from("win-64")
+                EnvValue::Set(String::from("win-64"))
             )]))
         );
         assert_eq!(
             linux64_activation_env,
             Some(&IndexMap::from([(
                 String::from("FOO"),
-                String::from("linux-64")
+                EnvValue::Set(String::from("linux-64"))
             )]))
         );
 
@@ -972,21 +1301,21 @@ mod tests {
             feature_activation_env,
             Some(&IndexMap::from([(
                 String::from("FOO"),
-                String::from("bar")
+                EnvValue::Set(String::from("bar"))
             )]))
         );
         assert_eq!(
             feature_win64_activation_env,
             Some(&IndexMap::from([(
                 String::from("FOO"),
-                String::from("bar-win-64")
+                EnvValue::Set(String::from("bar-win-64"))
             )]))
         );
         assert_eq!(
             feature_linux64_activation_env,
             Some(&IndexMap::from([(
                 String::from("FOO"),
-                String::from("bar-linux-64")
+                EnvValue::Set(String::from("bar-linux-64"))
             )]))
         );
     }
@@ -1792,6 +2121,103 @@ platforms = ["linux-64", "win-64"]
         );
     }
 
+    #[test]
+    fn test_environments_extends() {
+        let file_contents = r#"
+            [project]
+            name = "foo"
+            channels = ["conda-forge"]
+            platforms = ["linux-64", "osx-arm64", "win-64"]
+
+            [feature.py311.dependencies]
+            python = "~=3.11.0"
+
+            [feature.test.dependencies]
+            pytest = "*"
+
+            [feature.lint.dependencies]
+            ruff = "*"
+
+            [environments]
+            base = { features = ["py311"], abstract = true, platforms = ["linux-64", "osx-arm64"] }
+            test = { features = ["test"], extends = "base", solve-group = "default" }
+            lint = { features = ["lint", "py311"], extends = "test", channels = ["bioconda"] }
+        "#;
+        let manifest = Manifest::from_str(Path::new("pixi.toml"), file_contents).unwrap();
+        let environments = &manifest.workspace.environments;
+
+        // Abstract environments are not installable.
+        assert!(manifest.environment("base").is_none());
+        let base = environments.abstract_environments().exactly_one().unwrap();
+        assert_eq!(base.name.as_str(), "base");
+
+        let test = manifest.environment("test").unwrap();
+        assert_eq!(test.features, vec!["test", "py311"]);
+        assert_eq!(
+            test.extends,
+            Some(EnvironmentName::Named("base".to_string()))
+        );
+        assert_eq!(
+            test.platforms.as_ref().unwrap().value,
+            IndexSet::from([Platform::Linux64, Platform::OsxArm64])
+        );
+        assert!(test.solve_group.is_some());
+
+        // The features of the environment itself come first, inherited
+        // features are not duplicated.
+        let lint = manifest.environment("lint").unwrap();
+        assert_eq!(lint.features, vec!["lint", "py311", "test"]);
+        assert_eq!(lint.solve_group, None);
+        assert!(lint.platforms.is_some());
+        assert_eq!(
+            lint.channels
+                .iter()
+                .flatten()
+                .map(|channel| channel.channel.to_string())
+                .collect::<Vec<_>>(),
+            vec!["bioconda"]
+        );
+
+        let with_environments = |environments: &str| {
+            format!(
+                r#"
+                [project]
+                name = "foo"
+                channels = []
+                platforms = ["linux-64"]
+
+                [feature.test.dependencies]
+
+                [environments]
+                {environments}
+                "#
+            )
+        };
+        let err = Manifest::from_str(
+            Path::new("pixi.toml"),
+            with_environments(
+                r#"a = { features = ["test"], extends = "b" }
+                b = { extends = "a" }"#,
+            ),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("a -> b -> a"), "{err}");
+
+        let err = Manifest::from_str(
+            Path::new("pixi.toml"),
+            with_environments(r#"test = { features = ["test"], extends = "unknown" }"#),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("'unknown' which is not defined"));
+
+        let err = Manifest::from_str(
+            Path::new("pixi.toml"),
+            with_environments(r#"test = { features = ["test"], platforms = ["win-64"] }"#),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("'win-64'"), "{err}");
+    }
+
     #[test]
     fn test_feature_definition() {
         let file_contents = r#"
@@ -2391,4 +2817,36 @@ bar = "*"
         let err = manifest.unwrap_err();
         insta::assert_snapshot!(err, @"source dependencies are used in the feature 'default', but the `pixi-build` preview feature is not enabled");
     }
+
+    #[test]
+    fn test_requires_pixi() {
+        let manifest = |requires_pixi: &str, extra: &str| {
+            Manifest::from_str(
+                Path::new("pixi.toml"),
+                format!(
+                    r#"
+                    [workspace]
+                    name = "foo"
+                    channels = []
+                    platforms = []
+                    requires-pixi = "{requires_pixi}"
+                    {extra}
+                    "#
+                ),
+            )
+        };
+
+        let manifest_ok = manifest(">=0.1", "").unwrap();
+        assert_eq!(
+            manifest_ok.workspace.workspace.requires_pixi,
+            Some(VersionSpec::from_str(">=0.1", ParseStrictness::Strict).unwrap())
+        );
+
+        let err = manifest(">=1000", "").unwrap_err();
+        assert!(err.downcast_ref::<UnsupportedPixiVersion>().is_some());
+
+        // The requirement is reported instead of the fields a newer pixi knows.
+        let err = manifest(">=1000", "new-field = true").unwrap_err();
+        assert!(err.downcast_ref::<UnsupportedPixiVersion>().is_some());
+    }
 }
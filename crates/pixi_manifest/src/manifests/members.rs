@@ -0,0 +1,506 @@
+//! A workspace can consist of multiple members, each with their own
+//! `pixi.toml` in a directory matched by the `members` globs of the
+//! `[workspace]` section. The manifests of the members don't have a
+//! `[workspace]` section, they inherit the one of the workspace so they share
+//! its channels, platforms and lock file.
+
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use itertools::Itertools;
+use miette::{Context, IntoDiagnostic};
+use pixi_spec::{PathSpec, PixiSpec};
+use typed_path::Utf8TypedPathBuf;
+
+use super::manifest::check_requires_pixi;
+use crate::{
+    consts,
+    pypi::pypi_requirement::PyPiRequirement,
+    task::{CmdArgs, Execute},
+    toml::{TomlManifest, TomlWorkspace},
+    utils::PixiSpanned,
+    FeatureName, Manifest, ManifestKind, Task, TomlError, WorkspaceManifest,
+};
+
+/// Returns the paths of the manifests of the members of the workspace at
+/// `workspace_root`, sorted by path.
+pub(crate) fn member_manifest_paths(
+    workspace_root: &Path,
+    members: &[String],
+) -> miette::Result<Vec<PathBuf>> {
+    let globs = members
+        .iter()
+        .map(|member| {
+            let (exclude, pattern) = match member.strip_prefix('!') {
+                Some(pattern) => ("!", pattern),
+                None => ("", member.as_str()),
+            };
+            format!(
+                "{exclude}{}/{}",
+                pattern.trim_end_matches('/'),
+                consts::PROJECT_MANIFEST
+            )
+        })
+        .collect_vec();
+
+    let workspace_manifest = workspace_root.join(consts::PROJECT_MANIFEST);
+    Ok(
+        pixi_glob::find_files(workspace_root, globs.iter().map(String::as_str))
+            .into_diagnostic()
+            .wrap_err("failed to discover the members of the workspace")?
+            .into_iter()
+            .filter(|path| path != &workspace_manifest)
+            .collect(),
+    )
+}
+
+/// Finds the workspace that the manifest in `member_root` is a member of and
+/// returns its `[workspace]` section so the member can inherit it.
+///
+/// Returns `None` if there is no workspace manifest in any of the parent
+/// directories, and an error if the nearest workspace doesn't include the
+/// member.
+pub(crate) fn find_inherited_workspace(
+    member_root: &Path,
+) -> miette::Result<Option<PixiSpanned<TomlWorkspace>>> {
+    for dir in member_root.ancestors().skip(1) {
+        let workspace_manifest = dir.join(consts::PROJECT_MANIFEST);
+        let Ok(contents) = std::fs::read_to_string(&workspace_manifest) else {
+            continue;
+        };
+
+        // The member is used with the workspace, so it requires the same pixi.
+        check_requires_pixi(&ManifestKind::Pixi, &contents)?;
+
+        let Some(mut workspace) = TomlManifest::from_toml_str(&contents)
+            .ok()
+            .and_then(|manifest| manifest.workspace)
+        else {
+            continue;
+        };
+
+        let member_manifest = member_root.join(consts::PROJECT_MANIFEST);
+        if !member_manifest_paths(dir, &workspace.value.members)?.contains(&member_manifest) {
+            miette::bail!(
+                help = "add the directory to the `members` of the `[workspace]` section",
+                "'{}' is not a member of the workspace at '{}'",
+                member_manifest.display(),
+                workspace_manifest.display()
+            );
+        }
+
+        // The members of the workspace are discovered from the workspace itself,
+        // not from each member.
+        workspace.value.members.clear();
+        return Ok(Some(workspace));
+    }
+    Ok(None)
+}
+
+/// Returns the `[workspace]` section of `manifest` as it is inherited by its
+/// members, or `None` if the manifest doesn't define any members.
+///
+/// The members are loaded with this section so the workspace manifest isn't
+/// read and its members aren't discovered again for every member.
+pub(crate) fn inheritable_workspace(manifest: &TomlManifest) -> Option<PixiSpanned<TomlWorkspace>> {
+    let mut workspace = manifest
+        .workspace
+        .clone()
+        .filter(|workspace| !workspace.value.members.is_empty())?;
+    workspace.value.members.clear();
+    Some(workspace)
+}
+
+/// Verifies that the manifest of a member only contains what a member can
+/// define. The features and environments of the workspace are defined by the
+/// workspace manifest.
+pub(crate) fn validate_member(manifest: &WorkspaceManifest) -> Result<(), TomlError> {
+    if manifest.features.len() > 1 {
+        return Err(TomlError::Generic(
+            "the manifest of a workspace member can't define features, define them in the workspace manifest".into(),
+            None,
+        ));
+    }
+    if manifest
+        .environments
+        .iter()
+        .any(|environment| !environment.name.is_default())
+    {
+        return Err(TomlError::Generic(
+            "the manifest of a workspace member can't define environments, define them in the workspace manifest".into(),
+            None,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Makes the tasks of a member run in the directory of the member.
+///
+/// The tasks of a member are defined relative to the member directory while
+/// the workspace runs them relative to the workspace root, so their working
+/// directory, inputs and outputs are moved to `member_dir`.
+pub(crate) fn relocate_tasks(manifest: &mut WorkspaceManifest, member_dir: &Path) {
+    let relocate_globs = |globs: &mut Option<Vec<String>>| {
+        for glob in globs.iter_mut().flatten() {
+            let (exclude, pattern) = match glob.strip_prefix('!') {
+                Some(pattern) => ("!", pattern),
+                None => ("", glob.as_str()),
+            };
+            *glob = format!("{exclude}{}/{pattern}", member_dir.display());
+        }
+    };
+
+    let default_feature = manifest
+        .features
+        .get_mut(&FeatureName::Default)
+        .expect("the default feature should always exist");
+    for target in default_feature.targets.targets_mut() {
+        for task in target.tasks.values_mut() {
+            match task {
+                Task::Plain(cmd) => {
+                    *task = Task::Execute(Execute {
+                        cmd: CmdArgs::Single(std::mem::take(cmd)),
+                        inputs: None,
+                        outputs: None,
+                        depends_on: Vec::new(),
+                        cwd: Some(member_dir.to_path_buf()),
+                        env: None,
+                        description: None,
+                        clean_env: false,
+                    });
+                }
+                Task::Execute(execute) => {
+                    execute.cwd = Some(match &execute.cwd {
+                        Some(cwd) => member_dir.join(cwd),
+                        None => member_dir.to_path_buf(),
+                    });
+                    relocate_globs(&mut execute.inputs);
+                    relocate_globs(&mut execute.outputs);
+                }
+                Task::Alias(_) | Task::Custom(_) => {}
+            }
+        }
+    }
+}
+
+/// Makes the path dependencies of a member relative to the workspace root.
+///
+/// The dependencies of a member are defined relative to the member directory
+/// while the workspace resolves them relative to the workspace root.
+pub(crate) fn relocate_dependencies(manifest: &mut WorkspaceManifest, member_dir: &Path) {
+    let Some(member_dir) = member_dir.to_str() else {
+        return;
+    };
+    let member_dir = Utf8TypedPathBuf::from(member_dir);
+
+    let default_feature = manifest
+        .features
+        .get_mut(&FeatureName::Default)
+        .expect("the default feature should always exist");
+    for target in default_feature.targets.targets_mut() {
+        for spec in target
+            .dependencies
+            .values_mut()
+            .flat_map(IndexMap::values_mut)
+        {
+            if let PixiSpec::Path(PathSpec { path }) = spec {
+                if !path.is_absolute() && !path.as_str().starts_with('~') {
+                    *path = member_dir.join(path.as_str()).normalize();
+                }
+            }
+        }
+        for requirement in target
+            .pypi_dependencies
+            .iter_mut()
+            .flat_map(IndexMap::values_mut)
+        {
+            if let PyPiRequirement::Path { path, .. } = requirement {
+                let relocated = path
+                    .to_str()
+                    .filter(|relative| path.is_relative() && !relative.starts_with('~'))
+                    .map(|relative| PathBuf::from(member_dir.join(relative).normalize().as_str()));
+                if let Some(relocated) = relocated {
+                    *path = relocated;
+                }
+            }
+        }
+    }
+}
+
+/// Adds the dependencies of a member to the default feature of the workspace,
+/// so they are solved together into the lock file of the workspace.
+///
+/// Returns an error if the workspace or another member already requires a
+/// package with a different spec.
+pub(crate) fn merge_dependencies(
+    workspace: &mut WorkspaceManifest,
+    member: &Manifest,
+) -> miette::Result<()> {
+    let name = member_name(member);
+    let default_feature = workspace.default_feature_mut();
+    for (target, selector) in member.workspace.default_feature().targets.iter() {
+        let workspace_target = default_feature
+            .targets
+            .for_opt_target_or_default_mut(selector);
+        for (spec_type, dependencies) in &target.dependencies {
+            let workspace_dependencies =
+                workspace_target.dependencies.entry(*spec_type).or_default();
+            for (package, spec) in dependencies {
+                match workspace_dependencies.get(package) {
+                    Some(existing) if existing != spec => miette::bail!(
+                        help = "require the same spec in the workspace and all of its members",
+                        "the workspace member '{name}' requires '{}' with a different spec than the rest of the workspace",
+                        package.as_normalized()
+                    ),
+                    Some(_) => {}
+                    None => {
+                        workspace_dependencies.insert(package.clone(), spec.clone());
+                    }
+                }
+            }
+        }
+        for (package, requirement) in target.pypi_dependencies.iter().flatten() {
+            let workspace_dependencies = workspace_target
+                .pypi_dependencies
+                .get_or_insert_with(Default::default);
+            match workspace_dependencies.get(package) {
+                Some(existing) if existing != requirement => miette::bail!(
+                    help = "require the same spec in the workspace and all of its members",
+                    "the workspace member '{name}' requires '{}' with a different spec than the rest of the workspace",
+                    package.as_source()
+                ),
+                Some(_) => {}
+                None => {
+                    workspace_dependencies.insert(package.clone(), requirement.clone());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the name of a member, the name of its package or otherwise the
+/// name of its directory.
+pub(crate) fn member_name(member: &Manifest) -> String {
+    member
+        .package
+        .as_ref()
+        .map(|package| package.package.name.clone())
+        .unwrap_or_else(|| {
+            member
+                .path
+                .parent()
+                .and_then(Path::file_name)
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use rattler_conda_types::{PackageName, Platform};
+
+    use super::*;
+    use crate::{pypi::PyPiPackageName, TargetSelector};
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_member_discovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        write(
+            &root.join("pixi.toml"),
+            r#"
+            [workspace]
+            name = "monorepo"
+            channels = ["conda-forge"]
+            platforms = ["linux-64"]
+            members = ["packages/*", "!packages/legacy"]
+            "#,
+        );
+        for member in ["a", "b", "legacy"] {
+            write(
+                &root.join("packages").join(member).join("pixi.toml"),
+                "[tasks]\ntest = \"echo test\"\n",
+            );
+        }
+
+        let members = member_manifest_paths(
+            &root,
+            &["packages/*".to_string(), "!packages/legacy/".to_string()],
+        )
+        .unwrap();
+        assert_eq!(
+            members,
+            vec![
+                root.join("packages/a/pixi.toml"),
+                root.join("packages/b/pixi.toml")
+            ]
+        );
+
+        let workspace = find_inherited_workspace(&root.join("packages/a"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(workspace.value.name.as_deref(), Some("monorepo"));
+        assert!(workspace.value.members.is_empty());
+
+        let err = find_inherited_workspace(&root.join("packages/legacy")).unwrap_err();
+        assert!(err.to_string().contains("is not a member of the workspace"));
+    }
+
+    #[test]
+    fn test_workspace_members() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        write(
+            &root.join("pixi.toml"),
+            r#"
+            [workspace]
+            name = "monorepo"
+            channels = ["conda-forge"]
+            platforms = ["linux-64"]
+            members = ["packages/*"]
+            "#,
+        );
+        write(
+            &root.join("packages/a/pixi.toml"),
+            r#"
+            [tasks]
+            test = "pytest"
+            build = { cmd = "make", cwd = "src", inputs = ["src/*.c", "!src/generated.c"] }
+            "#,
+        );
+
+        let manifest = Manifest::from_path(root.join("pixi.toml")).unwrap();
+        assert_eq!(manifest.member_names().collect::<Vec<_>>(), vec!["a"]);
+
+        let member = manifest.member("a").unwrap();
+        let tasks = member.tasks(None, &FeatureName::Default).unwrap();
+        let test = tasks[&"test".into()].as_execute().unwrap();
+        assert_eq!(test.cwd.as_deref(), Some(Path::new("packages/a")));
+        let build = tasks[&"build".into()].as_execute().unwrap();
+        assert_eq!(build.cwd.as_deref(), Some(Path::new("packages/a/src")));
+        assert_eq!(
+            build.inputs.as_deref().unwrap(),
+            ["packages/a/src/*.c", "!packages/a/src/generated.c"]
+        );
+
+        // A member can't define the environments of the workspace.
+        write(
+            &root.join("packages/b/pixi.toml"),
+            "[environments]\ntest = []\n",
+        );
+        assert!(Manifest::from_path(root.join("pixi.toml")).is_err());
+    }
+
+    #[test]
+    fn test_member_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        write(
+            &root.join("pixi.toml"),
+            r#"
+            [workspace]
+            name = "monorepo"
+            channels = ["conda-forge"]
+            platforms = ["linux-64"]
+            members = ["packages/*"]
+            preview = ["pixi-build"]
+
+            [dependencies]
+            python = "3.12.*"
+            "#,
+        );
+        write(
+            &root.join("packages/a/pixi.toml"),
+            r#"
+            [dependencies]
+            python = "3.12.*"
+            b = { path = "../b" }
+
+            [target.linux-64.pypi-dependencies]
+            c = { path = "../c", editable = true }
+            "#,
+        );
+        write(
+            &root.join("packages/b/pixi.toml"),
+            "[tasks]\ntest = \"echo test\"\n",
+        );
+
+        let manifest = Manifest::from_path(root.join("pixi.toml")).unwrap();
+
+        // The dependencies of the member are resolved relative to the workspace
+        // root, and are solved together with those of the workspace.
+        let default_feature = manifest.default_feature();
+        let dependencies = default_feature
+            .targets
+            .default()
+            .run_dependencies()
+            .unwrap();
+        assert_eq!(
+            dependencies
+                .keys()
+                .map(PackageName::as_normalized)
+                .collect_vec(),
+            ["python", "b"]
+        );
+        assert_eq!(
+            dependencies[&PackageName::new_unchecked("b")],
+            PixiSpec::Path(PathSpec {
+                path: Utf8TypedPathBuf::from("packages/b")
+            })
+        );
+        let pypi_dependencies = default_feature
+            .targets
+            .for_target(&TargetSelector::Platform(Platform::Linux64))
+            .and_then(|target| target.pypi_dependencies.as_ref())
+            .unwrap();
+        assert_matches!(
+            &pypi_dependencies[&"c".parse::<PyPiPackageName>().unwrap()],
+            PyPiRequirement::Path { path, .. } if path == Path::new("packages/c")
+        );
+
+        // The member itself still runs its tasks.
+        assert!(manifest.member("b").is_some());
+
+        // A member can't require a different spec than the workspace.
+        write(
+            &root.join("packages/b/pixi.toml"),
+            "[dependencies]\npython = \"3.11.*\"\n",
+        );
+        let err = Manifest::from_path(root.join("pixi.toml")).unwrap_err();
+        assert!(err.to_string().contains("with a different spec"));
+    }
+
+    #[test]
+    fn test_member_requires_pixi_of_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        write(
+            &root.join("pixi.toml"),
+            r#"
+            [workspace]
+            name = "monorepo"
+            channels = ["conda-forge"]
+            platforms = ["linux-64"]
+            members = ["packages/*"]
+            requires-pixi = ">=1000"
+            "#,
+        );
+        write(
+            &root.join("packages/a/pixi.toml"),
+            "[tasks]\ntest = \"echo test\"\n",
+        );
+
+        let err = Manifest::from_path(root.join("packages/a/pixi.toml")).unwrap_err();
+        assert!(err
+            .downcast_ref::<crate::UnsupportedPixiVersion>()
+            .is_some());
+    }
+}
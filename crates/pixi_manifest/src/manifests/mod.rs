@@ -10,11 +10,15 @@
 
 pub mod project;
 
+mod format;
+mod include;
 mod manifest;
+mod members;
 mod package;
 mod source;
 mod workspace;
 
+pub use include::{IncludedManifest, RemoteInclude, RemoteIncludes};
 pub use manifest::{Manifest, ManifestKind};
 pub use package::PackageManifest;
 pub use source::ManifestSource;
@@ -5,6 +5,7 @@ use pixi_spec::PixiSpec;
 use rattler_conda_types::{PackageName, Platform};
 use toml_edit::{value, Array, Item, Table, Value};
 
+use super::format::format_pixi_table;
 use crate::toml::TomlDocument;
 use crate::{
     manifests::project::TableName, pypi::PyPiPackageName, FeatureName, PyPiRequirement,
@@ -394,6 +395,28 @@ impl ManifestSource {
     pub fn set_version(&mut self, version: &str) {
         self.as_table_mut()["project"]["version"] = value(version);
     }
+
+    /// Formats the pixi tables of the manifest: the tables are put in a
+    /// canonical order, the dependencies are sorted and the specs are written
+    /// in their shortest form. For a 'pyproject.toml' only the `[tool.pixi]`
+    /// section is formatted.
+    pub fn format(&mut self) {
+        match self {
+            ManifestSource::PixiToml(document) => {
+                format_pixi_table(document.as_table_mut(), true);
+            }
+            ManifestSource::PyProjectToml(document) => {
+                if let Some(pixi) = document
+                    .as_table_mut()
+                    .get_mut("tool")
+                    .and_then(|tool| tool.get_mut("pixi"))
+                    .and_then(Item::as_table_mut)
+                {
+                    format_pixi_table(pixi, false);
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
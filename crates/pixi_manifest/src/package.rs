@@ -1,6 +1,8 @@
 use std::path::PathBuf;
 
-use rattler_conda_types::Version;
+use indexmap::IndexMap;
+use pixi_spec::PixiSpec;
+use rattler_conda_types::{PackageName, Version};
 use url::Url;
 
 /// Defines the contents of the `[package]` section of the project manifest.
@@ -35,4 +37,8 @@ pub struct Package {
 
     /// URL of the project documentation
     pub documentation: Option<Url>,
+
+    /// Optional sets of run dependencies that users of the package can opt
+    /// into, keyed by the name of the extra.
+    pub extras: IndexMap<String, IndexMap<PackageName, PixiSpec>>,
 }
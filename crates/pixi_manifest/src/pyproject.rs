@@ -76,7 +76,8 @@ impl PyProjectManifest {
         if self.name().is_none() {
             let span = self
                 .pixi_manifest()
-                .and_then(|manifest| manifest.workspace.span());
+                .and_then(|manifest| manifest.workspace.as_ref())
+                .and_then(|workspace| workspace.span());
             return Err(TomlError::MissingField("name".into(), span));
         }
 
@@ -90,7 +91,8 @@ impl PyProjectManifest {
     pub fn name(&self) -> Option<&str> {
         if let Some(pixi_name) = self
             .pixi_manifest()
-            .and_then(|p| p.workspace.value.name.as_deref())
+            .and_then(|p| p.workspace.as_ref())
+            .and_then(|workspace| workspace.value.name.as_deref())
         {
             return Some(pixi_name);
         }
@@ -145,38 +147,31 @@ impl PyProjectManifest {
 
     /// Builds a list of pixi environments from pyproject groups of optional
     /// dependencies and/or dependency groups:
-    ///  - one environment is created per group with the same name
+    ///  - one environment is created per group with the same, normalized, name
     ///  - each environment includes the feature of the same name
-    ///  - it will also include other features inferred from any self references
-    ///    to other groups of optional dependencies (but won't for dependency
-    ///    groups, as recursion between groups is resolved upstream)
+    ///  - it will also include other features inferred from any (transitive)
+    ///    self references to other groups of optional dependencies (but won't
+    ///    for dependency groups, as their `include-group` references are
+    ///    already resolved into the feature)
     pub fn environments_from_extras(&self) -> Result<HashMap<String, Vec<String>>, Pep735Error> {
         let mut environments = HashMap::new();
-        if let Some(extras) = self.optional_dependencies() {
-            let pname = self.package_name();
-            for (extra, reqs) in extras {
-                let mut features = vec![extra.to_string()];
-                // Add any references to other groups of extra dependencies
-                for req in reqs.iter() {
-                    if pname.as_ref() == Some(&req.name) {
-                        for extra in &req.extras {
-                            features.push(extra.to_string())
-                        }
-                    }
-                }
-                // Environments can only contain number, strings and dashes
-                environments.insert(extra.replace('_', "-").clone(), features);
-            }
+        let extras = self.optional_dependencies().unwrap_or_default();
+        let pname = self.package_name();
+        for (extra, reqs) in extras.iter() {
+            let (_, referenced_extras) = expand_self_references(reqs, &extras, pname.as_ref());
+            let features = std::iter::once(extra.clone())
+                .chain(referenced_extras)
+                .collect();
+            environments.insert(normalize_environment_name(extra), features);
         }
 
         if let Some(groups) = self.dependency_groups().transpose()? {
             for group in groups.into_keys() {
-                let normalised = group.replace('_', "-");
                 // Nothing to do if a group of optional dependencies has the same name as the
                 // dependency group
-                if !environments.contains_key(&normalised) {
-                    environments.insert(normalised.clone(), vec![normalised]);
-                }
+                environments
+                    .entry(normalize_environment_name(&group))
+                    .or_insert_with(|| vec![group]);
             }
         }
 
@@ -184,6 +179,63 @@ impl PyProjectManifest {
     }
 }
 
+/// Normalizes the name of a group of dependencies into the name of an
+/// environment, which can only contain lowercase letters, numbers and dashes.
+/// This follows the normalization of names in PEP 735.
+fn normalize_environment_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
+    }
+    normalized
+}
+
+/// Expands the references to extras of the project itself, e.g.
+/// `project[test]`, in a group of requirements into the requirements of those
+/// extras, recursively.
+///
+/// Returns the requirements without the self references and without
+/// duplicates, together with the names of the referenced extras as they are
+/// defined in `[project.optional-dependencies]`.
+fn expand_self_references(
+    requirements: &[Requirement],
+    extras: &IndexMap<String, Vec<Requirement>>,
+    project_name: Option<&pep508_rs::PackageName>,
+) -> (Vec<Requirement>, Vec<String>) {
+    let mut expanded = Vec::new();
+    let mut referenced_extras: Vec<String> = Vec::new();
+    let mut stack = vec![requirements];
+    while let Some(requirements) = stack.pop() {
+        for requirement in requirements {
+            if project_name != Some(&requirement.name) {
+                if !expanded.contains(requirement) {
+                    expanded.push(requirement.clone());
+                }
+                continue;
+            }
+
+            for extra in &requirement.extras {
+                let Some((name, reqs)) = extras.iter().find(|(name, _)| {
+                    pep508_rs::ExtraName::from_str(name).is_ok_and(|name| &name == extra)
+                }) else {
+                    continue;
+                };
+                if !referenced_extras.contains(name) {
+                    referenced_extras.push(name.clone());
+                    stack.push(reqs.as_slice());
+                }
+            }
+        }
+    }
+    (expanded, referenced_extras)
+}
+
 #[derive(Debug, Error, Diagnostic)]
 pub enum PyProjectToManifestError {
     #[error("The [tool.pixi] table is missing")]
@@ -309,24 +361,33 @@ impl PyProjectManifest {
             }
         }
 
-        // Define an iterator over both optional dependencies and dependency groups
-        let groups = project
-            .optional_dependencies
-            .into_iter()
-            .chain(
-                dependency_groups
-                    .as_ref()
-                    .map(DependencyGroups::resolve)
-                    .transpose()?,
-            )
-            .flat_map(|map| map.into_iter());
+        // Collect both the optional dependencies and the dependency groups, the
+        // `include-group` references of the latter are resolved upstream.
+        let extras = project.optional_dependencies.unwrap_or_default();
+        let dependency_groups = dependency_groups
+            .as_ref()
+            .map(DependencyGroups::resolve)
+            .transpose()?
+            .unwrap_or_default();
 
-        // For each group of optional dependency or dependency group,
-        // create a feature of the same name if it does not exist,
-        // and add pypi dependencies, filtering out self-references in optional
-        // dependencies
+        // Merge groups with the same name and expand the self references to other
+        // groups of optional dependencies, so each feature can be used on its own.
         let project_name =
             pep508_rs::PackageName::new(workspace_manifest.workspace.name.clone()).ok();
+        let mut groups: IndexMap<&String, Vec<Requirement>> = IndexMap::new();
+        for (group, reqs) in extras.iter().chain(dependency_groups.iter()) {
+            let (reqs, _) = expand_self_references(reqs, &extras, project_name.as_ref());
+            let group_reqs = groups.entry(group).or_default();
+            for requirement in reqs {
+                if !group_reqs.contains(&requirement) {
+                    group_reqs.push(requirement);
+                }
+            }
+        }
+
+        // For each group of optional dependency or dependency group,
+        // create a feature of the same name if it does not exist,
+        // and add pypi dependencies
         for (group, reqs) in groups {
             let feature_name = FeatureName::Named(group.to_string());
             let target = workspace_manifest
@@ -336,18 +397,15 @@ impl PyProjectManifest {
                 .targets
                 .default_mut();
             for requirement in reqs.iter() {
-                // filter out any self references in groups of extra dependencies
-                if project_name.as_ref() != Some(&requirement.name) {
-                    target
-                        .try_add_pep508_dependency(
-                            requirement,
-                            None,
-                            DependencyOverwriteBehavior::Error,
-                        )
-                        .map_err(|err| {
-                            PyProjectToManifestError::DependencyError(requirement.clone(), err)
-                        })?;
-                }
+                target
+                    .try_add_pep508_dependency(
+                        requirement,
+                        None,
+                        DependencyOverwriteBehavior::Error,
+                    )
+                    .map_err(|err| {
+                        PyProjectToManifestError::DependencyError(requirement.clone(), err)
+                    })?;
             }
         }
 
@@ -391,6 +449,7 @@ mod tests {
     use std::{path::Path, str::FromStr};
 
     use insta::assert_snapshot;
+    use itertools::Itertools;
     use pep440_rs::VersionSpecifiers;
     use rattler_conda_types::{ParseStrictness, VersionSpec};
 
@@ -645,6 +704,50 @@ mod tests {
         assert_snapshot!(manifest.document.to_string());
     }
 
+    #[test]
+    fn test_dependency_groups_and_extras() {
+        let source = r#"
+        [project]
+        name = "project"
+        dependencies = []
+
+        [project.optional-dependencies]
+        test = ["pytest"]
+        all = ["project[test]", "numpy"]
+
+        [dependency-groups]
+        Lint_Tools = ["ruff"]
+        dev = [{include-group = "Lint_Tools"}, "ruff", "project[all]"]
+
+        [tool.pixi.project]
+        channels = []
+        platforms = []
+        "#;
+        let manifest = Manifest::from_str(Path::new("pyproject.toml"), source).unwrap();
+        let pypi_names = |feature: &str| {
+            manifest
+                .feature(feature)
+                .unwrap()
+                .pypi_dependencies(None)
+                .unwrap()
+                .keys()
+                .map(|name| name.as_source().to_string())
+                .sorted()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pypi_names("all"), vec!["numpy", "pytest"]);
+        assert_eq!(pypi_names("Lint_Tools"), vec!["ruff"]);
+        assert_eq!(pypi_names("dev"), vec!["numpy", "pytest", "ruff"]);
+
+        let environments = super::PyProjectManifest::from_toml_str(source)
+            .unwrap()
+            .environments_from_extras()
+            .unwrap();
+        assert_eq!(environments["all"], vec!["all", "test"]);
+        assert_eq!(environments["lint-tools"], vec!["Lint_Tools"]);
+        assert_eq!(environments["dev"], vec!["dev"]);
+    }
+
     #[test]
     fn test_version_url_to_matchspec() {
         fn cmp(v1: &str, v2: &str) {
@@ -1,6 +1,7 @@
 use miette::Diagnostic;
-use rattler_conda_types::Version;
-use rattler_virtual_packages::{Cuda, LibC, Linux, Osx, VirtualPackage};
+use pixi_default_versions::{default_glibc_version, default_linux_version, default_mac_os_version};
+use rattler_conda_types::{Platform, Version};
+use rattler_virtual_packages::{Archspec, Cuda, LibC, Linux, Osx, VirtualPackage};
 use serde::Deserialize;
 use serde_with::{serde_as, DisplayFromStr};
 use std::str::FromStr;
@@ -53,6 +54,60 @@ impl SystemRequirements {
         result
     }
 
+    /// Returns a reasonable modern set of virtual packages that should be safe
+    /// enough to assume for the given platform. At the time of writing, this
+    /// is in sync with the conda-lock set of minimal virtual packages.
+    /// <https://github.com/conda/conda-lock/blob/3d36688278ebf4f65281de0846701d61d6017ed2/conda_lock/virtual_package.py#L175>
+    ///
+    /// The versions of the virtual packages are raised to these system
+    /// requirements.
+    pub fn minimal_virtual_packages(&self, platform: Platform) -> Vec<VirtualPackage> {
+        // TODO: How to add a default cuda requirements
+        let mut virtual_packages: Vec<VirtualPackage> = vec![];
+
+        // Match high level platforms
+        if platform.is_unix() {
+            virtual_packages.push(VirtualPackage::Unix);
+        }
+        if platform.is_linux() {
+            let version = self.linux.clone().unwrap_or(default_linux_version());
+            virtual_packages.push(VirtualPackage::Linux(Linux { version }));
+
+            let (family, version) = self
+                .libc
+                .as_ref()
+                .map(LibCSystemRequirement::family_and_version)
+                .map(|(family, version)| (family.to_string(), version.clone()))
+                .unwrap_or((GLIBC_FAMILY.to_string(), default_glibc_version()));
+            virtual_packages.push(VirtualPackage::LibC(LibC { family, version }));
+        }
+
+        if platform.is_windows() {
+            virtual_packages.push(VirtualPackage::Win);
+        }
+
+        // Add platform specific packages
+        if platform.is_osx() {
+            let version = self
+                .macos
+                .clone()
+                .unwrap_or_else(|| default_mac_os_version(platform));
+            virtual_packages.push(VirtualPackage::Osx(Osx { version }));
+        }
+
+        // Cuda
+        if let Some(version) = self.cuda.clone() {
+            virtual_packages.push(VirtualPackage::Cuda(Cuda { version }));
+        }
+
+        // Archspec is only based on the platform for now
+        if let Some(spec) = Archspec::from_platform(platform) {
+            virtual_packages.push(VirtualPackage::Archspec(spec));
+        }
+
+        virtual_packages
+    }
+
     /// Returns the combination of two system requirements.
     ///
     /// If both system requirements specify the same virtual package, the highest version is taken.
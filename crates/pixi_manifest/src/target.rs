@@ -3,7 +3,10 @@ use std::{borrow::Cow, collections::HashMap, str::FromStr};
 use indexmap::{map::Entry, IndexMap};
 use itertools::Either;
 use pixi_spec::PixiSpec;
-use rattler_conda_types::{PackageName, Platform};
+use rattler_conda_types::{
+    version_spec::ParseVersionSpecError, GenericVirtualPackage, PackageName, ParseStrictness,
+    Platform, VersionSpec,
+};
 use serde::{Deserialize, Deserializer};
 
 use super::error::DependencyError;
@@ -12,7 +15,7 @@ use crate::{
     pypi::PyPiPackageName,
     task::{Task, TaskName},
     utils::PixiSpanned,
-    DependencyOverwriteBehavior, PyPiRequirement, SpecType,
+    DependencyOverwriteBehavior, PyPiRequirement, SpecType, SystemRequirements,
 };
 
 /// A workspace target describes the dependencies, activations and task
@@ -355,8 +358,8 @@ impl PackageTarget {
     }
 }
 
-/// Represents a target selector. Currently we only support explicit platform
-/// selection.
+/// Represents a target selector. A target is selected by the platform or by
+/// a virtual package of the environment that is solved.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum TargetSelector {
     // Platform specific configuration
@@ -365,11 +368,16 @@ pub enum TargetSelector {
     Linux,
     Win,
     MacOs,
+    // Configuration for environments with a certain virtual package
+    VirtualPackage(VirtualPackageSelector),
     // TODO: Add minijinja coolness here.
 }
 
 impl TargetSelector {
     /// Returns true if this selector matches the given platform.
+    ///
+    /// A virtual package selector never matches a platform on its own, use
+    /// [`Self::matches_virtual_packages`] to also match those.
     pub fn matches(&self, platform: Platform) -> bool {
         match self {
             TargetSelector::Platform(p) => p == &platform,
@@ -377,6 +385,20 @@ impl TargetSelector {
             TargetSelector::Unix => platform.is_unix(),
             TargetSelector::Win => platform.is_windows(),
             TargetSelector::MacOs => platform.is_osx(),
+            TargetSelector::VirtualPackage(_) => false,
+        }
+    }
+
+    /// Returns true if this selector matches the given platform or one of the
+    /// virtual packages of the environment for that platform.
+    pub fn matches_virtual_packages(
+        &self,
+        platform: Platform,
+        virtual_packages: &[GenericVirtualPackage],
+    ) -> bool {
+        match self {
+            TargetSelector::VirtualPackage(selector) => selector.matches(virtual_packages),
+            _ => self.matches(platform),
         }
     }
 }
@@ -389,6 +411,7 @@ impl std::fmt::Display for TargetSelector {
             TargetSelector::Unix => write!(f, "unix"),
             TargetSelector::Win => write!(f, "win"),
             TargetSelector::MacOs => write!(f, "osx"),
+            TargetSelector::VirtualPackage(selector) => write!(f, "{}", selector),
         }
     }
 }
@@ -410,13 +433,132 @@ impl<'de> Deserialize<'de> for TargetSelector {
             "unix" => Ok(TargetSelector::Unix),
             "win" => Ok(TargetSelector::Win),
             "osx" => Ok(TargetSelector::MacOs),
-            _ => Platform::from_str(&s)
-                .map(TargetSelector::Platform)
-                .map_err(serde::de::Error::custom),
+            _ => match Platform::from_str(&s) {
+                Ok(platform) => Ok(TargetSelector::Platform(platform)),
+                Err(_) if VirtualPackageSelector::is_virtual_package_selector(&s) => s
+                    .parse()
+                    .map(TargetSelector::VirtualPackage)
+                    .map_err(serde::de::Error::custom),
+                Err(err) => Err(serde::de::Error::custom(err)),
+            },
         }
     }
 }
 
+/// The virtual packages that can be selected without the `__` prefix.
+const KNOWN_VIRTUAL_PACKAGES: [&str; 5] = ["cuda", "glibc", "linux", "osx", "archspec"];
+
+/// Selects a target based on a virtual package of the environment, e.g.
+/// `cuda>=12` or `__glibc>=2.28`.
+///
+/// The virtual packages of an environment follow from its system
+/// requirements, see [`SystemRequirements::minimal_virtual_packages`].
+#[derive(Debug, Clone)]
+pub struct VirtualPackageSelector {
+    /// The name of the virtual package, including the `__` prefix.
+    pub name: String,
+
+    /// The version that the virtual package must match, if `None` any version
+    /// matches.
+    pub version: Option<VersionSpec>,
+
+    /// The selector as it was written in the manifest.
+    source: String,
+}
+
+impl VirtualPackageSelector {
+    /// Returns true if the string looks like it selects a virtual package.
+    fn is_virtual_package_selector(s: &str) -> bool {
+        let (name, _) = split_virtual_package_selector(s);
+        name.starts_with("__") || KNOWN_VIRTUAL_PACKAGES.contains(&name)
+    }
+
+    /// Returns true if any of the virtual packages matches this selector.
+    pub fn matches(&self, virtual_packages: &[GenericVirtualPackage]) -> bool {
+        virtual_packages.iter().any(|package| {
+            package.name.as_normalized() == self.name
+                && self
+                    .version
+                    .as_ref()
+                    .map_or(true, |version| version.matches(&package.version))
+        })
+    }
+}
+
+/// Splits a virtual package selector in the name and the version spec.
+fn split_virtual_package_selector(s: &str) -> (&str, &str) {
+    let s = s.trim();
+    let end = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(s.len());
+    (&s[..end], s[end..].trim())
+}
+
+/// An error that occurs when parsing a [`VirtualPackageSelector`].
+#[derive(Debug, thiserror::Error)]
+pub enum ParseVirtualPackageSelectorError {
+    #[error(
+        "'{0}' is not a known virtual package, prefix it with `__` to select any virtual package"
+    )]
+    UnknownVirtualPackage(String),
+
+    #[error("invalid version spec in target selector '{0}'")]
+    InvalidVersionSpec(String, #[source] ParseVersionSpecError),
+}
+
+impl FromStr for VirtualPackageSelector {
+    type Err = ParseVirtualPackageSelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, version) = split_virtual_package_selector(s);
+        let name = if name.starts_with("__") && name.len() > 2 {
+            name.to_string()
+        } else if KNOWN_VIRTUAL_PACKAGES.contains(&name) {
+            format!("__{name}")
+        } else {
+            return Err(ParseVirtualPackageSelectorError::UnknownVirtualPackage(
+                name.to_string(),
+            ));
+        };
+
+        let version = if version.is_empty() {
+            None
+        } else {
+            Some(
+                VersionSpec::from_str(version, ParseStrictness::Lenient).map_err(|err| {
+                    ParseVirtualPackageSelectorError::InvalidVersionSpec(s.to_string(), err)
+                })?,
+            )
+        };
+
+        Ok(Self {
+            name,
+            version,
+            source: s.trim().to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for VirtualPackageSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl PartialEq for VirtualPackageSelector {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for VirtualPackageSelector {}
+
+impl std::hash::Hash for VirtualPackageSelector {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.source.hash(state)
+    }
+}
+
 /// A collect of targets including a default target.
 #[derive(Debug, Clone, Default)]
 pub struct Targets<T> {
@@ -507,6 +649,42 @@ impl<T> Targets<T> {
             .rev()
     }
 
+    /// Returns all the targets that apply for the given platform in an
+    /// environment with the given system requirements.
+    ///
+    /// This is similar to [`Self::resolve`] but also includes the targets
+    /// that are selected by one of the virtual packages that follow from the
+    /// system requirements, see
+    /// [`SystemRequirements::minimal_virtual_packages`].
+    pub fn resolve_with_system_requirements(
+        &self,
+        platform: Option<Platform>,
+        system_requirements: &SystemRequirements,
+    ) -> impl DoubleEndedIterator<Item = &'_ T> + '_ {
+        let Some(platform) = platform else {
+            return Either::Right(std::iter::once(&self.default_target));
+        };
+
+        let virtual_packages = system_requirements
+            .minimal_virtual_packages(platform)
+            .into_iter()
+            .map(GenericVirtualPackage::from)
+            .collect::<Vec<_>>();
+        Either::Left(
+            std::iter::once(&self.default_target)
+                .chain(
+                    self.targets
+                        .iter()
+                        .filter(move |(selector, _)| {
+                            selector.matches_virtual_packages(platform, &virtual_packages)
+                        })
+                        .map(|(_, target)| target),
+                )
+                // We reverse this to get the most specific selector first.
+                .rev(),
+        )
+    }
+
     /// Returns the target for the given target selector.
     pub fn for_target(&self, target: &TargetSelector) -> Option<&T> {
         self.targets.get(target)
@@ -576,6 +754,11 @@ impl<T> Targets<T> {
         std::iter::once(&self.default_target).chain(self.targets.iter().map(|(_, target)| target))
     }
 
+    /// Returns a mutable iterator over all targets.
+    pub fn targets_mut(&mut self) -> impl Iterator<Item = &'_ mut T> + '_ {
+        std::iter::once(&mut self.default_target).chain(self.targets.values_mut())
+    }
+
     /// Returns user defined target selectors
     pub fn user_defined_selectors(&self) -> impl Iterator<Item = &TargetSelector> + '_ {
         self.targets.keys()
@@ -589,12 +772,14 @@ impl<T> Targets<T> {
 
 #[cfg(test)]
 mod tests {
-    use std::path::Path;
+    use std::{path::Path, str::FromStr};
 
     use insta::assert_snapshot;
     use itertools::Itertools;
 
-    use crate::Manifest;
+    use rattler_conda_types::{Platform, Version};
+
+    use crate::{Manifest, SpecType, SystemRequirements};
 
     #[test]
     fn test_targets_overwrite_order() {
@@ -635,4 +820,104 @@ mod tests {
         build = ==1.0
         "###);
     }
+
+    #[test]
+    fn test_virtual_package_target_selectors() {
+        let manifest = Manifest::from_str(
+            Path::new("pixi.toml"),
+            r#"
+        [project]
+        name = "test"
+        channels = []
+        platforms = ["linux-64", "osx-arm64"]
+
+        [dependencies]
+        pytorch = "*"
+
+        [target."cuda>=12".dependencies]
+        pytorch-cuda = "12.*"
+
+        [target."__glibc>=2.28".pypi-dependencies]
+        manylinux-only = "*"
+        "#,
+        )
+        .unwrap();
+        let feature = manifest.default_feature();
+        let names = |platform, system_requirements: &SystemRequirements| {
+            let conda = feature
+                .dependencies_with_system_requirements(
+                    SpecType::Run,
+                    Some(platform),
+                    system_requirements,
+                )
+                .unwrap_or_default()
+                .keys()
+                .map(|name| name.as_source().to_string())
+                .collect_vec();
+            let pypi = feature
+                .pypi_dependencies_with_system_requirements(Some(platform), system_requirements)
+                .unwrap_or_default()
+                .keys()
+                .map(|name| name.as_source().to_string())
+                .collect_vec();
+            conda.into_iter().chain(pypi).collect_vec()
+        };
+
+        let cuda = SystemRequirements {
+            cuda: Some(Version::from_str("12.2").unwrap()),
+            ..SystemRequirements::default()
+        };
+        assert_eq!(
+            names(Platform::Linux64, &SystemRequirements::default()),
+            vec!["pytorch", "manylinux-only"]
+        );
+        assert_eq!(
+            names(Platform::Linux64, &cuda),
+            vec!["pytorch", "pytorch-cuda", "manylinux-only"]
+        );
+        assert_eq!(
+            names(Platform::OsxArm64, &cuda),
+            vec!["pytorch", "pytorch-cuda"]
+        );
+
+        // Without a platform only the default target is used.
+        assert_eq!(
+            feature
+                .dependencies_with_system_requirements(SpecType::Run, None, &cuda)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Unknown selectors are still reported as invalid platforms.
+        assert!(Manifest::from_str(
+            Path::new("pixi.toml"),
+            r#"
+        [project]
+        name = "test"
+        channels = []
+        platforms = ["linux-64"]
+
+        [target."cudaa>=12".dependencies]
+        foo = "*"
+        "#,
+        )
+        .is_err());
+
+        // Virtual package targets can only add dependencies.
+        let err = Manifest::from_str(
+            Path::new("pixi.toml"),
+            r#"
+        [project]
+        name = "test"
+        channels = []
+        platforms = ["linux-64"]
+
+        [target."cuda".tasks]
+        train = "python train.py"
+        "#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("can only define dependencies"));
+    }
 }
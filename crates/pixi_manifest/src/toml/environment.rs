@@ -1,16 +1,34 @@
-use crate::utils::PixiSpanned;
+use indexmap::{IndexMap, IndexSet};
+use itertools::Itertools;
+use rattler_conda_types::Platform;
 use serde::{Deserialize, Deserializer};
 
+use crate::{
+    toml::TomlPrioritizedChannel, utils::PixiSpanned, Environment, EnvironmentName, TomlError,
+};
+
 /// Helper struct to deserialize the environment from TOML.
 /// The environment description can only hold these values.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct TomlEnvironment {
     #[serde(default)]
     pub features: PixiSpanned<Vec<String>>,
     pub solve_group: Option<String>,
-    #[serde(default)]
-    pub no_default_feature: bool,
+
+    /// Whether to exclude the default feature, inherited from the extended
+    /// environment when not set.
+    pub no_default_feature: Option<bool>,
+
+    /// The environment whose features, platforms and channels are inherited.
+    pub extends: Option<PixiSpanned<EnvironmentName>>,
+
+    /// Whether the environment only exists to be extended.
+    #[serde(default, rename = "abstract")]
+    pub is_abstract: bool,
+
+    pub platforms: Option<PixiSpanned<IndexSet<Platform>>>,
+    pub channels: Option<Vec<TomlPrioritizedChannel>>,
 }
 
 #[derive(Debug)]
@@ -19,6 +37,20 @@ pub enum TomlEnvironmentList {
     Seq(Vec<String>),
 }
 
+impl TomlEnvironmentList {
+    /// Returns the environment, a list of features is an environment that
+    /// only sets its features.
+    pub fn into_environment(self) -> TomlEnvironment {
+        match self {
+            TomlEnvironmentList::Map(env) => env,
+            TomlEnvironmentList::Seq(features) => TomlEnvironment {
+                features: PixiSpanned::from(features),
+                ..TomlEnvironment::default()
+            },
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for TomlEnvironmentList {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -31,3 +63,131 @@ impl<'de> Deserialize<'de> for TomlEnvironmentList {
             .deserialize(deserializer)
     }
 }
+
+/// Resolves the environments of a manifest, an environment that extends
+/// another environment inherits its features, platforms and channels.
+///
+/// The default environment is added first if it is not defined. Each
+/// environment is returned together with the name of its solve group.
+pub(crate) fn resolve_environments(
+    environments: IndexMap<EnvironmentName, TomlEnvironmentList>,
+) -> Result<Vec<(Environment, Option<String>)>, TomlError> {
+    let mut toml_environments = IndexMap::new();
+    if !environments.contains_key(&EnvironmentName::Default) {
+        toml_environments.insert(EnvironmentName::Default, TomlEnvironment::default());
+    }
+    toml_environments.extend(
+        environments
+            .into_iter()
+            .map(|(name, env)| (name, env.into_environment())),
+    );
+
+    let mut resolved = IndexMap::new();
+    for name in toml_environments.keys() {
+        resolve_environment(name, &toml_environments, &mut resolved, &mut Vec::new())?;
+    }
+
+    toml_environments
+        .into_iter()
+        .map(|(name, env)| {
+            let environment = resolved
+                .swap_remove(&name)
+                .expect("all environments have been resolved");
+            if environment.is_abstract && name.is_default() {
+                return Err(TomlError::Generic(
+                    "the default environment cannot be abstract".into(),
+                    env.features.span,
+                ));
+            }
+            if environment.is_abstract && env.solve_group.is_some() {
+                return Err(TomlError::Generic(
+                    format!(
+                        "the abstract environment '{name}' cannot be part of a solve group because it is never solved"
+                    )
+                    .into(),
+                    env.features.span,
+                ));
+            }
+            Ok((environment, env.solve_group))
+        })
+        .collect()
+}
+
+/// Resolves the environment `name` and the environments it extends into
+/// `resolved`. `extending` holds the environments that are being resolved
+/// and is used to detect cycles.
+fn resolve_environment(
+    name: &EnvironmentName,
+    toml_environments: &IndexMap<EnvironmentName, TomlEnvironment>,
+    resolved: &mut IndexMap<EnvironmentName, Environment>,
+    extending: &mut Vec<EnvironmentName>,
+) -> Result<(), TomlError> {
+    if resolved.contains_key(name) {
+        return Ok(());
+    }
+
+    let env = &toml_environments[name];
+    let mut environment = Environment {
+        name: name.clone(),
+        features: env.features.value.clone(),
+        features_source_loc: env.features.span.clone(),
+        solve_group: None,
+        no_default_feature: env.no_default_feature.unwrap_or_default(),
+        extends: None,
+        is_abstract: env.is_abstract,
+        platforms: env.platforms.clone(),
+        channels: env
+            .channels
+            .clone()
+            .map(|channels| channels.into_iter().map(Into::into).collect()),
+    };
+
+    if let Some(PixiSpanned {
+        value: parent_name,
+        span,
+    }) = &env.extends
+    {
+        if !toml_environments.contains_key(parent_name) {
+            return Err(TomlError::Generic(
+                format!(
+                    "the environment '{name}' extends the environment '{parent_name}' which is not defined"
+                )
+                .into(),
+                span.clone(),
+            ));
+        }
+
+        extending.push(name.clone());
+        if extending.contains(parent_name) {
+            return Err(TomlError::Generic(
+                format!(
+                    "the environments extend each other in a cycle: {}",
+                    extending.iter().chain([parent_name]).format(" -> ")
+                )
+                .into(),
+                span.clone(),
+            ));
+        }
+        resolve_environment(parent_name, toml_environments, resolved, extending)?;
+        extending.pop();
+
+        // The features of the environment itself take precedence, so they come
+        // before the features that are inherited.
+        let parent = &resolved[parent_name];
+        let inherited_features = parent
+            .features
+            .iter()
+            .filter(|feature| !environment.features.contains(feature))
+            .cloned()
+            .collect::<Vec<_>>();
+        environment.features.extend(inherited_features);
+        environment.no_default_feature =
+            env.no_default_feature.unwrap_or(parent.no_default_feature);
+        environment.platforms = environment.platforms.or_else(|| parent.platforms.clone());
+        environment.channels = environment.channels.or_else(|| parent.channels.clone());
+        environment.extends = Some(parent_name.clone());
+    }
+
+    resolved.insert(name.clone(), environment);
+    Ok(())
+}
@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 
 use indexmap::{IndexMap, IndexSet};
-use rattler_conda_types::Platform;
+use pixi_spec::PixiSpec;
+use rattler_conda_types::{PackageName, Platform};
 use rattler_solve::ChannelPriority;
 use serde::Deserialize;
 use serde_with::serde_as;
@@ -36,6 +37,12 @@ pub struct TomlFeature {
     pub build_dependencies: Option<PixiSpanned<UniquePackageMap>>,
     #[serde(default)]
     pub pypi_dependencies: Option<IndexMap<PyPiPackageName, PyPiRequirement>>,
+    #[serde(default)]
+    pub constraints: Option<PixiSpanned<UniquePackageMap>>,
+    #[serde(default)]
+    pub pypi_overrides: Option<IndexMap<PyPiPackageName, PyPiRequirement>>,
+    #[serde(default)]
+    pub pypi_constraints: Option<IndexMap<PyPiPackageName, PyPiRequirement>>,
 
     /// Additional information to activate an environment.
     #[serde(default)]
@@ -78,7 +85,37 @@ impl TomlFeature {
             channel_priority: self.channel_priority,
             system_requirements: self.system_requirements,
             pypi_options: self.pypi_options,
+            constraints: into_constraints(self.constraints)?,
+            pypi_overrides: self.pypi_overrides,
+            pypi_constraints: self.pypi_constraints,
             targets: Targets::from_default_and_user_defined(default_target, targets),
         })
     }
 }
+
+/// Converts a `[constraints]` table. Constraints restrict the versions of
+/// packages from channels, so they cannot refer to source packages.
+pub(super) fn into_constraints(
+    constraints: Option<PixiSpanned<UniquePackageMap>>,
+) -> Result<Option<IndexMap<PackageName, PixiSpec>>, TomlError> {
+    let Some(PixiSpanned {
+        value: constraints,
+        span,
+    }) = constraints
+    else {
+        return Ok(None);
+    };
+
+    if let Some((name, _)) = constraints.iter().find(|(_, spec)| spec.is_source()) {
+        return Err(TomlError::Generic(
+            format!(
+                "the constraint on '{}' refers to a source package, only packages from channels can be constrained",
+                name.as_source()
+            )
+            .into(),
+            span,
+        ));
+    }
+
+    Ok(Some(constraints.into()))
+}
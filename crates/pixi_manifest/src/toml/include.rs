@@ -0,0 +1,127 @@
+use std::fmt::{Display, Formatter};
+
+use indexmap::IndexMap;
+use rattler_digest::Sha256Hash;
+use serde::Deserialize;
+use serde_with::serde_as;
+use url::Url;
+
+use crate::{toml::TomlFeature, FeatureName, TomlError};
+
+/// Raw representation of a file that is included by a manifest through its
+/// `include` field. Included files can only define features.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct TomlInclude {
+    /// The features defined in the included file.
+    #[serde(default)]
+    pub feature: IndexMap<FeatureName, TomlFeature>,
+
+    /// The URI for the schema which is unused by pixi
+    #[serde(rename = "$schema")]
+    pub _schema: Option<String>,
+}
+
+impl TomlInclude {
+    /// Parses a toml string into an included file.
+    pub fn from_toml_str(source: &str) -> Result<Self, TomlError> {
+        toml_edit::de::from_str(source).map_err(|err| TomlError::from_deserialize(err, source))
+    }
+}
+
+/// The location of a file in the `include` field of a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TomlIncludeLocation {
+    /// A path relative to the manifest.
+    Path(String),
+
+    /// A remote file, which must be pinned by the sha256 hash of its contents
+    /// to be included.
+    Url {
+        url: Url,
+        sha256: Option<Sha256Hash>,
+    },
+}
+
+impl Display for TomlIncludeLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TomlIncludeLocation::Path(path) => write!(f, "{path}"),
+            TomlIncludeLocation::Url { url, .. } => write!(f, "{url}"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TomlIncludeLocation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[serde_as]
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct TomlRemoteInclude {
+            url: Url,
+            #[serde_as(
+                as = "Option<rattler_digest::serde::SerializableHash::<rattler_digest::Sha256>>"
+            )]
+            sha256: Option<Sha256Hash>,
+        }
+
+        serde_untagged::UntaggedEnumVisitor::new()
+            .string(|value| match Url::parse(value) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {
+                    Ok(TomlIncludeLocation::Url { url, sha256: None })
+                }
+                _ => Ok(TomlIncludeLocation::Path(value.to_string())),
+            })
+            .map(|map| {
+                let TomlRemoteInclude { url, sha256 } = map.deserialize()?;
+                Ok(TomlIncludeLocation::Url { url, sha256 })
+            })
+            .expecting("a path, a url or a table with the `url` and `sha256` of a remote file")
+            .deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_include_location() {
+        let parse = |source: &str| {
+            toml_edit::de::from_str::<IndexMap<String, TomlIncludeLocation>>(source)
+                .map(|mut locations| locations.swap_remove("include").unwrap())
+        };
+
+        assert_eq!(
+            parse(r#"include = "../shared/lint.toml""#).unwrap(),
+            TomlIncludeLocation::Path("../shared/lint.toml".to_string())
+        );
+
+        let sha256 = "c6f51a8b2a5ac3e3b3e4fb2ba5a0f5fa46bd0fcbf6d0c6ba6a84de1d1c1e5c9a";
+        let location = parse(&format!(
+            r#"include = {{ url = "https://example.com/lint.toml", sha256 = "{sha256}" }}"#
+        ))
+        .unwrap();
+        let TomlIncludeLocation::Url {
+            url,
+            sha256: Some(hash),
+        } = location
+        else {
+            panic!("expected a pinned remote include");
+        };
+        assert_eq!(url.as_str(), "https://example.com/lint.toml");
+        assert_eq!(format!("{hash:x}"), sha256);
+
+        // A url without a hash is accepted, it is rejected when it is included.
+        assert_eq!(
+            parse(r#"include = "https://example.com/lint.toml""#).unwrap(),
+            TomlIncludeLocation::Url {
+                url: "https://example.com/lint.toml".parse().unwrap(),
+                sha256: None
+            }
+        );
+    }
+}
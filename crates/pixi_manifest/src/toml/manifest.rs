@@ -11,8 +11,10 @@ use crate::{
     manifests::PackageManifest,
     pypi::{pypi_options::PypiOptions, PyPiPackageName},
     toml::{
-        environment::TomlEnvironmentList, ExternalPackageProperties, ExternalWorkspaceProperties,
-        PackageError, TomlFeature, TomlPackage, TomlTarget, TomlWorkspace, WorkspaceError,
+        environment::{resolve_environments, TomlEnvironmentList},
+        feature::into_constraints,
+        ExternalPackageProperties, ExternalWorkspaceProperties, PackageError, TomlFeature,
+        TomlIncludeLocation, TomlPackage, TomlTarget, TomlWorkspace, WorkspaceError,
     },
     utils::{package_map::UniquePackageMap, PixiSpanned},
     Activation, BuildSystem, Environment, EnvironmentName, Environments, Feature, FeatureName,
@@ -26,11 +28,18 @@ use crate::{
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct TomlManifest {
-    #[serde(alias = "project")]
-    pub workspace: PixiSpanned<TomlWorkspace>,
+    /// The workspace section, this is only optional for the manifests of the
+    /// members of a workspace which inherit it from the workspace manifest.
+    #[serde(default, alias = "project")]
+    pub workspace: Option<PixiSpanned<TomlWorkspace>>,
 
     pub package: Option<PixiSpanned<TomlPackage>>,
 
+    /// Files with features that are included in the manifest, as paths
+    /// relative to the manifest or pinned urls.
+    #[serde(default)]
+    pub include: Option<PixiSpanned<Vec<TomlIncludeLocation>>>,
+
     #[serde(default)]
     pub system_requirements: SystemRequirements,
 
@@ -60,6 +69,18 @@ pub struct TomlManifest {
     #[serde(default)]
     pub pypi_dependencies: Option<IndexMap<PyPiPackageName, PyPiRequirement>>,
 
+    /// Constraints on conda packages that apply to all environments.
+    #[serde(default)]
+    pub constraints: Option<PixiSpanned<UniquePackageMap>>,
+
+    /// Overrides of pypi requirements that apply to all environments.
+    #[serde(default)]
+    pub pypi_overrides: Option<IndexMap<PyPiPackageName, PyPiRequirement>>,
+
+    /// Constraints on pypi packages that apply to all environments.
+    #[serde(default)]
+    pub pypi_constraints: Option<IndexMap<PyPiPackageName, PyPiRequirement>>,
+
     /// Additional information to activate an environment.
     #[serde(default)]
     pub activation: Option<Activation>,
@@ -96,14 +117,25 @@ pub struct TomlManifest {
 impl TomlManifest {
     /// Parses a toml string into a project manifest.
     pub fn from_toml_str(source: &str) -> Result<Self, TomlError> {
-        toml_edit::de::from_str(source).map_err(TomlError::from)
+        toml_edit::de::from_str(source).map_err(|err| TomlError::from_deserialize(err, source))
     }
 
     pub fn is_pixi_build_enabled(&self) -> bool {
-        self.workspace
-            .value
-            .preview
-            .is_enabled(KnownPreviewFeature::PixiBuild)
+        self.workspace.as_ref().is_some_and(|workspace| {
+            workspace
+                .value
+                .preview
+                .is_enabled(KnownPreviewFeature::PixiBuild)
+        })
+    }
+
+    /// Uses the given workspace section if the manifest doesn't define one.
+    /// This is used for the manifests of the members of a workspace.
+    pub fn with_inherited_workspace(self, workspace: PixiSpanned<TomlWorkspace>) -> Self {
+        Self {
+            workspace: self.workspace.or(Some(workspace)),
+            ..self
+        }
     }
 
     /// Check if some dependency types are used which will not be used.
@@ -172,10 +204,24 @@ impl TomlManifest {
         external: ExternalWorkspaceProperties,
     ) -> Result<(WorkspaceManifest, Option<PackageManifest>), TomlError> {
         self.check_dependency_usage()?;
-
-        let preview = &self.workspace.value.preview;
         let pixi_build_enabled = self.is_pixi_build_enabled();
 
+        // Included files are resolved when the manifest is loaded from disk.
+        if let Some(include) = self.include {
+            return Err(TomlError::Generic(
+                "`include` is only supported in a `pixi.toml`".into(),
+                include.span,
+            ));
+        }
+
+        let PixiSpanned {
+            span: workspace_span,
+            value: workspace,
+        } = self
+            .workspace
+            .ok_or_else(|| TomlError::MissingField("[workspace]".into(), None))?;
+        let preview = &workspace.preview;
+
         let default_top_level_target = TomlTarget {
             dependencies: self.dependencies,
             host_dependencies: self.host_dependencies,
@@ -208,7 +254,7 @@ impl TomlManifest {
             platforms: None,
             channels: None,
 
-            channel_priority: self.workspace.value.channel_priority,
+            channel_priority: workspace.channel_priority,
 
             system_requirements: self.system_requirements,
 
@@ -216,6 +262,10 @@ impl TomlManifest {
             // the default feature
             pypi_options: self.pypi_options,
 
+            constraints: into_constraints(self.constraints)?,
+            pypi_overrides: self.pypi_overrides,
+            pypi_constraints: self.pypi_constraints,
+
             // Combine the default target with all user specified targets
             targets: Targets::from_default_and_user_defined(
                 default_workspace_target,
@@ -240,35 +290,23 @@ impl TomlManifest {
         let mut environments = Environments::default();
         let mut solve_groups = SolveGroups::default();
 
-        // Add the default environment first if it was not redefined.
-        if !self.environments.contains_key(&EnvironmentName::Default) {
-            environments.environments.push(Some(Environment::default()));
-            environments
-                .by_name
-                .insert(EnvironmentName::Default, EnvironmentIdx(0));
-        }
-
-        // Add all named environments
-        for (name, env) in self.environments {
-            // Decompose the TOML
-            let (features, features_source_loc, solve_group, no_default_feature) = match env {
-                TomlEnvironmentList::Map(env) => (
-                    env.features.value,
-                    env.features.span,
-                    env.solve_group,
-                    env.no_default_feature,
-                ),
-                TomlEnvironmentList::Seq(features) => (features, None, None, false),
-            };
+        for (environment, solve_group) in resolve_environments(self.environments)? {
+            // Abstract environments are only kept to show what they contain,
+            // they are never locked or installed.
+            if environment.is_abstract {
+                environments
+                    .abstract_environments
+                    .insert(environment.name.clone(), environment);
+                continue;
+            }
 
             let environment_idx = EnvironmentIdx(environments.environments.len());
-            environments.by_name.insert(name.clone(), environment_idx);
+            environments
+                .by_name
+                .insert(environment.name.clone(), environment_idx);
             environments.environments.push(Some(Environment {
-                name,
-                features,
-                features_source_loc,
                 solve_group: solve_group.map(|sg| solve_groups.add(sg, environment_idx)),
-                no_default_feature,
+                ..environment
             }));
         }
 
@@ -279,10 +317,6 @@ impl TomlManifest {
             .and_then(|p| p.value.name.as_ref())
             .cloned();
 
-        let PixiSpanned {
-            span: workspace_span,
-            value: workspace,
-        } = self.workspace;
         let workspace = workspace
             .into_workspace(ExternalWorkspaceProperties {
                 name: project_name.or(external.name),
@@ -338,6 +372,9 @@ impl TomlManifest {
                     PackageError::MissingVersion => {
                         TomlError::MissingField("version".into(), package_span)
                     }
+                    e @ PackageError::InvalidExtraName(_) => {
+                        TomlError::Generic(e.to_string().into(), package_span)
+                    }
                 })?;
 
             Some(PackageManifest {
@@ -499,6 +536,57 @@ mod test {
         assert_eq!(workspace_manifest.workspace.name, "foo");
     }
 
+    #[test]
+    fn test_package_extras() {
+        let manifest = |extras: &str| {
+            TomlManifest::from_toml_str(&format!(
+                r#"
+        [workspace]
+        channels = []
+        platforms = []
+        preview = ["pixi-build"]
+
+        [package]
+        name = "foo"
+        version = "0.1.0"
+
+        {extras}
+
+        [build-system]
+        dependencies = ["python-build-backend > 12"]
+        build-backend = "python-build-backend"
+        channels = []
+        "#
+            ))
+            .and_then(|manifest| manifest.into_manifests(ExternalWorkspaceProperties::default()))
+        };
+
+        let (_, package) = manifest(
+            r#"
+        [package.extras]
+        gpu = { pytorch-cuda = "12.*" }
+        "#,
+        )
+        .unwrap();
+        let extras = package.unwrap().package.extras;
+        assert_eq!(
+            extras["gpu"]
+                .keys()
+                .map(|name| name.as_source())
+                .collect::<Vec<_>>(),
+            vec!["pytorch-cuda"]
+        );
+
+        let err = manifest(
+            r#"
+        [package.extras]
+        GPU = { pytorch-cuda = "12.*" }
+        "#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("the extra 'GPU'"));
+    }
+
     #[test]
     fn test_run_dependencies_without_pixi_build() {
         assert_snapshot!(expect_parse_failure(
@@ -2,6 +2,7 @@ mod channel;
 mod document;
 mod environment;
 mod feature;
+mod include;
 mod manifest;
 mod package;
 mod target;
@@ -11,6 +12,7 @@ pub use channel::TomlPrioritizedChannel;
 pub use document::TomlDocument;
 pub use environment::{TomlEnvironment, TomlEnvironmentList};
 pub use feature::TomlFeature;
+pub use include::{TomlInclude, TomlIncludeLocation};
 pub use manifest::TomlManifest;
 pub use package::{ExternalPackageProperties, PackageError, TomlPackage};
 pub use target::TomlTarget;
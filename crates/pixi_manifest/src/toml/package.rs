@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use indexmap::IndexMap;
 use rattler_conda_types::Version;
 use serde::Deserialize;
 use serde_with::{serde_as, DisplayFromStr};
@@ -8,6 +9,7 @@ use url::Url;
 
 use crate::package::Package;
 use crate::toml::workspace::ExternalWorkspaceProperties;
+use crate::utils::package_map::UniquePackageMap;
 
 /// The TOML representation of the `[workspace]` section in a pixi manifest.
 ///
@@ -32,6 +34,11 @@ pub struct TomlPackage {
     pub homepage: Option<Url>,
     pub repository: Option<Url>,
     pub documentation: Option<Url>,
+
+    /// Optional sets of run dependencies of the package, keyed by the name of
+    /// the extra.
+    #[serde(default)]
+    pub extras: IndexMap<String, UniquePackageMap>,
 }
 
 /// Defines some of the properties that might be defined in other parts of the
@@ -76,6 +83,9 @@ pub enum PackageError {
 
     #[error("missing `version` in `[package]` section")]
     MissingVersion,
+
+    #[error("the extra '{0}' in the `[package]` section can only contain lowercase letters, numbers and dashes")]
+    InvalidExtraName(String),
 }
 
 impl TomlPackage {
@@ -92,6 +102,10 @@ impl TomlPackage {
             .or(external.version)
             .ok_or(PackageError::MissingVersion)?;
 
+        if let Some(name) = self.extras.keys().find(|name| !is_valid_extra_name(name)) {
+            return Err(PackageError::InvalidExtraName(name.clone()));
+        }
+
         Ok(Package {
             name,
             version,
@@ -103,6 +117,20 @@ impl TomlPackage {
             homepage: self.homepage.or(external.homepage),
             repository: self.repository.or(external.repository),
             documentation: self.documentation.or(external.documentation),
+            extras: self
+                .extras
+                .into_iter()
+                .map(|(name, dependencies)| (name, dependencies.into()))
+                .collect(),
         })
     }
 }
+
+/// Returns true if the name of an extra only consists of lowercase letters,
+/// numbers and dashes, which is the normalized form of names in PEP 685.
+fn is_valid_extra_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
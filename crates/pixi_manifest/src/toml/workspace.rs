@@ -1,7 +1,7 @@
 use std::{collections::HashMap, path::PathBuf};
 
 use indexmap::IndexSet;
-use rattler_conda_types::{NamedChannelOrUrl, Platform, Version};
+use rattler_conda_types::{NamedChannelOrUrl, Platform, Version, VersionSpec};
 use rattler_solve::ChannelPriority;
 use serde::Deserialize;
 use serde_with::{serde_as, DisplayFromStr};
@@ -9,8 +9,8 @@ use thiserror::Error;
 use url::Url;
 
 use crate::{
-    preview::Preview, pypi::pypi_options::PypiOptions, utils::PixiSpanned, PrioritizedChannel,
-    Workspace,
+    preview::Preview, pypi::pypi_options::PypiOptions, utils::PixiSpanned, AuditPolicy,
+    PrioritizedChannel, Workspace,
 };
 
 /// The TOML representation of the `[[workspace]]` section in a pixi manifest.
@@ -42,6 +42,12 @@ pub struct TomlWorkspace {
     pub documentation: Option<Url>,
     pub conda_pypi_map: Option<HashMap<NamedChannelOrUrl, String>>,
     pub pypi_options: Option<PypiOptions>,
+    pub audit: Option<AuditPolicy>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub requires_pixi: Option<VersionSpec>,
+
+    #[serde(default)]
+    pub members: Vec<String>,
 
     #[serde(default)]
     pub preview: Preview,
@@ -95,6 +101,9 @@ impl TomlWorkspace {
             platforms: self.platforms,
             conda_pypi_map: self.conda_pypi_map,
             pypi_options: self.pypi_options,
+            audit: self.audit,
+            requires_pixi: self.requires_pixi,
+            members: self.members,
             preview: self.preview,
         })
     }
@@ -9,6 +9,7 @@ use std::{
 
 use super::pypi::pypi_options::PypiOptions;
 use crate::{
+    fix::{did_you_mean, similar_names, unambiguous_name, FixableError, ManifestFix},
     Environment, Feature, FeatureName, KnownPreviewFeature, SystemRequirements, TargetSelector,
     WorkspaceManifest,
 };
@@ -18,73 +19,12 @@ impl WorkspaceManifest {
     pub fn validate(&self, source: NamedSource<String>, root_folder: &Path) -> miette::Result<()> {
         // Check if the targets are defined for existing platforms
         for feature in self.features.values() {
-            let platforms = feature
-                .platforms
-                .as_ref()
-                .unwrap_or(&self.workspace.platforms);
-            for target_sel in feature.targets.user_defined_selectors() {
-                match target_sel {
-                    TargetSelector::Platform(p) => {
-                        if !platforms.as_ref().contains(p) {
-                            return Err(create_unsupported_platform_report(
-                                source,
-                                feature.targets.source_loc(target_sel).unwrap_or_default(),
-                                &[p],
-                                feature,
-                            ));
-                        }
-                    }
-                    TargetSelector::Linux => {
-                        if !platforms.as_ref().iter().any(|p| p.is_linux()) {
-                            return Err(create_unsupported_platform_report(
-                                source,
-                                feature.targets.source_loc(target_sel).unwrap_or_default(),
-                                &[
-                                    &Platform::Linux64,
-                                    &Platform::LinuxAarch64,
-                                    &Platform::LinuxPpc64le,
-                                ],
-                                feature,
-                            ));
-                        }
-                    }
-                    TargetSelector::MacOs => {
-                        if !platforms.as_ref().iter().any(|p| p.is_osx()) {
-                            return Err(create_unsupported_platform_report(
-                                source,
-                                feature.targets.source_loc(target_sel).unwrap_or_default(),
-                                &[&Platform::OsxArm64, &Platform::Osx64],
-                                feature,
-                            ));
-                        }
-                    }
-                    TargetSelector::Win => {
-                        if !platforms.as_ref().iter().any(|p| p.is_windows()) {
-                            return Err(create_unsupported_platform_report(
-                                source,
-                                feature.targets.source_loc(target_sel).unwrap_or_default(),
-                                &[&Platform::Win64, &Platform::WinArm64],
-                                feature,
-                            ));
-                        }
-                    }
-                    TargetSelector::Unix => {
-                        if !platforms.as_ref().iter().any(|p| p.is_unix()) {
-                            return Err(create_unsupported_platform_report(
-                                source,
-                                feature.targets.source_loc(target_sel).unwrap_or_default(),
-                                &[&Platform::Linux64],
-                                feature,
-                            ));
-                        }
-                    }
-                }
-            }
+            self.validate_feature(feature, source.clone())?;
         }
 
         // Check if all features are used in environments, warn if not.
         let mut features_used = HashSet::new();
-        for env in self.environments.iter() {
+        for env in self.all_environments() {
             for feature in env.features.iter() {
                 features_used.insert(feature);
             }
@@ -127,9 +67,32 @@ impl WorkspaceManifest {
         check_file_existence(&self.workspace.readme)?;
 
         // Validate the environments defined in the project
-        for env in self.environments.iter() {
+        for env in self.all_environments() {
             if let Err(report) = self.validate_environment(env, self.default_feature()) {
-                return Err(report.with_source_code(source));
+                return Err(match report.downcast::<FixableError>() {
+                    Ok(err) => err.with_source_code(source).into(),
+                    Err(report) => report.with_source_code(source),
+                });
+            }
+        }
+
+        // The environments in a solve group are solved together, so they have
+        // to use the same channels.
+        for solve_group in self.solve_groups.iter() {
+            let mut environments = solve_group
+                .environments
+                .iter()
+                .map(|&idx| &self.environments[idx]);
+            if let Some(first) = environments.next() {
+                if let Some(env) = environments.find(|env| env.channels != first.channels) {
+                    return Err(miette::miette!(
+                        help = "set the same `channels` on all the environments in the solve group",
+                        "the environments '{}' and '{}' are in the solve group '{}' but use different channels",
+                        first.name,
+                        env.name,
+                        solve_group.name
+                    ));
+                }
             }
         }
 
@@ -171,6 +134,104 @@ impl WorkspaceManifest {
         Ok(())
     }
 
+    /// Validates the targets of a feature, errors are reported against
+    /// `source`, which is the file that defines the feature.
+    pub(crate) fn validate_feature(
+        &self,
+        feature: &Feature,
+        source: NamedSource<String>,
+    ) -> miette::Result<()> {
+        let platforms = feature
+            .platforms
+            .as_ref()
+            .unwrap_or(&self.workspace.platforms);
+        for target_sel in feature.targets.user_defined_selectors() {
+            match target_sel {
+                TargetSelector::Platform(p) => {
+                    if !platforms.as_ref().contains(p) {
+                        return Err(create_unsupported_platform_report(
+                            source,
+                            feature.targets.source_loc(target_sel).unwrap_or_default(),
+                            &[p],
+                            feature,
+                        ));
+                    }
+                }
+                TargetSelector::Linux => {
+                    if !platforms.as_ref().iter().any(|p| p.is_linux()) {
+                        return Err(create_unsupported_platform_report(
+                            source,
+                            feature.targets.source_loc(target_sel).unwrap_or_default(),
+                            &[
+                                &Platform::Linux64,
+                                &Platform::LinuxAarch64,
+                                &Platform::LinuxPpc64le,
+                            ],
+                            feature,
+                        ));
+                    }
+                }
+                TargetSelector::MacOs => {
+                    if !platforms.as_ref().iter().any(|p| p.is_osx()) {
+                        return Err(create_unsupported_platform_report(
+                            source,
+                            feature.targets.source_loc(target_sel).unwrap_or_default(),
+                            &[&Platform::OsxArm64, &Platform::Osx64],
+                            feature,
+                        ));
+                    }
+                }
+                TargetSelector::Win => {
+                    if !platforms.as_ref().iter().any(|p| p.is_windows()) {
+                        return Err(create_unsupported_platform_report(
+                            source,
+                            feature.targets.source_loc(target_sel).unwrap_or_default(),
+                            &[&Platform::Win64, &Platform::WinArm64],
+                            feature,
+                        ));
+                    }
+                }
+                TargetSelector::Unix => {
+                    if !platforms.as_ref().iter().any(|p| p.is_unix()) {
+                        return Err(create_unsupported_platform_report(
+                            source,
+                            feature.targets.source_loc(target_sel).unwrap_or_default(),
+                            &[&Platform::Linux64],
+                            feature,
+                        ));
+                    }
+                }
+                TargetSelector::VirtualPackage(_) => {
+                    // Only the solve knows the virtual packages of an
+                    // environment, so these targets can only add dependencies.
+                    let target = feature
+                        .targets
+                        .for_target(target_sel)
+                        .expect("the selector is user defined");
+                    if target.activation.is_some() || !target.tasks.is_empty() {
+                        let span = feature.targets.source_loc(target_sel).unwrap_or_default();
+                        return Err(miette::miette!(
+                            labels = vec![LabeledSpan::at(span, "selects a virtual package")],
+                            help = "move the activation and tasks to a platform target",
+                            "the target '{}' can only define dependencies",
+                            target_sel
+                        )
+                        .with_source_code(source));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the environments including the abstract environments.
+    fn all_environments(&self) -> impl Iterator<Item = &Environment> + '_ {
+        self.environments
+            .iter()
+            .chain(self.environments.abstract_environments())
+    }
+
     /// Validates that the given environment is valid.
     fn validate_environment(
         &self,
@@ -199,14 +260,32 @@ impl WorkspaceManifest {
             match self.features.get(&FeatureName::Named(feature.clone())) {
                 Some(feature) => features.push(feature),
                 None => {
+                    let span = env.features_source_loc.clone().unwrap_or_default();
+                    let message =
+                        format!("the feature '{feature}' is not defined in the project manifest");
+                    let known_features = self.features.keys().filter_map(FeatureName::name);
+                    if let Some(replacement) = unambiguous_name(feature, known_features.clone()) {
+                        return Err(FixableError::new(
+                            message,
+                            format!("unknown feature '{feature}'"),
+                            ManifestFix {
+                                span,
+                                original: feature.clone(),
+                                replacement: replacement.to_string(),
+                            },
+                        )
+                        .into());
+                    }
                     return Err(miette::miette!(
                         labels = vec![LabeledSpan::at(
-                            env.features_source_loc.clone().unwrap_or_default(),
+                            span,
                             format!("unknown feature '{}'", feature)
                         )],
-                        help = "add the feature to the project manifest",
-                        "the feature '{}' is not defined in the project manifest",
-                        feature
+                        help = did_you_mean(&similar_names(feature, known_features))
+                            .unwrap_or_else(
+                                || "add the feature to the project manifest".to_string()
+                            ),
+                        "{message}"
                     ));
                 }
             }
@@ -219,6 +298,48 @@ impl WorkspaceManifest {
             Either::Right(std::iter::once(&default_feature))
         };
 
+        // The platforms of the environment can only restrict the platforms
+        // that are supported by its features.
+        if let Some(platforms) = &env.platforms {
+            let supported_platforms = features
+                .iter()
+                .chain(default.clone())
+                .map(|feature| {
+                    feature
+                        .platforms
+                        .as_ref()
+                        .unwrap_or(&self.workspace.platforms)
+                        .value
+                        .iter()
+                        .collect::<HashSet<_>>()
+                })
+                .reduce(|acc, platforms| acc.intersection(&platforms).copied().collect())
+                .unwrap_or_default();
+            if let Some(platform) = platforms
+                .value
+                .iter()
+                .find(|platform| !supported_platforms.contains(platform))
+            {
+                return Err(miette::miette!(
+                    labels = vec![LabeledSpan::at(
+                        platforms.span.clone().unwrap_or_default(),
+                        format!("'{platform}' is not supported by the features")
+                    )],
+                    help = format!(
+                        "the features of the environment support {}",
+                        supported_platforms
+                            .iter()
+                            .sorted_by_key(|platform| platform.as_str())
+                            .map(|platform| format!("'{platform}'"))
+                            .join(", ")
+                    ),
+                    "the platform '{}' of the environment '{}' is not supported by its features",
+                    platform,
+                    env.name
+                ));
+            }
+        }
+
         // Check if there are conflicts in system requirements between features
         if let Err(e) = features
             .iter()
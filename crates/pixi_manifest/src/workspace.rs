@@ -1,12 +1,12 @@
 use std::{collections::HashMap, path::PathBuf};
 
 use indexmap::IndexSet;
-use rattler_conda_types::{NamedChannelOrUrl, Platform, Version};
+use rattler_conda_types::{NamedChannelOrUrl, Platform, Version, VersionSpec};
 use rattler_solve::ChannelPriority;
 use url::Url;
 
 use super::pypi::pypi_options::PypiOptions;
-use crate::{preview::Preview, utils::PixiSpanned, PrioritizedChannel};
+use crate::{preview::Preview, utils::PixiSpanned, AuditPolicy, PrioritizedChannel};
 
 /// Describes the contents of the `[workspace]` section of the project manifest.
 #[derive(Debug, Clone)]
@@ -58,6 +58,16 @@ pub struct Workspace {
     /// The pypi options supported in the project
     pub pypi_options: Option<PypiOptions>,
 
+    /// The policy used to audit the locked packages
+    pub audit: Option<AuditPolicy>,
+
+    /// The versions of pixi that can be used with the workspace
+    pub requires_pixi: Option<VersionSpec>,
+
+    /// Glob patterns of the directories of the members of the workspace,
+    /// relative to the workspace root
+    pub members: Vec<String>,
+
     /// Preview features
     pub preview: Preview,
 }
@@ -1,9 +1,9 @@
 use miette::{Context, IntoDiagnostic};
-use pixi_utils::executable_from_path;
-use serde::Deserialize;
+use pixi_utils::{activation_hash, executable_from_path, PrefixActivation};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
 use std::ops::Not;
 #[cfg(target_family = "unix")]
 use std::os::unix::process::CommandExt;
@@ -13,24 +13,25 @@ use std::process::{Command, Stdio};
 // trampoline configuration folder name
 pub const TRAMPOLINE_CONFIGURATION: &str = "trampoline_configuration";
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 struct Metadata {
     exe: String,
+    #[serde(default)]
+    path: PathBuf,
     env: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prefix: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    env_hash: Option<String>,
 }
 
-fn read_metadata(current_exe: &Path) -> miette::Result<Metadata> {
+fn metadata_path(current_exe: &Path) -> miette::Result<PathBuf> {
     // the metadata file is next to the current executable parent folder,
     // under trampoline_configuration/current_exe_name.json
     if let Some(exe_parent) = current_exe.parent() {
-        let metadata_path = exe_parent
+        return Ok(exe_parent
             .join(TRAMPOLINE_CONFIGURATION)
-            .join(format!("{}.json", executable_from_path(current_exe),));
-        let metadata_file = File::open(&metadata_path)
-            .into_diagnostic()
-            .wrap_err(format!("Couldn't open {:?}", metadata_path))?;
-        let metadata: Metadata = serde_json::from_reader(metadata_file).into_diagnostic()?;
-        return Ok(metadata);
+            .join(format!("{}.json", executable_from_path(current_exe),)));
     }
     miette::bail!(
         "Couldn't get the parent folder of the current executable: {:?}",
@@ -38,6 +39,40 @@ fn read_metadata(current_exe: &Path) -> miette::Result<Metadata> {
     );
 }
 
+fn read_metadata(metadata_path: &Path) -> miette::Result<Metadata> {
+    let metadata_file = File::open(metadata_path)
+        .into_diagnostic()
+        .wrap_err(format!("Couldn't open {:?}", metadata_path))?;
+    let metadata: Metadata = serde_json::from_reader(metadata_file).into_diagnostic()?;
+    Ok(metadata)
+}
+
+/// Use the activation that pixi cached for the prefix if the environment changed since the
+/// metadata was written, e.g. because `pixi global update` installed packages with different
+/// activation scripts.
+fn refresh_activation(metadata: &mut Metadata, metadata_path: &Path) {
+    let (Some(prefix), Some(expected_hash)) = (&metadata.prefix, &metadata.env_hash) else {
+        // Written by an older version of pixi, nothing to compare against
+        return;
+    };
+
+    if &activation_hash(prefix) == expected_hash {
+        return;
+    }
+
+    // Without an up-to-date activation of the prefix the recorded one is kept
+    let Some(activation) = PrefixActivation::read(prefix) else {
+        return;
+    };
+    metadata.env = activation.environment_variables;
+    metadata.env_hash = Some(activation.hash);
+
+    // Storing the new activation is best effort, the bin directory might not be writable
+    if let Ok(contents) = serde_json::to_string_pretty(&metadata) {
+        let _ = fs::write(metadata_path, contents);
+    }
+}
+
 /// Compute the difference between two PATH variables (the entries split by `;` or `:`)
 fn update_path(cached_path: &str) -> String {
     // Get current PATH
@@ -72,7 +107,9 @@ fn trampoline() -> miette::Result<()> {
         .into_diagnostic()
         .wrap_err("Couldn't set the ctrl-c handler")?;
 
-    let metadata = read_metadata(&current_exe)?;
+    let metadata_path = metadata_path(&current_exe)?;
+    let mut metadata = read_metadata(&metadata_path)?;
+    refresh_activation(&mut metadata, &metadata_path);
 
     // Create a new Command for the specified executable
     let mut cmd = Command::new(metadata.exe);
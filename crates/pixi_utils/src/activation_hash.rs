@@ -0,0 +1,174 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The 64 bit FNV-1a hash. Unlike the hasher of the standard library its
+/// output is stable across Rust versions, which is required because the hash
+/// is computed by both pixi and the trampoline, which are built separately.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Writes a separator so that consecutive values can't be confused.
+    fn write_separator(&mut self) {
+        self.write(&[0xff]);
+    }
+}
+
+/// Returns the sorted names of the files in the directory, or nothing if the
+/// directory doesn't exist.
+fn sorted_file_names(dir: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_file())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Computes a hash of the parts of a prefix that influence its activation:
+/// the installed packages, the environment variables in `conda-meta/state`
+/// and the activation scripts. If the hash changes the activation of the
+/// prefix has to be recomputed.
+///
+/// The hash only requires reading a few small files so it is cheap enough to
+/// be computed every time a trampoline is started.
+pub fn activation_hash(prefix: &Path) -> String {
+    let mut hasher = Fnv1a::new();
+
+    let conda_meta = prefix.join("conda-meta");
+    for name in sorted_file_names(&conda_meta) {
+        if name.ends_with(".json") {
+            hasher.write(name.as_bytes());
+            hasher.write_separator();
+        }
+    }
+    hasher.write_separator();
+
+    if let Ok(state) = fs::read(conda_meta.join("state")) {
+        hasher.write(&state);
+    }
+    hasher.write_separator();
+
+    let activate_dir = prefix.join("etc").join("conda").join("activate.d");
+    for name in sorted_file_names(&activate_dir) {
+        hasher.write(name.as_bytes());
+        hasher.write_separator();
+        if let Ok(contents) = fs::read(activate_dir.join(&name)) {
+            hasher.write(&contents);
+        }
+        hasher.write_separator();
+    }
+
+    format!("{:016x}", hasher.0)
+}
+
+/// The activation of a prefix as computed by pixi. It is cached in the prefix
+/// so the trampolines of the prefix can reuse it when the activation they
+/// recorded is stale, without running the activation scripts themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrefixActivation {
+    /// The activation hash of the prefix when the variables were computed.
+    pub hash: String,
+    /// The environment variables set by the activation.
+    pub environment_variables: HashMap<String, String>,
+}
+
+impl PrefixActivation {
+    /// Records the activation of the prefix together with its current hash.
+    pub fn new(prefix: &Path, environment_variables: HashMap<String, String>) -> Self {
+        Self {
+            hash: activation_hash(prefix),
+            environment_variables,
+        }
+    }
+
+    /// Returns the path of the cached activation. The file doesn't have a
+    /// `.json` extension, so it doesn't influence the hash.
+    fn path(prefix: &Path) -> PathBuf {
+        prefix.join("conda-meta").join("pixi_activation_env")
+    }
+
+    /// Reads the cached activation of the prefix. Returns `None` if there is
+    /// none or if the prefix changed since it was computed.
+    pub fn read(prefix: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(Self::path(prefix)).ok()?;
+        let activation: Self = serde_json::from_str(&contents).ok()?;
+        (activation.hash == activation_hash(prefix)).then_some(activation)
+    }
+
+    /// Caches the activation in the prefix.
+    pub fn write(&self, prefix: &Path) -> std::io::Result<()> {
+        let contents = serde_json::to_string(self).map_err(std::io::Error::other)?;
+        fs::write(Self::path(prefix), contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activation_hash() {
+        let prefix = tempfile::tempdir().unwrap();
+        let conda_meta = prefix.path().join("conda-meta");
+        let activate_dir = prefix.path().join("etc/conda/activate.d");
+        fs::create_dir_all(&conda_meta).unwrap();
+        fs::create_dir_all(&activate_dir).unwrap();
+
+        fs::write(conda_meta.join("python-3.12.0-h1_0.json"), "{}").unwrap();
+        let initial = activation_hash(prefix.path());
+        assert_eq!(initial, activation_hash(prefix.path()));
+
+        // Other files in `conda-meta` don't influence the activation.
+        fs::write(conda_meta.join("history"), "==> 2024-01-01 <==").unwrap();
+        assert_eq!(initial, activation_hash(prefix.path()));
+
+        // A new activation script changes the hash.
+        fs::write(activate_dir.join("env.sh"), "export FOO=bar").unwrap();
+        let with_script = activation_hash(prefix.path());
+        assert_ne!(initial, with_script);
+
+        // And so does a change of its contents.
+        fs::write(activate_dir.join("env.sh"), "export FOO=baz").unwrap();
+        assert_ne!(with_script, activation_hash(prefix.path()));
+    }
+
+    #[test]
+    fn test_prefix_activation() {
+        let prefix = tempfile::tempdir().unwrap();
+        let conda_meta = prefix.path().join("conda-meta");
+        fs::create_dir_all(&conda_meta).unwrap();
+        fs::write(conda_meta.join("python-3.12.0-h1_0.json"), "{}").unwrap();
+
+        let activation = PrefixActivation::new(
+            prefix.path(),
+            HashMap::from([("FOO".to_string(), "bar".to_string())]),
+        );
+        activation.write(prefix.path()).unwrap();
+        assert_eq!(PrefixActivation::read(prefix.path()), Some(activation));
+
+        // The cached activation is stale once the prefix changes.
+        fs::write(conda_meta.join("numpy-2.0.0-h1_0.json"), "{}").unwrap();
+        assert_eq!(PrefixActivation::read(prefix.path()), None);
+    }
+}
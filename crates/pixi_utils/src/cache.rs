@@ -3,12 +3,27 @@ use std::hash::{DefaultHasher, Hash, Hasher};
 use rattler_conda_types::{MatchSpec, Platform};
 
 /// A hash that uniquely identifies an environment.
-#[derive(Hash)]
 pub struct EnvironmentHash {
     pub command: String,
     pub specs: Vec<MatchSpec>,
     pub channels: Vec<String>,
     pub platform: Platform,
+    pub pypi_requirements: Vec<String>,
+}
+
+impl Hash for EnvironmentHash {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.command.hash(state);
+        self.specs.hash(state);
+        self.channels.hash(state);
+        self.platform.hash(state);
+
+        // Only hash the pypi requirements if there are any, this keeps the names
+        // of existing conda-only environments stable.
+        if !self.pypi_requirements.is_empty() {
+            self.pypi_requirements.hash(state);
+        }
+    }
 }
 
 impl EnvironmentHash {
@@ -24,6 +39,15 @@ impl EnvironmentHash {
             specs,
             channels,
             platform,
+            pypi_requirements: Vec::new(),
+        }
+    }
+
+    /// Sets the pypi requirements that are installed in the environment.
+    pub fn with_pypi_requirements(self, pypi_requirements: Vec<String>) -> Self {
+        Self {
+            pypi_requirements,
+            ..self
         }
     }
 
@@ -35,3 +59,32 @@ impl EnvironmentHash {
         format!("{}-{:x}", &self.command, hash)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rattler_conda_types::ParseStrictness;
+
+    use super::*;
+
+    fn environment_hash() -> EnvironmentHash {
+        EnvironmentHash::new(
+            String::from("ruff"),
+            vec![MatchSpec::from_str("python", ParseStrictness::Lenient).unwrap()],
+            vec![String::from("https://conda.anaconda.org/conda-forge/")],
+            Platform::Linux64,
+        )
+    }
+
+    #[test]
+    fn test_pypi_requirements_change_name() {
+        let conda = environment_hash();
+        let empty_pypi = environment_hash().with_pypi_requirements(Vec::new());
+        let pypi = environment_hash().with_pypi_requirements(vec![String::from("ruff==0.6")]);
+
+        assert_eq!(conda.name(), empty_pypi.name());
+        assert_ne!(conda.name(), pypi.name());
+        assert!(pypi.name().starts_with("ruff-"));
+    }
+}
@@ -1,3 +1,4 @@
+mod activation_hash;
 pub mod cache;
 pub mod conda_environment_file;
 pub mod indicatif;
@@ -7,5 +8,6 @@ pub mod reqwest;
 mod executable_utils;
 pub use executable_utils::{executable_from_path, is_binary_folder, strip_executable_extension};
 
+pub use activation_hash::{activation_hash, PrefixActivation};
 pub use cache::EnvironmentHash;
 pub use prefix_guard::{PrefixGuard, WriteGuard};
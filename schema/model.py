@@ -104,6 +104,31 @@ KnownPreviewFeature = PixiBuildFeature
 #     PixiBuild: Annotated[str, Field(description="Enables building of source records")] = "pixi-build"
 
 
+class AuditPolicy(StrictBaseModel):
+    """The policy used by `pixi audit` to check the locked packages."""
+
+    allow_licenses: list[NonEmptyStr] | None = Field(
+        None,
+        description="The SPDX license identifiers that are allowed. If empty, every license that is not denied is allowed",
+        examples=[["MIT", "BSD-3-Clause"]],
+    )
+    deny_licenses: list[NonEmptyStr] | None = Field(
+        None,
+        description="The SPDX license identifiers that are not allowed",
+        examples=[["GPL-3.0-only"]],
+    )
+    allow_unknown_licenses: bool | None = Field(
+        None,
+        description="Whether packages without (valid) license information are allowed, defaults to `true`",
+    )
+    ignore_advisories: list[NonEmptyStr] | None = Field(
+        None, description="The identifiers or aliases of advisories that should not be reported"
+    )
+    advisory_db: PathNoBackslash | None = Field(
+        None, description="The path to a directory with OSV advisories, relative to the project root"
+    )
+
+
 class Workspace(StrictBaseModel):
     """The project's metadata information."""
 
@@ -154,6 +179,19 @@ class Workspace(StrictBaseModel):
     pypi_options: PyPIOptions | None = Field(
         None, description="Options related to PyPI indexes for this project"
     )
+    audit: AuditPolicy | None = Field(
+        None, description="The policy used to audit the locked packages"
+    )
+    requires_pixi: NonEmptyStr | None = Field(
+        None,
+        description="The version constraint on the pixi binary that can be used with this workspace",
+        examples=[">=0.40"],
+    )
+    members: list[NonEmptyStr] | None = Field(
+        None,
+        description="Glob patterns of the directories of the members of the workspace, relative to the workspace root",
+        examples=[["packages/*", "!packages/deprecated"]],
+    )
     preview: list[KnownPreviewFeature | str] | bool | None = Field(
         None, description="Defines the enabling of preview features of the project"
     )
@@ -189,8 +227,15 @@ class Package(StrictBaseModel):
     documentation: AnyHttpUrl | None = Field(
         None, description="The URL of the documentation of the project"
     )
+    extras: dict[ExtraName, Dependencies] | None = Field(
+        None,
+        description="Optional sets of run dependencies of the package",
+        examples=[{"gpu": {"pytorch-cuda": "12.*"}}],
+    )
 
 
+ExtraName = Annotated[str, Field(pattern=r"^[a-z\d\-]+$")]
+
 ########################
 # Dependencies section #
 ########################
@@ -336,6 +381,18 @@ BuildDependenciesField = Field(
     description="The build `conda` dependencies, used in the build process",
 )
 Dependencies = dict[CondaPackageName, MatchSpec] | None
+ConstraintsField = Field(
+    None,
+    description="Constraints on the versions of `conda` packages, these only apply when the packages are part of the environment",
+)
+PyPIOverridesField = Field(
+    None,
+    description="Requirements that replace all requirements on the same PyPI packages, including those of transitive dependencies",
+)
+PyPIConstraintsField = Field(
+    None,
+    description="Constraints on the versions of PyPI packages, these only apply when the packages are part of the environment",
+)
 
 ################
 # Task section #
@@ -436,11 +493,52 @@ class Environment(StrictBaseModel):
         False,
         description="Whether to add the default feature to this environment",
     )
+    extends: EnvironmentName | None = Field(
+        None,
+        description="The environment whose features, platforms and channels are inherited",
+    )
+    abstract: Optional[bool] = Field(
+        False,
+        description="Whether the environment only exists to be extended, an abstract environment is never installed",
+    )
+    platforms: list[Platform] | None = Field(
+        None,
+        description="The platforms of the environment, these restrict the platforms supported by its features",
+    )
+    channels: list[Channel] | None = Field(
+        None,
+        description="The `conda` channels of the environment, these are used instead of the channels of its features",
+    )
 
 
 ######################
 # Activation section #
 ######################
+class EnvSet(StrictBaseModel):
+    set: str = Field(description="The value of the variable")
+
+
+class EnvPrependPath(StrictBaseModel):
+    prepend_path: list[NonEmptyStr] = Field(
+        alias="prepend-path",
+        description="Paths to add in front of the list of paths in the variable",
+    )
+
+
+class EnvAppendPath(StrictBaseModel):
+    append_path: list[NonEmptyStr] = Field(
+        alias="append-path",
+        description="Paths to add at the end of the list of paths in the variable",
+    )
+
+
+class EnvUnset(StrictBaseModel):
+    unset: Literal[True] = Field(description="Removes the variable")
+
+
+EnvValue = NonEmptyStr | EnvSet | EnvPrependPath | EnvAppendPath | EnvUnset
+
+
 class Activation(StrictBaseModel):
     """A description of steps performed when an environment is activated"""
 
@@ -449,10 +547,21 @@ class Activation(StrictBaseModel):
         description="The scripts to run when the environment is activated",
         examples=["activate.sh", "activate.bat"],
     )
-    env: dict[NonEmptyStr, NonEmptyStr] | None = Field(
+    task_shell_scripts: list[NonEmptyStr] | None = Field(
         None,
-        description="A map of environment variables to values, used in the activation of the environment. These will be set in the shell. Thus these variables are shell specific. Using '$' might not expand to a value in different shells.",
-        examples=[{"key": "value"}, {"ARGUMENT": "value"}],
+        alias="task-shell-scripts",
+        description="Scripts that are executed with the cross-platform shell that is also used for tasks. The variables they export are added to the activation of every shell",
+        examples=["activate.task-shell"],
+    )
+    env: dict[NonEmptyStr, EnvValue] | None = Field(
+        None,
+        description="A map of environment variables to their values or modifications, used in the activation of the environment. Values can reference other variables with `$NAME` or `${NAME}`.",
+        examples=[
+            {"key": "value"},
+            {"PATH": {"prepend-path": ["$PIXI_PROJECT_ROOT/bin"]}},
+            {"LD_LIBRARY_PATH": {"append-path": ["/opt/lib"]}},
+            {"OLD_VARIABLE": {"unset": True}},
+        ],
     )
 
 
@@ -506,6 +615,9 @@ class Feature(StrictBaseModel):
     pypi_dependencies: dict[PyPIPackageName, PyPIRequirement] | None = Field(
         None, description="The PyPI dependencies of this feature"
     )
+    constraints: Dependencies = ConstraintsField
+    pypi_overrides: dict[PyPIPackageName, PyPIRequirement] | None = PyPIOverridesField
+    pypi_constraints: dict[PyPIPackageName, PyPIRequirement] | None = PyPIConstraintsField
     tasks: dict[TaskName, TaskInlineTable | NonEmptyStr] | None = Field(
         None, description="The tasks provided by this feature"
     )
@@ -518,7 +630,10 @@ class Feature(StrictBaseModel):
     target: dict[TargetName, Target] | None = Field(
         None,
         description="Machine-specific aspects of this feature",
-        examples=[{"linux": {"dependencies": {"python": "3.8"}}}],
+        examples=[
+            {"linux": {"dependencies": {"python": "3.8"}}},
+            {"cuda>=12": {"dependencies": {"pytorch-cuda": "12.*"}}},
+        ],
     )
     pypi_options: PyPIOptions | None = Field(
         None, description="Options related to PyPI indexes for this feature"
@@ -580,6 +695,13 @@ class PyPIOptions(StrictBaseModel):
     )
 
 
+class RemoteInclude(StrictBaseModel):
+    """A remote file with features that is included in the manifest, pinned by its hash."""
+
+    url: AnyHttpUrl = Field(description="The URL of the file")
+    sha256: Sha256Sum = Field(description="The sha256 hash of the file")
+
+
 #######################
 # The Manifest itself #
 #######################
@@ -607,12 +729,28 @@ class BaseManifest(StrictBaseModel):
     workspace: Workspace | None = Field(None, description="The workspace's metadata information")
     project: Workspace | None = Field(None, description="The project's metadata information")
     package: Package | None = Field(None, description="The package's metadata information")
+    include: list[NonEmptyStr | RemoteInclude] | None = Field(
+        None,
+        description="Files with features to include in the manifest, as paths relative to the manifest or URLs pinned by their hash",
+        examples=[
+            [
+                "../shared/lint.toml",
+                {
+                    "url": "https://example.com/pixi/cuda.toml",
+                    "sha256": "6a1e4b4e2b6b1ec1b3f3a0e0c5c8e36f0e8d6f6a4c1d0b7e5b2f8a3c9d4e7f10",
+                },
+            ]
+        ],
+    )
     dependencies: Dependencies = DependenciesField
     host_dependencies: Dependencies = HostDependenciesField
     build_dependencies: Dependencies = BuildDependenciesField
     pypi_dependencies: dict[PyPIPackageName, PyPIRequirement] | None = Field(
         None, description="The PyPI dependencies"
     )
+    constraints: Dependencies = ConstraintsField
+    pypi_overrides: dict[PyPIPackageName, PyPIRequirement] | None = PyPIOverridesField
+    pypi_constraints: dict[PyPIPackageName, PyPIRequirement] | None = PyPIConstraintsField
     pypi_options: PyPIOptions | None = Field(None, description="Options related to PyPI indexes")
     tasks: dict[TaskName, TaskInlineTable | NonEmptyStr] | None = Field(
         None, description="The tasks of the project"
@@ -633,7 +771,10 @@ class BaseManifest(StrictBaseModel):
     target: dict[TargetName, Target] | None = Field(
         None,
         description="The targets of the project",
-        examples=[{"linux": {"dependencies": {"python": "3.8"}}}],
+        examples=[
+            {"linux": {"dependencies": {"python": "3.8"}}},
+            {"cuda>=12": {"dependencies": {"pytorch-cuda": "12.*"}}},
+        ],
     )
     tool: dict[str, Any] = Field(
         None, description="Third-party tool configurations, ignored by pixi"
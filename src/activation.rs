@@ -1,23 +1,32 @@
 use crate::{project::Environment, Project};
 use crate::{project::HasProjectRef, task::EnvironmentHash};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use deno_task_shell::{
+    execute_with_pipes, pipe, ExecuteResult, ShellCommand, ShellCommandContext, ShellPipeWriter,
+    ShellState,
+};
 use fs_err::tokio as tokio_fs;
+use futures::future::LocalBoxFuture;
 use indexmap::IndexMap;
 use itertools::Itertools;
-use miette::IntoDiagnostic;
-use pixi_manifest::EnvironmentName;
+use miette::{Context, IntoDiagnostic};
+use parking_lot::Mutex;
 use pixi_manifest::FeaturesExt;
+use pixi_manifest::{expand_env_references, EnvValue, EnvironmentName};
 use rattler_conda_types::Platform;
 use rattler_lock::LockFile;
 use rattler_shell::{
     activation::{
-        ActivationError, ActivationError::FailedToRunActivationScript, ActivationVariables,
-        Activator, PathModificationBehavior,
+        ActivationError::FailedToRunActivationScript, ActivationVariables, Activator,
+        PathModificationBehavior,
     },
     shell::ShellEnum,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
 
 // Setting a base prefix for the pixi package
 const PROJECT_PREFIX: &str = "PIXI_PROJECT_";
@@ -32,6 +41,18 @@ pub enum CurrentEnvVarBehavior {
     Exclude,
 }
 
+impl CurrentEnvVarBehavior {
+    /// Returns the value of a variable of the current shell that can be
+    /// referenced by the activation environment variables. Only the
+    /// variables of an included shell environment can be referenced.
+    pub(crate) fn host_env(&self, name: &str) -> Option<String> {
+        match self {
+            CurrentEnvVarBehavior::Include => std::env::var(name).ok(),
+            CurrentEnvVarBehavior::Clean | CurrentEnvVarBehavior::Exclude => None,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct ActivationCache {
     /// The hash of the environment which produced the activation's environment variables.
@@ -79,7 +100,10 @@ const ENV_PREFIX: &str = "PIXI_ENVIRONMENT_";
 
 impl Environment<'_> {
     /// Returns environment variables and their values that should be injected when running a command.
-    pub(crate) fn get_metadata_env(&self) -> IndexMap<String, String> {
+    pub(crate) fn get_metadata_env(
+        &self,
+        env_var_behavior: &CurrentEnvVarBehavior,
+    ) -> IndexMap<String, String> {
         let prompt = match self.name() {
             EnvironmentName::Named(name) => {
                 format!("{}:{}", self.project().name(), name)
@@ -96,19 +120,109 @@ impl Environment<'_> {
         ]);
 
         // Add the activation environment variables
-        map.extend(self.activation_env(Some(Platform::current())));
+        self.apply_activation_env(&mut map, Platform::current(), |name| {
+            env_var_behavior.host_env(name)
+        });
         map
     }
+
+    /// Applies the environment variables of the activation on top of `env`,
+    /// except for the paths that are added to `PATH` which are returned by
+    /// [`Self::activation_paths`].
+    fn apply_activation_env(
+        &self,
+        env: &mut IndexMap<String, String>,
+        platform: Platform,
+        host_env: impl Fn(&str) -> Option<String>,
+    ) {
+        let lookup = self.activation_env_lookup(host_env);
+        let separator = if platform.is_windows() { ";" } else { ":" };
+        for (name, value) in self.activation_env(Some(platform)) {
+            if is_path_variable(&name)
+                && matches!(value, EnvValue::PrependPath(_) | EnvValue::AppendPath(_))
+            {
+                continue;
+            }
+            value.apply(&name, env, &lookup, separator);
+        }
+    }
+
+    /// Returns the paths of the activation that are added to `PATH` in front
+    /// of and after the paths of the environment.
+    fn activation_paths(
+        &self,
+        env: &IndexMap<String, String>,
+        platform: Platform,
+        host_env: impl Fn(&str) -> Option<String>,
+    ) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let lookup = self.activation_env_lookup(host_env);
+        let lookup = |name: &str| env.get(name).cloned().or_else(|| lookup(name));
+        let (mut prepend, mut append) = (Vec::new(), Vec::new());
+        for (name, value) in self.activation_env(Some(platform)) {
+            if !is_path_variable(&name) {
+                continue;
+            }
+            let expand = |paths: &[String]| {
+                paths
+                    .iter()
+                    .map(|path| PathBuf::from(expand_env_references(path, &lookup)))
+                    .collect_vec()
+            };
+            match value {
+                EnvValue::PrependPath(paths) => {
+                    prepend = expand(&paths).into_iter().chain(prepend).collect();
+                }
+                EnvValue::AppendPath(paths) => append.extend(expand(&paths)),
+                EnvValue::Set(_) | EnvValue::Unset => {}
+            }
+        }
+        (prepend, append)
+    }
+
+    /// Returns the names of the variables that are removed by the activation.
+    pub(crate) fn unset_activation_env(&self, platform: Platform) -> Vec<String> {
+        self.activation_env(Some(platform))
+            .into_iter()
+            .fold(IndexMap::new(), |mut acc, (name, value)| {
+                acc.insert(name, matches!(value, EnvValue::Unset));
+                acc
+            })
+            .into_iter()
+            .filter_map(|(name, unset)| unset.then_some(name))
+            .collect()
+    }
+
+    /// Returns the values of the variables that can be referenced by the
+    /// activation environment variables, besides the variables of the
+    /// activation itself. Variables that are not defined by pixi are looked
+    /// up with `host_env`.
+    fn activation_env_lookup(
+        &self,
+        host_env: impl Fn(&str) -> Option<String>,
+    ) -> impl Fn(&str) -> Option<String> {
+        let project_env = self.project().get_metadata_env();
+        let prefix = self.dir().to_string_lossy().into_owned();
+        move |name: &str| match name {
+            "CONDA_PREFIX" => Some(prefix.clone()),
+            _ => project_env.get(name).cloned().or_else(|| host_env(name)),
+        }
+    }
+}
+
+/// Returns true if the variable is the list of paths to find executables in.
+fn is_path_variable(name: &str) -> bool {
+    name.eq_ignore_ascii_case("PATH")
 }
 
 /// Get the complete activator for the environment.
 /// This method will create an activator for the environment and add the activation scripts from the project.
 /// The activator will be created for the current platform and the default shell.
 /// The activation scripts from the environment will be checked for existence and the extension will be checked for correctness.
-pub(crate) fn get_activator<'p>(
+pub(crate) async fn get_activator<'p>(
     environment: &'p Environment<'p>,
     shell: ShellEnum,
-) -> Result<Activator<ShellEnum>, ActivationError> {
+    env_var_behavior: &CurrentEnvVarBehavior,
+) -> miette::Result<Activator<ShellEnum>> {
     let platform = Platform::current();
     let additional_activation_scripts = environment.activation_scripts(Some(platform));
 
@@ -137,7 +251,8 @@ pub(crate) fn get_activator<'p>(
     }
 
     let mut activator =
-        Activator::from_path(environment.dir().as_path(), shell, Platform::current())?;
+        Activator::from_path(environment.dir().as_path(), shell, Platform::current())
+            .into_diagnostic()?;
 
     // Add the custom activation scripts from the environment
     activator
@@ -145,13 +260,149 @@ pub(crate) fn get_activator<'p>(
         .extend(additional_activation_scripts);
 
     // Add the environment variables from the project.
-    activator
-        .env_vars
-        .extend(get_static_environment_variables(environment));
+    let static_env = get_static_environment_variables(environment, env_var_behavior);
+
+    // Add the paths of the activation around the paths of the environment.
+    let (prepend_paths, append_paths) =
+        environment.activation_paths(&static_env, platform, |name| {
+            env_var_behavior.host_env(name)
+        });
+    activator.paths = prepend_paths
+        .into_iter()
+        .chain(std::mem::take(&mut activator.paths))
+        .chain(append_paths)
+        .collect();
+
+    activator.env_vars.extend(static_env);
+
+    // Add the variables exported by the task shell scripts, these are the same
+    // for every shell.
+    let (task_shell_scripts, missing_scripts): (Vec<_>, Vec<_>) = environment
+        .activation_task_shell_scripts(Some(platform))
+        .into_iter()
+        .map(|script| environment.project().root().join(script))
+        .partition(|full_path| full_path.is_file());
+    if !missing_scripts.is_empty() {
+        tracing::warn!(
+            "Could not find task shell activation scripts: {}",
+            missing_scripts.iter().map(|p| p.display()).format(", ")
+        );
+    }
+    if !task_shell_scripts.is_empty() {
+        // The scripts only run once per environment, the exported variables
+        // are reused by every following activation.
+        let exported = environment
+            .project()
+            .environment_vars(environment)?
+            .task_shell()
+            .get_or_try_init(async {
+                let mut env: HashMap<String, String> = std::env::vars().collect();
+                env.extend(activator.env_vars.clone());
+                env.insert(
+                    String::from("CONDA_PREFIX"),
+                    environment.dir().to_string_lossy().to_string(),
+                );
+                let current_path = std::env::var_os("PATH").unwrap_or_default();
+                let path = std::env::join_paths(
+                    activator
+                        .paths
+                        .iter()
+                        .cloned()
+                        .chain(std::env::split_paths(&current_path)),
+                )
+                .into_diagnostic()?;
+                env.insert(String::from("PATH"), path.to_string_lossy().to_string());
+
+                run_task_shell_scripts(
+                    task_shell_scripts,
+                    env,
+                    environment.project().root().to_path_buf(),
+                )
+                .await
+            })
+            .await?;
+        activator.env_vars.extend(exported.clone());
+    }
 
     Ok(activator)
 }
 
+/// The command that is appended to task shell activation scripts to capture
+/// the variables they export.
+const CAPTURE_ENV_COMMAND: &str = "__pixi_capture_activation_env";
+
+/// A task shell command that stores the exported variables of the shell.
+struct CaptureEnvCommand(Arc<Mutex<Option<HashMap<String, String>>>>);
+
+impl ShellCommand for CaptureEnvCommand {
+    fn execute(&self, context: ShellCommandContext) -> LocalBoxFuture<'static, ExecuteResult> {
+        *self.0.lock() = Some(context.state.env_vars().clone());
+        Box::pin(futures::future::ready(ExecuteResult::from_exit_code(0)))
+    }
+}
+
+/// Runs activation scripts with the cross-platform shell that is also used to
+/// run tasks and returns the variables they exported or changed, sorted by
+/// name.
+///
+/// The scripts are executed in order, every script sees the variables exported
+/// by the scripts before it. Their output is written to stderr so it doesn't
+/// end up in the output of `pixi shell-hook`.
+pub(crate) async fn run_task_shell_scripts(
+    scripts: Vec<PathBuf>,
+    env: HashMap<String, String>,
+    cwd: PathBuf,
+) -> miette::Result<IndexMap<String, String>> {
+    let mut current = env.clone();
+    for script in scripts {
+        let contents = tokio_fs::read_to_string(&script).await.into_diagnostic()?;
+        let list =
+            deno_task_shell::parser::parse(&format!("{}\n{CAPTURE_ENV_COMMAND}", contents.trim()))
+                .map_err(|err| {
+                    miette::miette!(
+                        "failed to parse the activation script '{}': {err}",
+                        script.display()
+                    )
+                })?;
+
+        let captured = Arc::new(Mutex::new(None));
+        let custom_commands = HashMap::from([(
+            CAPTURE_ENV_COMMAND.to_string(),
+            Rc::new(CaptureEnvCommand(captured.clone())) as Rc<dyn ShellCommand>,
+        )]);
+        let state = ShellState::new(current.clone(), &cwd, custom_commands);
+        let (stdin, stdin_writer) = pipe();
+        drop(stdin_writer);
+        let code = execute_with_pipes(
+            list,
+            state,
+            stdin,
+            ShellPipeWriter::stderr(),
+            ShellPipeWriter::stderr(),
+        )
+        .await;
+        if code != 0 {
+            miette::bail!(
+                "the activation script '{}' failed with exit code {code}",
+                script.display()
+            );
+        }
+
+        current = captured.lock().take().ok_or_else(|| {
+            miette::miette!(
+                "the activation script '{}' exited before it finished",
+                script.display()
+            )
+        })?;
+    }
+
+    Ok(current
+        .into_iter()
+        .filter(|(key, value)| env.get(key) != Some(value))
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .collect())
+}
+
 /// Get the environment variables from the shell environment.
 /// This method retrieves the specified environment variables from the shell and returns them as a HashMap.
 /// If the variable is not set, its value will be `None`.
@@ -172,7 +423,7 @@ fn get_environment_variable_from_shell_environment(
 /// If the cache is valid, it will return the environment variables from the cache.
 ///
 /// Without a lock file it will not use the cache, as it indicates the cache is not interesting
-async fn try_get_valid_activation_cache(
+pub(crate) async fn try_get_valid_activation_cache(
     lock_file: &LockFile,
     environment: &Environment<'_>,
     cache_file: PathBuf,
@@ -248,13 +499,9 @@ pub async fn run_activation(
     }
     tracing::debug!("Running activation script for {:?}", environment.name());
 
-    let activator = get_activator(environment, ShellEnum::default()).map_err(|e| {
-        miette::miette!(format!(
-            "failed to create activator for {:?}\n{}",
-            environment.name(),
-            e
-        ))
-    })?;
+    let activator = get_activator(environment, ShellEnum::default(), env_var_behavior)
+        .await
+        .wrap_err_with(|| format!("failed to create activator for {:?}", environment.name()))?;
 
     let path_modification_behavior = match env_var_behavior {
         // We need to replace the full environment path with the new one.
@@ -263,7 +510,8 @@ pub async fn run_activation(
         _ => PathModificationBehavior::Prepend,
     };
 
-    let activator_result = match tokio::task::spawn_blocking(move || {
+    let unset_env = environment.unset_activation_env(Platform::current());
+    let mut activator_result = match tokio::task::spawn_blocking(move || {
         // Run and cache the activation script
         activator.run_activation(
             ActivationVariables {
@@ -311,6 +559,12 @@ pub async fn run_activation(
         }
     };
 
+    // The variables that are removed by the activation are set to an empty value
+    // by the activation script.
+    for name in unset_env {
+        activator_result.remove(&name);
+    }
+
     // If the lock file is provided, and we can compute the environment hash, let's rewrite the
     // cache file.
     if experimental {
@@ -351,6 +605,7 @@ pub async fn run_activation(
 /// Returns IndexMap to stay sorted, as pixi should export the metadata before exporting variables that could depend on it.
 pub(crate) fn get_static_environment_variables<'p>(
     environment: &'p Environment<'p>,
+    env_var_behavior: &CurrentEnvVarBehavior,
 ) -> IndexMap<String, String> {
     // Get environment variables from the pixi project meta data
     let project_env = environment.project().get_metadata_env();
@@ -364,7 +619,7 @@ pub(crate) fn get_static_environment_variables<'p>(
     shell_env.insert("CONDA_DEFAULT_ENV".to_string(), env_name);
 
     // Get environment variables from the pixi environment
-    let environment_env = environment.get_metadata_env();
+    let environment_env = environment.get_metadata_env(env_var_behavior);
 
     // Combine the environments
     project_env
@@ -450,12 +705,110 @@ pub(crate) async fn initialize_env_variables(
         CurrentEnvVarBehavior::Exclude => HashMap::new(),
     };
 
-    let all_variables: HashMap<String, String> = current_shell_env_vars
+    Ok(merge_env_variables(
+        environment,
+        current_shell_env_vars,
+        activation_env,
+    ))
+}
+
+/// Combines the variables of the current shell with the variables of the
+/// activation. The variables that are unset by the activation are also
+/// removed when they come from the current shell.
+fn merge_env_variables(
+    environment: &Environment<'_>,
+    current_shell_env_vars: HashMap<String, String>,
+    activation_env: HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut all_variables: HashMap<String, String> = current_shell_env_vars
         .into_iter()
         .chain(activation_env)
         .collect();
+    for name in environment.unset_activation_env(Platform::current()) {
+        all_variables.remove(&name);
+    }
+    all_variables
+}
 
-    Ok(all_variables)
+/// The variable in which the [`EnvironmentDiff`] of the active environment is
+/// stored, so that it can be deactivated again later.
+pub(crate) const DEACTIVATION_VAR: &str = "PIXI_DEACTIVATE";
+
+/// The changes that activating an environment made to the variables of a
+/// shell. For every variable that was changed by the activation, including
+/// `PATH` and the variables set by the activation scripts of packages, the
+/// value it had before the activation is recorded.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct EnvironmentDiff {
+    /// The value of each changed variable before the activation, or `None` if
+    /// it was not set.
+    previous: BTreeMap<String, Option<String>>,
+}
+
+impl EnvironmentDiff {
+    /// Records the variables of `activated` that differ from the value
+    /// returned by `current`.
+    pub(crate) fn from_activation<'a>(
+        activated: impl IntoIterator<Item = (&'a String, &'a String)>,
+        current: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let previous = activated
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let previous = current(name);
+                (previous.as_ref() != Some(value)).then(|| (name.clone(), previous))
+            })
+            .collect();
+        Self { previous }
+    }
+
+    /// Also records the variables in `unset` that are removed by the
+    /// activation, if they are set.
+    pub(crate) fn with_unset(
+        mut self,
+        unset: &[String],
+        current: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        for name in unset {
+            if let Some(previous) = current(name) {
+                self.previous.insert(name.clone(), Some(previous));
+            }
+        }
+        self
+    }
+
+    /// Returns the diff that is stored in the current process by a previous
+    /// activation, if any.
+    pub(crate) fn from_env() -> Option<Self> {
+        Self::decode(&std::env::var(DEACTIVATION_VAR).ok()?)
+    }
+
+    /// Returns the value a variable had before the activation, if the
+    /// activation changed it.
+    pub(crate) fn previous_value(&self, name: &str) -> Option<Option<&str>> {
+        self.previous.get(name).map(Option::as_deref)
+    }
+
+    /// Iterates over the changed variables and the value they should be
+    /// restored to, `None` means the variable should be unset.
+    pub(crate) fn restore(&self) -> impl Iterator<Item = (&str, Option<&str>)> + '_ {
+        self.previous
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_deref()))
+    }
+
+    /// Encodes the diff so it can be stored in a variable of the shell.
+    pub(crate) fn encode(&self) -> String {
+        URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(&self.previous).expect("serializing strings cannot fail"))
+    }
+
+    /// Decodes a diff from [`Self::encode`], returns `None` for invalid input.
+    pub(crate) fn decode(encoded: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(encoded).ok()?;
+        let previous = serde_json::from_slice(&bytes).ok()?;
+        Some(Self { previous })
+    }
 }
 
 #[cfg(test)]
@@ -483,14 +836,14 @@ mod tests {
         let project = Project::from_str(Path::new("pixi.toml"), multi_env_project).unwrap();
 
         let default_env = project.default_environment();
-        let env = default_env.get_metadata_env();
+        let env = default_env.get_metadata_env(&CurrentEnvVarBehavior::Include);
 
         assert_eq!(env.get("PIXI_ENVIRONMENT_NAME").unwrap(), "default");
         assert!(env.get("PIXI_ENVIRONMENT_PLATFORMS").is_some());
         assert!(env.get("PIXI_PROMPT").unwrap().contains("pixi"));
 
         let test_env = project.environment("test").unwrap();
-        let env = test_env.get_metadata_env();
+        let env = test_env.get_metadata_env(&CurrentEnvVarBehavior::Include);
 
         assert_eq!(env.get("PIXI_ENVIRONMENT_NAME").unwrap(), "test");
         assert!(env.get("PIXI_PROMPT").unwrap().contains("pixi"));
@@ -498,6 +851,78 @@ mod tests {
         assert!(env.get("TEST").unwrap().contains("123test123"));
     }
 
+    #[test]
+    fn test_activation_env_merge() {
+        let project = r#"
+        [project]
+        name = "pixi"
+        channels = ["conda-forge"]
+        platforms = ["linux-64", "osx-64", "win-64"]
+
+        [activation.env]
+        MODE = "default"
+        DATA = "$PIXI_PROJECT_ROOT/data"
+        PIXI_TEST_LIBS = { prepend-path = ["/default/lib"] }
+        PATH = { prepend-path = ["$PIXI_PROJECT_ROOT/bin"] }
+
+        [feature.a.activation.env]
+        MODE = "a"
+        PIXI_TEST_LIBS = { prepend-path = ["/a/lib"] }
+        CACHE = "a"
+
+        [feature.b.activation.env]
+        MODE = "b"
+        PIXI_TEST_LIBS = { append-path = ["/b/lib"] }
+        CACHE = { unset = true }
+
+        [environments]
+        ab = ["a", "b"]
+        "#;
+        let project = Project::from_str(Path::new("pixi.toml"), project).unwrap();
+        let separator = if Platform::current().is_windows() {
+            ";"
+        } else {
+            ":"
+        };
+
+        let env = project
+            .default_environment()
+            .get_metadata_env(&CurrentEnvVarBehavior::Include);
+        assert_eq!(env["MODE"], "default");
+        assert_eq!(
+            env["DATA"],
+            format!("{}/data", project.root().to_string_lossy())
+        );
+        assert!(!env.contains_key("PATH"));
+
+        // The features are applied in the order they are listed, followed by
+        // the default feature, so the default feature takes precedence.
+        let ab = project.environment("ab").unwrap();
+        let env = ab.get_metadata_env(&CurrentEnvVarBehavior::Include);
+        assert_eq!(env["MODE"], "default");
+        assert_eq!(
+            env["DATA"],
+            format!("{}/data", project.root().to_string_lossy())
+        );
+        assert_eq!(env["CACHE"], "");
+        assert_eq!(
+            env["PIXI_TEST_LIBS"],
+            ["/default/lib", "/a/lib", "/b/lib"].join(separator)
+        );
+        assert_eq!(ab.unset_activation_env(Platform::current()), vec!["CACHE"]);
+
+        let (prepend, append) =
+            ab.activation_paths(&IndexMap::new(), Platform::current(), |_| None);
+        assert_eq!(
+            prepend,
+            vec![PathBuf::from(format!(
+                "{}/bin",
+                project.root().to_string_lossy()
+            ))]
+        );
+        assert!(append.is_empty());
+    }
+
     #[test]
     fn test_metadata_project_env() {
         let project = r#"
@@ -539,7 +964,10 @@ mod tests {
         ZAB = "123test123"
         "#;
         let project = Project::from_str(Path::new("pixi.toml"), project).unwrap();
-        let env = get_static_environment_variables(&project.default_environment());
+        let env = get_static_environment_variables(
+            &project.default_environment(),
+            &CurrentEnvVarBehavior::Include,
+        );
 
         // Make sure the user defined environment variables are at the end.
         assert!(
@@ -567,6 +995,69 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_initialize_env_variables_unset() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let project = r#"
+        [project]
+        name = "pixi"
+        channels = []
+        platforms = []
+
+        [activation.env]
+        PIXI_TEST_UNSET = { unset = true }
+        "#;
+        let project =
+            Project::from_str(temp_dir.path().join("pixi.toml").as_path(), project).unwrap();
+
+        let environment = project.default_environment();
+        let activation_env = run_activation(
+            &environment,
+            &CurrentEnvVarBehavior::Include,
+            None,
+            false,
+            false,
+        )
+        .await
+        .unwrap();
+        let shell_env = HashMap::from([
+            ("PIXI_TEST_UNSET".to_string(), "from the shell".to_string()),
+            ("PIXI_TEST_KEEP".to_string(), "from the shell".to_string()),
+        ]);
+        let env = merge_env_variables(&environment, shell_env, activation_env);
+        assert!(!env.contains_key("PIXI_TEST_UNSET"));
+        assert_eq!(env["PIXI_TEST_KEEP"], "from the shell");
+        assert!(env.contains_key("CONDA_PREFIX"));
+    }
+
+    #[test]
+    fn test_activation_env_host_lookup() {
+        let project = r#"
+        [project]
+        name = "pixi"
+        channels = []
+        platforms = []
+
+        [activation.env]
+        PIXI_TEST_HOST_REF = "$PIXI_TEST_HOST/bin"
+        "#;
+        let project = Project::from_str(Path::new("pixi.toml"), project).unwrap();
+        let environment = project.default_environment();
+
+        // Variables of the shell can only be referenced when they are included.
+        let host_env = |name: &str| (name == "PIXI_TEST_HOST").then(|| "/host".to_string());
+        let mut env = IndexMap::new();
+        environment.apply_activation_env(&mut env, Platform::current(), host_env);
+        assert_eq!(env["PIXI_TEST_HOST_REF"], "/host/bin");
+
+        let mut env = IndexMap::new();
+        environment.apply_activation_env(&mut env, Platform::current(), |_| None);
+        assert_eq!(env["PIXI_TEST_HOST_REF"], "$PIXI_TEST_HOST/bin");
+
+        assert_eq!(CurrentEnvVarBehavior::Exclude.host_env("PATH"), None);
+        assert_eq!(CurrentEnvVarBehavior::Clean.host_env("PATH"), None);
+    }
+
     /// Test that the activation cache is created and used correctly based on the lockfile.
     ///
     /// This test will validate the cache usages by running the activation script and checking if the cache is created.
@@ -826,4 +1317,89 @@ packages:
     //     // Unset the environment variable
     //     std::env::remove_var("TEST_ENV_VAR");
     // }
+
+    #[test]
+    fn test_environment_diff() {
+        let current = HashMap::from([
+            ("PATH".to_string(), "/usr/bin".to_string()),
+            ("UNCHANGED".to_string(), "same".to_string()),
+        ]);
+        let activated = HashMap::from([
+            ("PATH".to_string(), "/env/bin:/usr/bin".to_string()),
+            ("UNCHANGED".to_string(), "same".to_string()),
+            ("CONDA_PREFIX".to_string(), "/env".to_string()),
+        ]);
+        let diff = EnvironmentDiff::from_activation(&activated, |name| current.get(name).cloned());
+
+        assert_eq!(diff.previous_value("PATH"), Some(Some("/usr/bin")));
+        assert_eq!(diff.previous_value("CONDA_PREFIX"), Some(None));
+        assert_eq!(diff.previous_value("UNCHANGED"), None);
+        assert_eq!(EnvironmentDiff::decode(&diff.encode()), Some(diff.clone()));
+        assert_eq!(EnvironmentDiff::decode("not a diff!"), None);
+    }
+
+    #[test]
+    fn test_environment_hash_task_shell_script_contents() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let project = r#"
+        [project]
+        name = "pixi"
+        channels = []
+        platforms = []
+
+        [activation]
+        task-shell-scripts = ["env.txt"]
+        "#;
+        let project =
+            Project::from_str(temp_dir.path().join("pixi.toml").as_path(), project).unwrap();
+        let environment = project.default_environment();
+        let lock_file = LockFile::default();
+        let script = temp_dir.path().join("env.txt");
+
+        fs_err::write(&script, "export FOO=bar").unwrap();
+        let hash = EnvironmentHash::from_environment(&environment, &HashMap::new(), &lock_file);
+        assert_eq!(
+            hash,
+            EnvironmentHash::from_environment(&environment, &HashMap::new(), &lock_file)
+        );
+
+        // Changing the script invalidates the activation cache.
+        fs_err::write(&script, "export FOO=baz").unwrap();
+        assert_ne!(
+            hash,
+            EnvironmentHash::from_environment(&environment, &HashMap::new(), &lock_file)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_task_shell_scripts() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.txt");
+        let second = dir.path().join("second.txt");
+        fs_err::write(&first, "export FOO=bar\necho 'not in the output'\n").unwrap();
+        fs_err::write(&second, "export BAR=\"$FOO-baz\" && export KEPT=same").unwrap();
+
+        let env = HashMap::from([(String::from("KEPT"), String::from("same"))]);
+        let exported = run_task_shell_scripts(
+            vec![first.clone(), second],
+            env.clone(),
+            dir.path().to_path_buf(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            exported.into_iter().collect_vec(),
+            vec![
+                (String::from("BAR"), String::from("bar-baz")),
+                (String::from("FOO"), String::from("bar")),
+            ]
+        );
+
+        let failing = dir.path().join("failing.txt");
+        fs_err::write(&failing, "exit 3").unwrap();
+        let err = run_task_shell_scripts(vec![failing], env, dir.path().to_path_buf())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("exit code 3"));
+    }
 }
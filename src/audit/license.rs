@@ -0,0 +1,105 @@
+use pixi_manifest::AuditPolicy;
+
+/// The outcome of checking a license against an [`AuditPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LicenseVerdict {
+    /// The license is allowed by the policy.
+    Allowed,
+    /// The license is not allowed by the policy.
+    Denied,
+    /// The license is missing or is not a valid SPDX expression.
+    Unknown,
+}
+
+impl LicenseVerdict {
+    /// Returns true if this verdict violates the given policy.
+    pub fn is_violation(self, policy: &AuditPolicy) -> bool {
+        match self {
+            LicenseVerdict::Allowed => false,
+            LicenseVerdict::Denied => true,
+            LicenseVerdict::Unknown => !policy.allow_unknown_licenses,
+        }
+    }
+}
+
+/// Checks the (SPDX) license expression of a package against the policy.
+///
+/// An expression is allowed if it can be satisfied with licenses that are
+/// allowed, e.g. `MIT OR GPL-3.0-only` is allowed if `GPL-3.0-only` is denied
+/// but `MIT` is not.
+pub fn check_license(policy: &AuditPolicy, license: Option<&str>) -> LicenseVerdict {
+    let Some(license) = license.map(str::trim).filter(|l| !l.is_empty()) else {
+        return LicenseVerdict::Unknown;
+    };
+
+    let Ok(expression) = spdx::Expression::parse_mode(license, spdx::ParseMode::LAX) else {
+        // Without a valid expression we can still match the raw string.
+        return if contains(&policy.deny_licenses, license) {
+            LicenseVerdict::Denied
+        } else if contains(&policy.allow_licenses, license) {
+            LicenseVerdict::Allowed
+        } else {
+            LicenseVerdict::Unknown
+        };
+    };
+
+    let is_allowed = |id: &str| {
+        !contains(&policy.deny_licenses, id)
+            && (policy.allow_licenses.is_empty() || contains(&policy.allow_licenses, id))
+    };
+    if expression.evaluate(|req| req.license.id().is_some_and(|id| is_allowed(id.name))) {
+        LicenseVerdict::Allowed
+    } else {
+        LicenseVerdict::Denied
+    }
+}
+
+fn contains(licenses: &[String], license: &str) -> bool {
+    licenses.iter().any(|l| l.eq_ignore_ascii_case(license))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allow: &[&str], deny: &[&str]) -> AuditPolicy {
+        AuditPolicy {
+            allow_licenses: allow.iter().map(|s| s.to_string()).collect(),
+            deny_licenses: deny.iter().map(|s| s.to_string()).collect(),
+            ..AuditPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_check_license() {
+        let policy = policy(&[], &["GPL-3.0-only"]);
+        assert_eq!(check_license(&policy, Some("MIT")), LicenseVerdict::Allowed);
+        assert_eq!(
+            check_license(&policy, Some("GPL-3.0-only")),
+            LicenseVerdict::Denied
+        );
+        assert_eq!(
+            check_license(&policy, Some("MIT OR GPL-3.0-only")),
+            LicenseVerdict::Allowed
+        );
+        assert_eq!(
+            check_license(&policy, Some("MIT AND GPL-3.0-only")),
+            LicenseVerdict::Denied
+        );
+        assert_eq!(check_license(&policy, None), LicenseVerdict::Unknown);
+    }
+
+    #[test]
+    fn test_allow_list() {
+        let policy = policy(&["MIT", "BSD-3-Clause"], &[]);
+        assert_eq!(
+            check_license(&policy, Some("BSD-3-Clause")),
+            LicenseVerdict::Allowed
+        );
+        assert_eq!(
+            check_license(&policy, Some("Apache-2.0")),
+            LicenseVerdict::Denied
+        );
+        assert!(!LicenseVerdict::Unknown.is_violation(&policy));
+    }
+}
@@ -0,0 +1,261 @@
+//! Auditing of the packages in a lock-file against a database of security
+//! advisories and a license policy.
+
+mod license;
+mod osv;
+
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+pub use license::{check_license, LicenseVerdict};
+pub use osv::{Advisory, AdvisoryDatabase, Ecosystem};
+use pixi_manifest::AuditPolicy;
+use rattler_lock::LockedPackageRef;
+use serde::Serialize;
+
+use crate::lock_file::PypiPackageIdentifier;
+
+/// A locked package in a form that can be audited.
+#[derive(Debug, Clone)]
+pub struct AuditedPackage {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    pub version: String,
+    pub license: Option<String>,
+
+    /// The PyPI packages that are provided by a conda package (derived from
+    /// its purls). Advisories for these packages also apply to the conda
+    /// package.
+    pub provides: Vec<(String, String)>,
+}
+
+impl AuditedPackage {
+    /// Extracts the information to audit from a package in the lock-file.
+    pub fn from_locked(package: LockedPackageRef<'_>) -> Self {
+        match package {
+            LockedPackageRef::Conda(conda) => {
+                let record = conda.record();
+                let provides = PypiPackageIdentifier::from_package_record(record)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|id| (id.name.as_normalized().to_string(), id.version.to_string()))
+                    .collect();
+                Self {
+                    ecosystem: Ecosystem::Conda,
+                    name: record.name.as_normalized().to_string(),
+                    version: record.version.to_string(),
+                    license: record.license.clone(),
+                    provides,
+                }
+            }
+            LockedPackageRef::Pypi(data, _) => Self {
+                ecosystem: Ecosystem::PyPI,
+                name: data.name.to_string(),
+                version: data.version.to_string(),
+                license: None,
+                provides: Vec::new(),
+            },
+        }
+    }
+}
+
+/// A package that is affected by an advisory.
+#[derive(Debug, Clone, Serialize)]
+pub struct Vulnerability {
+    pub ecosystem: String,
+    pub name: String,
+    pub version: String,
+    pub id: String,
+    pub aliases: Vec<String>,
+    pub summary: Option<String>,
+    pub severity: Option<String>,
+}
+
+/// The license of a package together with the verdict of the policy.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageLicense {
+    pub ecosystem: String,
+    pub name: String,
+    pub version: String,
+    pub license: Option<String>,
+    pub violation: bool,
+}
+
+/// The results of auditing a set of packages.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct AuditFindings {
+    pub vulnerabilities: Vec<Vulnerability>,
+    pub licenses: Vec<PackageLicense>,
+}
+
+impl AuditFindings {
+    /// Returns true if any of the findings should fail the audit.
+    pub fn has_failures(&self) -> bool {
+        !self.vulnerabilities.is_empty() || self.licenses.iter().any(|l| l.violation)
+    }
+}
+
+/// Audits the given packages against the advisories and the license policy.
+pub fn audit_packages(
+    packages: impl IntoIterator<Item = AuditedPackage>,
+    database: &AdvisoryDatabase,
+    policy: &AuditPolicy,
+) -> AuditFindings {
+    let mut findings = AuditFindings::default();
+    for package in packages {
+        let candidates = std::iter::once((package.ecosystem, &package.name, &package.version))
+            .chain(
+                package
+                    .provides
+                    .iter()
+                    .map(|(name, version)| (Ecosystem::PyPI, name, version)),
+            );
+        for (ecosystem, name, version) in candidates {
+            for advisory in database.find(ecosystem, name, version) {
+                if policy
+                    .ignore_advisories
+                    .iter()
+                    .any(|id| advisory.is_known_as(id))
+                {
+                    continue;
+                }
+                findings.vulnerabilities.push(Vulnerability {
+                    ecosystem: package.ecosystem.to_string(),
+                    name: package.name.clone(),
+                    version: package.version.clone(),
+                    id: advisory.id.clone(),
+                    aliases: advisory.aliases.clone(),
+                    summary: advisory.summary.clone(),
+                    severity: advisory.severity(),
+                });
+            }
+        }
+
+        let verdict = check_license(policy, package.license.as_deref());
+        findings.licenses.push(PackageLicense {
+            ecosystem: package.ecosystem.to_string(),
+            name: package.name,
+            version: package.version,
+            license: package.license,
+            violation: verdict.is_violation(policy),
+        });
+    }
+    findings
+}
+
+/// Reads the licenses of the PyPI packages installed in the given prefix from
+/// the `METADATA` files of their `.dist-info` directories.
+///
+/// Returns a map from the normalized package name to the installed version
+/// and its license.
+pub fn installed_pypi_licenses(prefix: &Path) -> HashMap<String, (pep440_rs::Version, String)> {
+    let mut licenses = HashMap::new();
+    let site_packages_dirs = ["lib", "Lib"]
+        .iter()
+        .map(|lib| prefix.join(lib))
+        .filter_map(|lib| fs_err::read_dir(lib).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .flat_map(|path| {
+            // Unix: lib/pythonX.Y/site-packages, Windows: Lib/site-packages
+            [path.join("site-packages"), path.clone()]
+        })
+        .filter(|path| path.ends_with("site-packages") && path.is_dir());
+
+    for site_packages in site_packages_dirs {
+        let Ok(entries) = fs_err::read_dir(&site_packages) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "dist-info") {
+                if let Ok(metadata) = fs_err::read_to_string(path.join("METADATA")) {
+                    if let Some((name, version, license)) = parse_metadata_license(&metadata) {
+                        licenses.insert(name, (version, license));
+                    }
+                }
+            }
+        }
+    }
+    licenses
+}
+
+/// Extracts the normalized name, version and license from a core metadata
+/// file. `License-Expression` is preferred over the free form `License` field.
+fn parse_metadata_license(metadata: &str) -> Option<(String, pep440_rs::Version, String)> {
+    let mut name = None;
+    let mut version = None;
+    let mut expression = None;
+    let mut license = None;
+    for line in metadata.lines() {
+        // The headers end at the first empty line, after that the description
+        // follows.
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Name: ") {
+            name = Some(Ecosystem::PyPI.normalize_name(value.trim()));
+        } else if let Some(value) = line.strip_prefix("Version: ") {
+            version = pep440_rs::Version::from_str(value.trim()).ok();
+        } else if let Some(value) = line.strip_prefix("License-Expression: ") {
+            expression = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("License: ") {
+            license = Some(value.trim().to_string());
+        }
+    }
+    Some((name?, version?, expression.or(license)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_metadata_license() {
+        let metadata = "Metadata-Version: 2.4\nName: My_Package\nVersion: 1.0\nLicense: BSD\nLicense-Expression: MIT\n\nLicense: not a header\n";
+        assert_eq!(
+            parse_metadata_license(metadata),
+            Some((
+                "my-package".to_string(),
+                pep440_rs::Version::from_str("1.0").unwrap(),
+                "MIT".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_audit_packages() {
+        let mut database = AdvisoryDatabase::default();
+        database.insert(
+            serde_json::from_str(
+                r#"{
+                    "id": "GHSA-1",
+                    "affected": [{
+                        "package": { "ecosystem": "PyPI", "name": "numpy" },
+                        "versions": ["1.0.0"]
+                    }]
+                }"#,
+            )
+            .unwrap(),
+        );
+
+        let package = AuditedPackage {
+            ecosystem: Ecosystem::Conda,
+            name: "numpy".to_string(),
+            version: "1.0.0".to_string(),
+            license: Some("BSD-3-Clause".to_string()),
+            provides: vec![("numpy".to_string(), "1.0.0".to_string())],
+        };
+
+        let findings = audit_packages([package.clone()], &database, &AuditPolicy::default());
+        assert_eq!(findings.vulnerabilities.len(), 1);
+        assert!(findings.has_failures());
+
+        let policy = AuditPolicy {
+            ignore_advisories: vec!["GHSA-1".to_string()],
+            ..AuditPolicy::default()
+        };
+        let findings = audit_packages([package], &database, &policy);
+        assert!(!findings.has_failures());
+    }
+}
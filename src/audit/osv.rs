@@ -0,0 +1,395 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    path::Path,
+    str::FromStr,
+};
+
+use miette::{Context, IntoDiagnostic};
+use serde::Deserialize;
+
+/// The ecosystems of packages that can be locked by pixi.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Ecosystem {
+    Conda,
+    PyPI,
+}
+
+impl Ecosystem {
+    /// Parses the ecosystem as used in OSV advisories. Unsupported ecosystems
+    /// return `None`.
+    fn from_osv(ecosystem: &str) -> Option<Self> {
+        match ecosystem.to_ascii_lowercase().as_str() {
+            "conda" | "conda-forge" => Some(Self::Conda),
+            "pypi" => Some(Self::PyPI),
+            _ => None,
+        }
+    }
+
+    /// Normalizes a package name the way the ecosystem compares names.
+    pub(crate) fn normalize_name(self, name: &str) -> String {
+        match self {
+            Self::Conda => name.to_ascii_lowercase(),
+            Self::PyPI => pep508_rs::PackageName::from_str(name)
+                .map(|name| name.to_string())
+                .unwrap_or_else(|_| name.to_ascii_lowercase()),
+        }
+    }
+
+    /// Parses the `version` with the version semantics of the ecosystem.
+    /// Returns `None` if the version is invalid.
+    fn parse_version(self, version: &str) -> Option<EcosystemVersion> {
+        match self {
+            Self::Conda => rattler_conda_types::Version::from_str(version)
+                .ok()
+                .map(EcosystemVersion::Conda),
+            Self::PyPI => pep440_rs::Version::from_str(version)
+                .ok()
+                .map(EcosystemVersion::PyPI),
+        }
+    }
+}
+
+/// A version parsed with the semantics of its ecosystem. Versions are only
+/// compared with versions of the same ecosystem.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum EcosystemVersion {
+    Conda(rattler_conda_types::Version),
+    PyPI(pep440_rs::Version),
+}
+
+impl Display for Ecosystem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Conda => write!(f, "conda"),
+            Self::PyPI => write!(f, "pypi"),
+        }
+    }
+}
+
+/// A single advisory in the [OSV format](https://ossf.github.io/osv-schema/).
+///
+/// Only the fields that are required to match packages are deserialized.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Advisory {
+    pub id: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub withdrawn: Option<String>,
+    #[serde(default)]
+    pub severity: Vec<Severity>,
+    #[serde(default)]
+    pub database_specific: Option<serde_json::Value>,
+    #[serde(default)]
+    pub affected: Vec<Affected>,
+}
+
+impl Advisory {
+    /// Returns true if the advisory is known under the given identifier.
+    pub fn is_known_as(&self, id: &str) -> bool {
+        self.id == id || self.aliases.iter().any(|alias| alias == id)
+    }
+
+    /// Returns a human readable severity if the advisory contains one.
+    pub fn severity(&self) -> Option<String> {
+        self.database_specific
+            .as_ref()
+            .and_then(|value| value.get("severity"))
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+            .or_else(|| self.severity.first().map(|s| s.score.clone()))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Severity {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub score: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Affected {
+    pub package: AffectedPackage,
+    #[serde(default)]
+    pub ranges: Vec<AffectedRange>,
+    #[serde(default)]
+    pub versions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AffectedPackage {
+    pub ecosystem: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AffectedRange {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub events: Vec<RangeEvent>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeEvent {
+    Introduced(String),
+    Fixed(String),
+    LastAffected(String),
+    Limit(String),
+}
+
+impl RangeEvent {
+    fn version(&self) -> &str {
+        match self {
+            RangeEvent::Introduced(v)
+            | RangeEvent::Fixed(v)
+            | RangeEvent::LastAffected(v)
+            | RangeEvent::Limit(v) => v,
+        }
+    }
+}
+
+impl AffectedRange {
+    /// Returns true if the `version` lies within this range.
+    ///
+    /// Only `ECOSYSTEM` ranges are supported because the other range types
+    /// (`SEMVER` and `GIT`) do not apply to the packages in a lock-file.
+    fn contains(&self, ecosystem: Ecosystem, version: &str) -> bool {
+        if !self.kind.eq_ignore_ascii_case("ECOSYSTEM") {
+            return false;
+        }
+
+        let Some(version) = ecosystem.parse_version(version) else {
+            return false;
+        };
+
+        // Walk the events in version order. The version "0" is represented
+        // as `None` which sorts before every other version, events with an
+        // invalid version can't be ordered and are skipped.
+        let mut events = self
+            .events
+            .iter()
+            .filter_map(|event| match event.version() {
+                "0" => Some((None, event)),
+                event_version => Some((Some(ecosystem.parse_version(event_version)?), event)),
+            })
+            .collect::<Vec<_>>();
+        events.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut affected = false;
+        for (bound, event) in events {
+            let ordering = bound.as_ref().cmp(&Some(&version));
+            match event {
+                RangeEvent::Introduced(_) if ordering.is_le() => affected = true,
+                RangeEvent::Fixed(_) if ordering.is_le() => affected = false,
+                RangeEvent::LastAffected(_) if ordering.is_lt() => affected = false,
+                _ => {}
+            }
+        }
+        affected
+    }
+}
+
+/// An offline collection of OSV advisories.
+#[derive(Debug, Default)]
+pub struct AdvisoryDatabase {
+    advisories: Vec<Advisory>,
+
+    /// Maps the ecosystem and normalized package name to the indices of the
+    /// advisories that affect a package with that name.
+    by_package: HashMap<(Ecosystem, String), Vec<usize>>,
+}
+
+impl AdvisoryDatabase {
+    /// Reads all `*.json` advisories from the given directory (recursively).
+    pub fn from_dir(path: &Path) -> miette::Result<Self> {
+        let mut database = Self::default();
+        let mut pending = vec![path.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let entries = fs_err::read_dir(&dir).into_diagnostic()?;
+            for entry in entries {
+                let path = entry.into_diagnostic()?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if path.extension().is_some_and(|ext| ext == "json") {
+                    let contents = fs_err::read_to_string(&path).into_diagnostic()?;
+                    let advisory: Advisory = serde_json::from_str(&contents)
+                        .into_diagnostic()
+                        .with_context(|| format!("failed to parse advisory {}", path.display()))?;
+                    database.insert(advisory);
+                }
+            }
+        }
+        Ok(database)
+    }
+
+    /// Adds a single advisory to the database. Withdrawn advisories are
+    /// ignored.
+    pub fn insert(&mut self, advisory: Advisory) {
+        if advisory.withdrawn.is_some() {
+            return;
+        }
+
+        let idx = self.advisories.len();
+        for affected in &advisory.affected {
+            let Some(ecosystem) = Ecosystem::from_osv(&affected.package.ecosystem) else {
+                continue;
+            };
+            let entry = self
+                .by_package
+                .entry((ecosystem, ecosystem.normalize_name(&affected.package.name)))
+                .or_default();
+            if entry.last() != Some(&idx) {
+                entry.push(idx);
+            }
+        }
+        self.advisories.push(advisory);
+    }
+
+    /// Returns the number of advisories in the database.
+    pub fn len(&self) -> usize {
+        self.advisories.len()
+    }
+
+    /// Returns all advisories that affect the given package.
+    pub fn find(&self, ecosystem: Ecosystem, name: &str, version: &str) -> Vec<&Advisory> {
+        let name = ecosystem.normalize_name(name);
+        let Some(indices) = self.by_package.get(&(ecosystem, name.clone())) else {
+            return Vec::new();
+        };
+
+        indices
+            .iter()
+            .map(|&idx| &self.advisories[idx])
+            .filter(|advisory| {
+                advisory.affected.iter().any(|affected| {
+                    Ecosystem::from_osv(&affected.package.ecosystem) == Some(ecosystem)
+                        && ecosystem.normalize_name(&affected.package.name) == name
+                        && (affected.versions.iter().any(|v| v == version)
+                            || affected
+                                .ranges
+                                .iter()
+                                .any(|range| range.contains(ecosystem, version)))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn advisory(json: &str) -> AdvisoryDatabase {
+        let mut database = AdvisoryDatabase::default();
+        database.insert(serde_json::from_str(json).unwrap());
+        database
+    }
+
+    #[test]
+    fn test_ecosystem_range() {
+        let database = advisory(
+            r#"{
+                "id": "GHSA-0000-0000-0000",
+                "affected": [{
+                    "package": { "ecosystem": "PyPI", "name": "Jinja2" },
+                    "ranges": [{
+                        "type": "ECOSYSTEM",
+                        "events": [{ "introduced": "0" }, { "fixed": "3.1.3" }]
+                    }]
+                }]
+            }"#,
+        );
+
+        assert_eq!(database.find(Ecosystem::PyPI, "jinja2", "3.1.2").len(), 1);
+        assert!(database.find(Ecosystem::PyPI, "jinja2", "3.1.3").is_empty());
+        assert!(database
+            .find(Ecosystem::Conda, "jinja2", "3.1.2")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_multiple_ranges_and_versions() {
+        let database = advisory(
+            r#"{
+                "id": "OSV-1",
+                "aliases": ["CVE-2024-0001"],
+                "affected": [{
+                    "package": { "ecosystem": "conda", "name": "openssl" },
+                    "ranges": [{
+                        "type": "ECOSYSTEM",
+                        "events": [
+                            { "introduced": "3.0.0" }, { "fixed": "3.0.8" },
+                            { "introduced": "3.1.0" }, { "last_affected": "3.1.2" }
+                        ]
+                    }],
+                    "versions": ["1.1.1w"]
+                }]
+            }"#,
+        );
+
+        assert_eq!(database.find(Ecosystem::Conda, "openssl", "3.0.1").len(), 1);
+        assert!(database
+            .find(Ecosystem::Conda, "openssl", "3.0.8")
+            .is_empty());
+        assert_eq!(database.find(Ecosystem::Conda, "openssl", "3.1.2").len(), 1);
+        assert!(database
+            .find(Ecosystem::Conda, "openssl", "3.1.3")
+            .is_empty());
+        assert_eq!(
+            database.find(Ecosystem::Conda, "openssl", "1.1.1w").len(),
+            1
+        );
+        assert!(database.advisories[0].is_known_as("CVE-2024-0001"));
+    }
+
+    #[test]
+    fn test_invalid_event_versions() {
+        let database = advisory(
+            r#"{
+                "id": "OSV-2",
+                "affected": [{
+                    "package": { "ecosystem": "PyPI", "name": "foo" },
+                    "ranges": [{
+                        "type": "ECOSYSTEM",
+                        "events": [
+                            { "fixed": "2.0" }, { "introduced": "not a version" },
+                            { "introduced": "0" }, { "fixed": "also not a version" },
+                            { "introduced": "3.0" }
+                        ]
+                    }]
+                }]
+            }"#,
+        );
+
+        // The events with invalid versions are ignored.
+        assert_eq!(database.find(Ecosystem::PyPI, "foo", "1.0").len(), 1);
+        assert!(database.find(Ecosystem::PyPI, "foo", "2.5").is_empty());
+        assert_eq!(database.find(Ecosystem::PyPI, "foo", "3.1").len(), 1);
+        assert!(database
+            .find(Ecosystem::PyPI, "foo", "not a version")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_withdrawn_advisory() {
+        let database = advisory(
+            r#"{
+                "id": "OSV-2",
+                "withdrawn": "2024-01-01T00:00:00Z",
+                "affected": [{
+                    "package": { "ecosystem": "PyPI", "name": "requests" },
+                    "versions": ["2.0.0"]
+                }]
+            }"#,
+        );
+        assert!(database
+            .find(Ecosystem::PyPI, "requests", "2.0.0")
+            .is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    io::{stdout, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use clap::Parser;
+use fancy_display::FancyDisplay;
+use itertools::Itertools;
+use miette::IntoDiagnostic;
+use pixi_consts::consts;
+use pixi_manifest::{EnvironmentName, FeaturesExt};
+use rattler_conda_types::Platform;
+use serde::Serialize;
+use tabwriter::TabWriter;
+
+use crate::{
+    audit::{
+        audit_packages, installed_pypi_licenses, AdvisoryDatabase, AuditFindings, AuditedPackage,
+        Ecosystem,
+    },
+    cli::cli_config::{PrefixUpdateConfig, ProjectConfig},
+    lock_file::UpdateLockFileOptions,
+    Project,
+};
+
+/// Audit the locked packages for known vulnerabilities and license issues.
+///
+/// The packages are matched against an offline database of advisories in the
+/// OSV format and the licenses are checked against the `[workspace.audit]`
+/// policy of the manifest. Exits with a non-zero exit code if any issue is
+/// found.
+#[derive(Debug, Parser)]
+#[clap(arg_required_else_help = false)]
+pub struct Args {
+    #[clap(flatten)]
+    pub project_config: ProjectConfig,
+
+    /// The environments to audit. Defaults to all environments.
+    #[arg(short, long)]
+    pub environment: Option<Vec<EnvironmentName>>,
+
+    /// The platforms to audit. Defaults to all platforms of the selected
+    /// environments.
+    #[arg(short, long)]
+    pub platform: Option<Vec<Platform>>,
+
+    /// A directory containing OSV advisories (`*.json`). Overrides the
+    /// `advisory-db` of the manifest.
+    #[arg(long, env = "PIXI_ADVISORY_DB")]
+    pub advisory_db: Option<PathBuf>,
+
+    /// Also list the licenses of all packages, not only violations.
+    #[arg(long)]
+    pub licenses: bool,
+
+    /// Output the findings in JSON format.
+    #[arg(long)]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub prefix_update_config: PrefixUpdateConfig,
+}
+
+/// The findings for a single environment and platform.
+#[derive(Serialize)]
+struct TargetFindings {
+    environment: String,
+    platform: Platform,
+    #[serde(flatten)]
+    findings: AuditFindings,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.project_config.manifest_path.as_deref())?
+        .with_cli_config(args.prefix_update_config.config.clone());
+
+    let policy = project
+        .manifest()
+        .workspace
+        .workspace
+        .audit
+        .clone()
+        .unwrap_or_default();
+
+    let advisory_db = args.advisory_db.clone().or_else(|| {
+        policy
+            .advisory_db
+            .as_ref()
+            .map(|path| project.root().join(path))
+    });
+    let database = match advisory_db {
+        Some(path) => {
+            let database = AdvisoryDatabase::from_dir(&path)?;
+            tracing::info!(
+                "loaded {} advisories from {}",
+                database.len(),
+                path.display()
+            );
+            database
+        }
+        None => {
+            tracing::warn!(
+                "no advisory database configured, only licenses are audited. Use `--advisory-db` or set `advisory-db` in the `[workspace.audit]` table."
+            );
+            AdvisoryDatabase::default()
+        }
+    };
+
+    let lock_file = project
+        .update_lock_file(UpdateLockFileOptions {
+            lock_file_usage: args.prefix_update_config.lock_file_usage(),
+            no_install: true,
+            max_concurrent_solves: project.config().max_concurrent_solves(),
+        })
+        .await?
+        .lock_file;
+
+    let environments = match &args.environment {
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                project.environment(name).ok_or_else(|| {
+                    miette::miette!("unknown environment '{}'", name.fancy_display())
+                })
+            })
+            .collect::<miette::Result<Vec<_>>>()?,
+        None => project.environments(),
+    };
+
+    let mut results = Vec::new();
+    for environment in environments {
+        let Some(locked_environment) = lock_file.environment(environment.name().as_str()) else {
+            continue;
+        };
+
+        // PyPI packages in the lock-file don't record a license, use the metadata of
+        // the installed packages if the environment is installed. The environment is
+        // only installed for the best platform of the current machine.
+        let pypi_licenses = installed_pypi_licenses(&environment.dir());
+        let installed_platform = environment.best_platform();
+
+        for platform in environment
+            .platforms()
+            .into_iter()
+            .sorted_by_key(|p| p.as_str())
+        {
+            if args
+                .platform
+                .as_ref()
+                .is_some_and(|platforms| !platforms.contains(&platform))
+            {
+                continue;
+            }
+            let Some(packages) = locked_environment.packages(platform) else {
+                continue;
+            };
+
+            let packages = packages
+                .map(AuditedPackage::from_locked)
+                .map(|mut package| {
+                    if package.license.is_none()
+                        && package.ecosystem == Ecosystem::PyPI
+                        && platform == installed_platform
+                    {
+                        // Only use the metadata if the installed version is the locked
+                        // version, the environment might be out of date.
+                        let locked_version = pep440_rs::Version::from_str(&package.version).ok();
+                        package.license = pypi_licenses
+                            .get(&package.name)
+                            .filter(|(version, _)| Some(version) == locked_version.as_ref())
+                            .map(|(_, license)| license.clone());
+                    }
+                    package
+                });
+
+            results.push(TargetFindings {
+                environment: environment.name().to_string(),
+                platform,
+                findings: audit_packages(packages, &database, &policy),
+            });
+        }
+    }
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&results).into_diagnostic()?;
+        println!("{}", json);
+    } else {
+        print_findings(&results, args.licenses).into_diagnostic()?;
+    }
+
+    let vulnerabilities = results
+        .iter()
+        .map(|r| r.findings.vulnerabilities.len())
+        .sum::<usize>();
+    let violations = results
+        .iter()
+        .flat_map(|r| r.findings.licenses.iter())
+        .filter(|l| l.violation)
+        .count();
+    if vulnerabilities > 0 || violations > 0 {
+        miette::bail!(
+            "found {vulnerabilities} vulnerable package(s) and {violations} license violation(s)"
+        );
+    }
+
+    if !args.json {
+        eprintln!(
+            "{}No vulnerabilities or license violations found",
+            console::style(console::Emoji("✔ ", "")).green()
+        );
+    }
+
+    Ok(())
+}
+
+fn print_findings(results: &[TargetFindings], all_licenses: bool) -> std::io::Result<()> {
+    let mut writer = TabWriter::new(stdout());
+    for result in results {
+        let licenses = result
+            .findings
+            .licenses
+            .iter()
+            .filter(|l| all_licenses || l.violation)
+            .collect_vec();
+        if result.findings.vulnerabilities.is_empty() && licenses.is_empty() {
+            continue;
+        }
+
+        writeln!(
+            writer,
+            "{} {}",
+            consts::ENVIRONMENT_STYLE.apply_to(&result.environment),
+            consts::PLATFORM_STYLE.apply_to(result.platform)
+        )?;
+
+        for vulnerability in &result.findings.vulnerabilities {
+            writeln!(
+                writer,
+                "  {}\t{}\t{}\t{}\t{}",
+                console::style(&vulnerability.name).red(),
+                vulnerability.version,
+                vulnerability.id,
+                vulnerability.severity.as_deref().unwrap_or("-"),
+                vulnerability.summary.as_deref().unwrap_or_default()
+            )?;
+        }
+
+        for license in licenses {
+            let license_str = license.license.as_deref().unwrap_or("unknown");
+            let license_str = if license.violation {
+                console::style(license_str).red()
+            } else {
+                console::style(license_str).green()
+            };
+            writeln!(
+                writer,
+                "  {}\t{}\t{}",
+                license.name, license.version, license_str
+            )?;
+        }
+    }
+    writer.flush()
+}
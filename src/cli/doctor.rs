@@ -0,0 +1,667 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use fancy_display::FancyDisplay;
+use indicatif::HumanBytes;
+use itertools::Itertools;
+use miette::IntoDiagnostic;
+use pixi_config;
+use rattler_lock::LockFile;
+use serde::Serialize;
+
+use crate::{
+    activation::try_get_valid_activation_cache,
+    cli::{
+        cli_config::ProjectConfig,
+        info::{collect_info, Info},
+    },
+    environment::previous_prefix_location,
+    global::{trampoline::diagnose_bin_dir, BinDir},
+    load_lock_file,
+    lock_file::OutdatedEnvironments,
+    project::{
+        virtual_packages::{
+            verify_current_platform_has_required_virtual_packages, VerifyCurrentPlatformError,
+        },
+        Environment,
+    },
+    Project,
+};
+
+/// Below this amount of free space in the cache directory a warning is
+/// reported.
+const LOW_DISK_SPACE: u64 = 1024 * 1024 * 1024;
+
+/// Below this amount of free space in the cache directory an error is
+/// reported, installing packages will most likely fail.
+const CRITICAL_DISK_SPACE: u64 = 100 * 1024 * 1024;
+
+/// Check the system, the project and the global installation for common
+/// problems.
+///
+/// Next to the information reported by `pixi info` this checks for moved
+/// environments, stale activation caches, broken global executables, the
+/// permissions and free space of the cache directory, system requirements that
+/// the machine doesn't meet and an outdated lock file. Every problem comes
+/// with a suggestion on how to fix it.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Whether to show the output as JSON or not
+    #[arg(long)]
+    json: bool,
+
+    #[clap(flatten)]
+    pub project_config: ProjectConfig,
+}
+
+/// How severe the result of a check is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// The result of a single check.
+#[derive(Debug, Serialize)]
+struct Check {
+    /// The kind of check, e.g. `prefix-location`.
+    check: &'static str,
+
+    /// What was checked, e.g. the name of an environment or an executable.
+    subject: String,
+
+    severity: Severity,
+
+    message: String,
+
+    /// How to fix the problem.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(check: &'static str, subject: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            check,
+            subject: subject.into(),
+            severity: Severity::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(
+        check: &'static str,
+        subject: impl Into<String>,
+        message: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            check,
+            subject: subject.into(),
+            severity: Severity::Warning,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn error(
+        check: &'static str,
+        subject: impl Into<String>,
+        message: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            check,
+            subject: subject.into(),
+            severity: Severity::Error,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let marker = match self.severity {
+            Severity::Ok => console::style(console::Emoji("✔ ", "ok ")).green(),
+            Severity::Warning => console::style(console::Emoji("⚠ ", "! ")).yellow(),
+            Severity::Error => console::style(console::Emoji("✘ ", "x ")).red(),
+        };
+        write!(
+            f,
+            "{marker}{} {}",
+            console::style(format!("[{}]", self.subject)).bold(),
+            self.message
+        )?;
+        if let Some(fix) = &self.fix {
+            write!(f, "\n    {} {}", console::style("fix:").cyan(), fix)?;
+        }
+        Ok(())
+    }
+}
+
+/// The report of `pixi doctor`, the information of `pixi info` together with
+/// the results of all checks.
+#[derive(Serialize)]
+struct Report {
+    info: Info,
+    checks: Vec<Check>,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.project_config.manifest_path.as_deref()).ok();
+    let info = collect_info(project.as_ref(), false).await?;
+
+    let mut checks = check_cache_dir(&pixi_config::get_cache_dir()?);
+    checks.extend(check_global_executables().await);
+    if let Some(project) = &project {
+        checks.extend(check_project(project).await);
+    }
+
+    let errors = checks
+        .iter()
+        .filter(|check| check.severity == Severity::Error)
+        .count();
+    let warnings = checks
+        .iter()
+        .filter(|check| check.severity == Severity::Warning)
+        .count();
+
+    if args.json {
+        let report = Report { info, checks };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).into_diagnostic()?
+        );
+    } else {
+        eprintln!("{}", render_checks(&checks));
+    }
+
+    Project::warn_on_discovered_from_env(args.project_config.manifest_path.as_deref());
+
+    if errors > 0 {
+        miette::bail!(
+            help = "apply the suggested fixes and run `pixi doctor` again",
+            "found {errors} problem{} and {warnings} warning{}",
+            if errors == 1 { "" } else { "s" },
+            if warnings == 1 { "" } else { "s" },
+        );
+    }
+    if !args.json {
+        eprintln!(
+            "{}No problems found{}",
+            console::style(console::Emoji("✔ ", "")).green(),
+            match warnings {
+                0 => String::new(),
+                1 => String::from(", but there is 1 warning"),
+                _ => format!(", but there are {warnings} warnings"),
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Renders the results grouped by the kind of check, in the order in which
+/// the kinds were first checked.
+fn render_checks(checks: &[Check]) -> String {
+    let mut output = String::new();
+    for kind in checks.iter().map(|check| check.check).unique() {
+        output.push_str(&format!("{}\n", console::style(kind).bold().cyan()));
+        for result in checks.iter().filter(|check| check.check == kind) {
+            output.push_str(&format!("  {result}\n"));
+        }
+    }
+    output
+}
+
+/// Formats an error together with its causes on a single line.
+fn error_message(err: &miette::Report) -> String {
+    err.chain().map(ToString::to_string).join(": ")
+}
+
+/// Checks that the cache directory is writable and has enough free space.
+fn check_cache_dir(cache_dir: &Path) -> Vec<Check> {
+    const CHECK: &str = "cache-dir";
+    let subject = cache_dir.display().to_string();
+
+    // The cache directory is created on first use, so check the closest
+    // directory that exists.
+    let Some(existing) = cache_dir.ancestors().find(|path| path.is_dir()) else {
+        return vec![Check::error(
+            CHECK,
+            subject,
+            "none of the parent directories of the cache directory exist",
+            "set `PIXI_CACHE_DIR` to an existing directory",
+        )];
+    };
+
+    let mut checks = Vec::new();
+    match tempfile::tempfile_in(existing) {
+        Ok(_) => checks.push(Check::ok(
+            CHECK,
+            &subject,
+            "the cache directory is writable",
+        )),
+        Err(err) => checks.push(Check::error(
+            CHECK,
+            &subject,
+            format!("the cache directory is not writable: {err}"),
+            format!(
+                "fix the permissions of {} or set `PIXI_CACHE_DIR` to a writable directory",
+                existing.display()
+            ),
+        )),
+    }
+
+    match fs4::available_space(existing) {
+        Ok(available) => {
+            let message = format!("{} of free disk space", HumanBytes(available));
+            let fix = "free up disk space, e.g. by running `pixi clean cache`, or set `PIXI_CACHE_DIR` to a different disk";
+            checks.push(if available < CRITICAL_DISK_SPACE {
+                Check::error(CHECK, &subject, message, fix)
+            } else if available < LOW_DISK_SPACE {
+                Check::warning(CHECK, &subject, message, fix)
+            } else {
+                Check::ok(CHECK, &subject, message)
+            });
+        }
+        Err(err) => tracing::debug!(
+            "failed to determine the free space of {}: {err}",
+            existing.display()
+        ),
+    }
+
+    checks
+}
+
+const GLOBAL_EXECUTABLES_CHECK: &str = "global-executables";
+
+/// Checks the exposed executables of `pixi global`.
+async fn check_global_executables() -> Vec<Check> {
+    match BinDir::from_env().await {
+        Ok(bin_dir) => check_bin_dir(bin_dir.path()).await,
+        Err(err) => vec![Check::error(
+            GLOBAL_EXECUTABLES_CHECK,
+            "global",
+            format!(
+                "failed to determine the global bin directory: {}",
+                error_message(&err)
+            ),
+            "fix the configuration of `pixi global`, e.g. the `PIXI_HOME` environment variable",
+        )],
+    }
+}
+
+/// Checks the exposed executables in the given bin directory.
+async fn check_bin_dir(bin_dir: &Path) -> Vec<Check> {
+    const CHECK: &str = GLOBAL_EXECUTABLES_CHECK;
+    let subject = bin_dir.display().to_string();
+
+    if !bin_dir.is_dir() {
+        return vec![Check::ok(CHECK, subject, "no executables are exposed")];
+    }
+
+    let issues = match diagnose_bin_dir(bin_dir).await {
+        Ok(issues) => issues,
+        Err(err) => {
+            return vec![Check::error(
+                CHECK,
+                subject,
+                format!(
+                    "failed to check the exposed executables: {}",
+                    error_message(&err)
+                ),
+                "run `pixi global sync` to recreate the trampolines",
+            )]
+        }
+    };
+    if issues.is_empty() {
+        return vec![Check::ok(
+            CHECK,
+            subject,
+            "all exposed executables are valid",
+        )];
+    }
+
+    issues
+        .into_iter()
+        .map(|(path, issue)| {
+            let subject = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string());
+            if issue.is_error() {
+                Check::error(
+                    CHECK,
+                    subject,
+                    issue.to_string(),
+                    "run `pixi global sync` to recreate the trampolines",
+                )
+            } else {
+                Check::warning(
+                    CHECK,
+                    subject,
+                    issue.to_string(),
+                    "nothing to do, the executable refreshes its activation on its next run",
+                )
+            }
+        })
+        .collect()
+}
+
+/// Checks the lock file and the environments of the project. A lock file that
+/// can't be read is reported instead of aborting the other checks.
+async fn check_project(project: &Project) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let lock_file = match load_lock_file(project).await {
+        Ok(lock_file) => {
+            checks.extend(check_lock_file(project, &lock_file).await);
+            Some(lock_file)
+        }
+        Err(err) => {
+            checks.push(Check::error(
+                "lock-file",
+                project.lock_file_path().display().to_string(),
+                format!("the lock file can't be read: {}", error_message(&err)),
+                format!(
+                    "remove {} and run `pixi lock` to recreate it",
+                    project.lock_file_path().display()
+                ),
+            ));
+            None
+        }
+    };
+    for environment in project.environments() {
+        checks.extend(check_environment(&environment, lock_file.as_ref()).await);
+    }
+    checks.extend(check_orphaned_activation_caches(project));
+    checks
+}
+
+/// Checks whether the lock file exists and is up-to-date with the manifest.
+async fn check_lock_file(project: &Project, lock_file: &LockFile) -> Vec<Check> {
+    const CHECK: &str = "lock-file";
+    let subject = project.lock_file_path().display().to_string();
+
+    if !project.lock_file_path().is_file() {
+        return vec![Check::warning(
+            CHECK,
+            subject,
+            "the project doesn't have a lock file",
+            "run `pixi lock` to create it",
+        )];
+    }
+
+    let outdated =
+        OutdatedEnvironments::from_project_and_lock_file(project, lock_file, Default::default())
+            .await;
+    if outdated.is_empty() {
+        return vec![Check::ok(
+            CHECK,
+            subject,
+            "the lock file is up-to-date with the manifest",
+        )];
+    }
+
+    let outdated_platforms = outdated
+        .conda
+        .iter()
+        .chain(outdated.pypi.iter())
+        .map(|(environment, platforms)| (environment.name().clone(), platforms))
+        .into_group_map();
+    outdated_platforms
+        .into_iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(environment, platforms)| {
+            let platforms = platforms
+                .into_iter()
+                .flatten()
+                .map(|platform| platform.as_str())
+                .unique()
+                .sorted()
+                .join(", ");
+            Check::warning(
+                CHECK,
+                subject.clone(),
+                format!(
+                    "the lock file is out of date for environment {} on {platforms}",
+                    environment.fancy_display()
+                ),
+                "run `pixi lock` to update the lock file",
+            )
+        })
+        .collect()
+}
+
+/// Checks a single environment of the project. The activation cache is only
+/// checked if the lock file could be read.
+async fn check_environment(
+    environment: &Environment<'_>,
+    lock_file: Option<&LockFile>,
+) -> Vec<Check> {
+    let subject = environment.name().to_string();
+    let mut checks = Vec::new();
+
+    match verify_current_platform_has_required_virtual_packages(environment) {
+        Ok(()) => checks.push(Check::ok(
+            "system-requirements",
+            &subject,
+            "the system meets the system requirements",
+        )),
+        Err(VerifyCurrentPlatformError::UnsupportedPlatform(err)) => {
+            checks.push(Check::warning(
+                "system-requirements",
+                &subject,
+                err.to_string(),
+                format!(
+                    "add the platform with `pixi project platform add {}` if the environment should be available",
+                    err.platform
+                ),
+            ))
+        }
+        Err(err) => checks.push(Check::error(
+            "system-requirements",
+            &subject,
+            err.to_string(),
+            "lower the requirement in the `[system-requirements]` table, or override the detected version with the matching `CONDA_OVERRIDE_*` environment variable",
+        )),
+    }
+
+    match previous_prefix_location(&environment.dir()) {
+        Ok(None) => {}
+        Ok(Some(previous)) => checks.push(Check::error(
+            "prefix-location",
+            &subject,
+            format!(
+                "the environment was moved from {} to {}, environments are not relocatable",
+                previous.display(),
+                environment.dir().display()
+            ),
+            format!(
+                "run `pixi clean -e {}` and `pixi install -e {}` to recreate it",
+                environment.name(),
+                environment.name()
+            ),
+        )),
+        Err(err) => checks.push(Check::error(
+            "prefix-location",
+            &subject,
+            format!("failed to read the location of the environment: {err}"),
+            format!(
+                "run `pixi clean -e {}` to remove the environment",
+                environment.name()
+            ),
+        )),
+    }
+
+    let cache_file = environment.activation_cache_file_path();
+    if let Some(lock_file) = lock_file.filter(|_| cache_file.is_file()) {
+        let is_valid = try_get_valid_activation_cache(lock_file, environment, cache_file)
+            .await
+            .is_some();
+        if !is_valid {
+            checks.push(Check::warning(
+                "activation-cache",
+                &subject,
+                "the activation cache is stale, it is recomputed on the next activation",
+                format!(
+                    "run `pixi clean -e {} --activation-cache` to remove it",
+                    environment.name()
+                ),
+            ));
+        }
+    }
+
+    checks
+}
+
+/// Finds activation caches of environments that no longer exist.
+fn check_orphaned_activation_caches(project: &Project) -> Vec<Check> {
+    let known = project
+        .environments()
+        .iter()
+        .map(|environment| environment.activation_cache_file_path())
+        .collect::<Vec<PathBuf>>();
+
+    let Ok(entries) = fs_err::read_dir(project.activation_env_cache_folder()) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && !known.contains(path))
+        .sorted()
+        .map(|path| {
+            Check::warning(
+                "activation-cache",
+                path.display().to_string(),
+                "the activation cache belongs to an environment that no longer exists",
+                format!("remove {}", path.display()),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_project(dir: &Path) -> Project {
+        Project::from_str(
+            &dir.join("pixi.toml"),
+            r#"
+        [project]
+        name = "foobar"
+        channels = []
+        platforms = []
+
+        [feature.test.dependencies]
+
+        [environments]
+        test = ["test"]
+        "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_check_cache_dir() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // The cache directory doesn't have to exist yet.
+        let checks = check_cache_dir(&dir.path().join("does-not-exist"));
+        let writable = checks.first().unwrap();
+        assert_eq!(writable.check, "cache-dir");
+        assert_eq!(writable.severity, Severity::Ok);
+        assert!(writable.fix.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_check_project_with_invalid_lock_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = test_project(dir.path());
+        fs_err::write(project.lock_file_path(), "not a lock file").unwrap();
+
+        // The other checks still run when the lock file can't be read.
+        let checks = check_project(&project).await;
+        let lock_file = checks
+            .iter()
+            .find(|check| check.check == "lock-file")
+            .unwrap();
+        assert_eq!(lock_file.severity, Severity::Error);
+        assert!(lock_file.fix.as_ref().unwrap().contains("pixi lock"));
+        assert_eq!(
+            checks
+                .iter()
+                .filter(|check| check.check == "system-requirements")
+                .map(|check| check.subject.as_str())
+                .collect_vec(),
+            vec!["default", "test"]
+        );
+    }
+
+    #[test]
+    fn test_check_orphaned_activation_caches() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = test_project(dir.path());
+        assert!(check_orphaned_activation_caches(&project).is_empty());
+
+        let orphan = project.activation_env_cache_folder().join("removed.json");
+        fs_err::create_dir_all(project.activation_env_cache_folder()).unwrap();
+        fs_err::write(&orphan, "{}").unwrap();
+        let checks = check_orphaned_activation_caches(&project);
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].severity, Severity::Warning);
+        assert_eq!(checks[0].subject, orphan.display().to_string());
+    }
+
+    #[tokio::test]
+    async fn test_check_bin_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin_dir = dir.path().join("bin");
+
+        let checks = check_bin_dir(&bin_dir).await;
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].severity, Severity::Ok);
+
+        // A configuration without a trampoline.
+        let configuration_dir = bin_dir.join(crate::global::trampoline::TRAMPOLINE_CONFIGURATION);
+        fs_err::create_dir_all(&configuration_dir).unwrap();
+        fs_err::write(configuration_dir.join("orphan.json"), "{}").unwrap();
+        let checks = check_bin_dir(&bin_dir).await;
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].check, GLOBAL_EXECUTABLES_CHECK);
+        assert_eq!(checks[0].severity, Severity::Error);
+        assert_eq!(
+            checks[0].fix.as_deref(),
+            Some("run `pixi global sync` to recreate the trampolines")
+        );
+    }
+
+    #[test]
+    fn test_render_checks() {
+        let checks = vec![
+            Check::ok("system-requirements", "default", "first"),
+            Check::ok("prefix-location", "default", "second"),
+            Check::ok("system-requirements", "test", "third"),
+            Check::error("prefix-location", "test", "fourth", "the fix"),
+        ];
+        let output = console::strip_ansi_codes(&render_checks(&checks)).to_string();
+        assert_eq!(output.matches("system-requirements").count(), 1);
+        assert_eq!(output.matches("prefix-location").count(), 1);
+
+        // The results are grouped by kind in the order they were checked.
+        let position = |text: &str| output.find(text).unwrap();
+        assert!(position("system-requirements") < position("first"));
+        assert!(position("third") < position("prefix-location"));
+        assert!(position("prefix-location") < position("second"));
+        assert!(position("second") < position("fourth"));
+        assert!(output.contains("[test] fourth\n    fix: the fix"));
+    }
+}
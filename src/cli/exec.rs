@@ -1,28 +1,55 @@
-use std::{path::Path, str::FromStr};
+use std::{
+    io::{stdout, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
+use chrono::{DateTime, NaiveDate, Utc};
 use clap::{Parser, ValueHint};
+use indexmap::{IndexMap, IndexSet};
+use indicatif::{HumanBytes, ProgressBar};
+use itertools::Itertools;
 use miette::{Context, IntoDiagnostic};
+use pep508_rs::{Requirement, VersionOrUrl};
 use pixi_config::{self, Config, ConfigCli};
+use pixi_consts::consts;
+use pixi_manifest::{pypi::pypi_options::PypiOptions, PyPiRequirement, SystemRequirements};
 use pixi_progress::{await_in_progress, global_multi_progress, wrap_in_progress};
+use pixi_record::PixiRecord;
 use pixi_utils::{reqwest::build_reqwest_clients, EnvironmentHash, PrefixGuard};
+use pixi_uv_conversions::to_uv_normalize;
 use rattler::{
-    install::{IndicatifReporter, Installer},
+    install::{IndicatifReporter, Installer, PythonInfo},
     package_cache::PackageCache,
 };
-use rattler_conda_types::{GenericVirtualPackage, MatchSpec, PackageName, Platform};
+use rattler_conda_types::{
+    Channel, GenericVirtualPackage, MatchSpec, Matches, PackageName, Platform, RepoDataRecord,
+};
+use rattler_lock::{CondaPackageData, LockFile, LockFileBuilder, LockedPackageRef};
 use rattler_solve::{resolvo::Solver, SolverImpl, SolverTask};
 use rattler_virtual_packages::{VirtualPackage, VirtualPackageOverrides};
 use reqwest_middleware::ClientWithMiddleware;
+use serde::{Deserialize, Serialize};
+use tabwriter::TabWriter;
 
 use super::cli_config::ChannelsConfig;
-use crate::prefix::Prefix;
+use crate::{
+    install_pypi::update_python_distributions,
+    lock_file::{resolve_pypi, PypiRecord, UvResolutionContext},
+    prefix::Prefix,
+};
+
+/// The file in the root of a cached environment that describes how it was
+/// created.
+const EXEC_INFO_FILE: &str = ".pixi-exec.json";
 
 /// Run a command in a temporary environment.
 #[derive(Parser, Debug)]
 #[clap(trailing_var_arg = true, arg_required_else_help = true)]
 pub struct Args {
-    /// The executable to run.
-    #[clap(num_args = 1.., value_hint = ValueHint::CommandWithArguments)]
+    /// The executable to run. With `--list` or `--clean` only the cached
+    /// environments of this command are considered.
+    #[clap(num_args = 1.., value_hint = ValueHint::CommandWithArguments, required_unless_present_any = ["list", "clean"])]
     pub command: Vec<String>,
 
     /// Matchspecs of packages to install. If this is not provided, the package
@@ -30,6 +57,11 @@ pub struct Args {
     #[clap(long = "spec", short = 's')]
     pub specs: Vec<MatchSpec>,
 
+    /// PyPI requirements to install with uv. A python interpreter is added to
+    /// the environment, and the package is no longer guessed from the command.
+    #[clap(long = "pypi", value_name = "REQUIREMENT")]
+    pub pypi: Vec<Requirement>,
+
     #[clap(flatten)]
     channels: ChannelsConfig,
 
@@ -42,21 +74,64 @@ pub struct Args {
     #[clap(long)]
     pub force_reinstall: bool,
 
+    /// A lock file for the environment. If the file exists and satisfies the
+    /// specs the exact packages from the file are installed, otherwise the
+    /// environment is solved and the result is written to the file.
+    #[clap(long, value_name = "FILE")]
+    pub lock: Option<PathBuf>,
+
+    /// Only use packages that were published before this date (`YYYY-MM-DD`)
+    /// or RFC 3339 timestamp, this pins the environment to a snapshot of the
+    /// channels.
+    #[clap(long, value_name = "DATE", value_parser = parse_snapshot)]
+    pub snapshot: Option<DateTime<Utc>>,
+
+    /// List the cached environments instead of running a command.
+    #[clap(long, conflicts_with = "clean")]
+    pub list: bool,
+
+    /// Remove the cached environments instead of running a command.
+    #[clap(long)]
+    pub clean: bool,
+
     #[clap(flatten)]
     pub config: ConfigCli,
 }
 
+/// Describes how a cached environment was created, this is stored in the
+/// environment itself so `pixi exec --list` can show it.
+#[derive(Debug, Serialize, Deserialize)]
+struct ExecEnvironmentInfo {
+    command: String,
+    specs: Vec<String>,
+    channels: Vec<String>,
+    platform: Platform,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    snapshot: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lock_file: Option<PathBuf>,
+}
+
 /// CLI entry point for `pixi runx`
 pub async fn execute(args: Args) -> miette::Result<()> {
     let config = Config::with_cli_config(&args.config);
     let cache_dir = pixi_config::get_cache_dir().context("failed to determine cache directory")?;
 
+    if args.list {
+        return list_cached_environments(&cache_dir, args.command.first().map(String::as_str));
+    }
+    if args.clean {
+        return clean_cached_environments(&cache_dir, args.command.first().map(String::as_str))
+            .await;
+    }
+
     let mut command_args = args.command.iter();
     let command = command_args.next().ok_or_else(|| miette::miette!(help ="i.e when specifying specs explicitly use a command at the end: `pixi exec -s python==3.12 python`", "missing required command to execute",))?;
-    let (_, client) = build_reqwest_clients(Some(&config));
+    let (client, authenticated_client) = build_reqwest_clients(Some(&config));
 
     // Create the environment to run the command in.
-    let prefix = create_exec_prefix(&args, &cache_dir, &config, &client).await?;
+    let prefix =
+        create_exec_prefix(&args, &cache_dir, &config, &client, &authenticated_client).await?;
 
     // Get environment variables from the activation
     let activation_env = run_activation(&prefix).await?;
@@ -76,27 +151,110 @@ pub async fn execute(args: Args) -> miette::Result<()> {
     std::process::exit(status.code().unwrap_or(1));
 }
 
+/// The packages that are installed in a `pixi exec` environment.
+#[derive(Debug, Clone, Default)]
+struct ExecPackages {
+    conda: Vec<RepoDataRecord>,
+    pypi: Vec<PypiRecord>,
+}
+
+impl ExecPackages {
+    /// Returns the sorted locations of all packages, these uniquely identify
+    /// the environment.
+    fn locations(&self) -> Vec<String> {
+        self.conda
+            .iter()
+            .map(|record| record.url.to_string())
+            .chain(self.pypi.iter().map(|(data, _)| data.location.to_string()))
+            .sorted()
+            .collect()
+    }
+}
+
 /// Creates a prefix for the `pixi exec` command.
 pub async fn create_exec_prefix(
     args: &Args,
     cache_dir: &Path,
     config: &Config,
-    client: &ClientWithMiddleware,
+    client: &reqwest::Client,
+    authenticated_client: &ClientWithMiddleware,
 ) -> miette::Result<Prefix> {
     let command = args.command.first().expect("missing required command");
-    let specs = args.specs.clone();
-    let channels = args
-        .channels
-        .resolve_from_config(config)?
+    let channels = args.channels.resolve_from_config(config)?;
+    let channel_urls = channels
         .iter()
         .map(|c| c.base_url.to_string())
-        .collect();
+        .collect::<Vec<_>>();
 
-    let environment_hash = EnvironmentHash::new(command.clone(), specs, channels, args.platform);
+    // Determine the specs to use for the environment
+    let mut specs = if !args.specs.is_empty() {
+        args.specs.clone()
+    } else if !args.pypi.is_empty() {
+        // The command is most likely provided by one of the pypi packages.
+        Vec::new()
+    } else {
+        let guessed_spec = guess_package_spec(command);
+
+        tracing::debug!(
+            "no specs provided, guessed {} from command {command}",
+            guessed_spec
+        );
+
+        vec![guessed_spec]
+    };
+
+    // A python interpreter is required to install pypi packages.
+    let python = PackageName::new_unchecked("python");
+    if !args.pypi.is_empty() && !specs.iter().any(|spec| spec.name.as_ref() == Some(&python)) {
+        specs.push(MatchSpec {
+            name: Some(python),
+            ..Default::default()
+        });
+    }
+
+    // Use the packages from the lock file if it satisfies the specs.
+    let locked_packages = match &args.lock {
+        Some(lock_path) if lock_path.is_file() => {
+            let packages = read_exec_lock(lock_path, args.platform, &specs, &args.pypi)?;
+            if packages.is_none() {
+                tracing::info!(
+                    "{} does not satisfy the requested specs, solving the environment",
+                    lock_path.display()
+                );
+            }
+            packages
+        }
+        _ => None,
+    };
+
+    // A locked environment is identified by the exact packages it contains,
+    // otherwise by the requirements and the channels the packages are taken
+    // from.
+    let environment_hash = match &locked_packages {
+        Some(packages) => EnvironmentHash::new(
+            command.clone(),
+            args.specs.clone(),
+            packages.locations(),
+            args.platform,
+        ),
+        None => {
+            let mut hashed_channels = channel_urls.clone();
+            if let Some(snapshot) = args.snapshot {
+                hashed_channels.push(format!("exclude-newer={}", snapshot.to_rfc3339()));
+            }
+            EnvironmentHash::new(
+                command.clone(),
+                args.specs.clone(),
+                hashed_channels,
+                args.platform,
+            )
+            .with_pypi_requirements(args.pypi.iter().map(ToString::to_string).collect())
+        }
+    };
 
     let prefix = Prefix::new(
         cache_dir
-            .join(pixi_consts::consts::CACHED_ENVS_DIR)
+            .join(consts::CACHED_ENVS_DIR)
             .join(environment_hash.name()),
     );
 
@@ -109,8 +267,12 @@ pub async fn create_exec_prefix(
         .context("failed to acquire write lock to prefix guard")?;
 
     // If the environment already exists, and we are not forcing a
-    // reinstallation, we can return early.
-    if write_guard.is_ready() && !args.force_reinstall {
+    // reinstallation, we can return early. If the lock file needs to be
+    // written the environment is solved again, so the lock file matches the
+    // installed packages.
+    let is_locked = locked_packages.is_some();
+    let needs_lock = args.lock.is_some() && !is_locked;
+    if write_guard.is_ready() && !args.force_reinstall && !needs_lock {
         tracing::info!(
             "reusing existing environment in {}",
             prefix.root().display()
@@ -125,25 +287,152 @@ pub async fn create_exec_prefix(
         .into_diagnostic()
         .context("failed to write lock status to prefix guard")?;
 
-    // Construct a gateway to get repodata.
-    let gateway = config.gateway(client.clone());
+    let mut packages = match locked_packages {
+        Some(packages) => packages,
+        None => ExecPackages {
+            conda: solve_exec_environment(
+                args,
+                config,
+                authenticated_client,
+                &prefix,
+                channels,
+                specs.clone(),
+            )
+            .await?,
+            pypi: Vec::new(),
+        },
+    };
 
-    // Determine the specs to use for the environment
-    let specs = if args.specs.is_empty() {
-        let command = args.command.first().expect("missing required command");
-        let guessed_spec = guess_package_spec(command);
+    // Install the environment
+    Installer::new()
+        .with_target_platform(args.platform)
+        .with_download_client(authenticated_client.clone())
+        .with_reporter(
+            IndicatifReporter::builder()
+                .with_multi_progress(global_multi_progress())
+                .clear_when_done(true)
+                .finish(),
+        )
+        .with_package_cache(PackageCache::new(
+            cache_dir.join(consts::CONDA_PACKAGE_CACHE_DIR),
+        ))
+        .install(prefix.root(), packages.conda.clone())
+        .await
+        .into_diagnostic()
+        .context("failed to create environment")?;
 
-        tracing::debug!(
-            "no specs provided, guessed {} from command {command}",
-            guessed_spec
-        );
+    // Install the pypi packages with the python interpreter of the environment.
+    if !args.pypi.is_empty() {
+        let python_record = packages
+            .conda
+            .iter()
+            .find(|record| record.package_record.name.as_normalized() == "python")
+            .ok_or_else(|| miette::miette!("no python interpreter was installed"))?;
+        let python_info =
+            PythonInfo::from_python_record(&python_record.package_record, args.platform)
+                .into_diagnostic()?;
+        let pixi_records = packages
+            .conda
+            .iter()
+            .cloned()
+            .map(PixiRecord::Binary)
+            .collect_vec();
+        let uv_context = UvResolutionContext::from_config(config, client.clone())?;
+        let env_variables = prefix.run_activation().await?;
+
+        if !is_locked {
+            packages.pypi = resolve_pypi(
+                uv_context.clone(),
+                &PypiOptions::default(),
+                exec_pypi_dependencies(&args.pypi)?,
+                IndexMap::new(),
+                IndexMap::new(),
+                SystemRequirements::default(),
+                &pixi_records,
+                &[],
+                args.platform,
+                &ProgressBar::hidden(),
+                &prefix.root().join(&python_info.path),
+                &env_variables,
+                prefix.root(),
+            )
+            .await
+            .context("failed to resolve the pypi requirements")?;
+        }
+
+        await_in_progress("installing pypi packages", |_| {
+            update_python_distributions(
+                prefix.root(),
+                &prefix,
+                &pixi_records,
+                &packages.pypi,
+                &python_info.path,
+                &SystemRequirements::default(),
+                &uv_context,
+                None,
+                &env_variables,
+                args.platform,
+                None,
+            )
+        })
+        .await?;
+    }
 
-        vec![guessed_spec]
-    } else {
-        args.specs.clone()
+    if let (Some(lock_path), true) = (&args.lock, needs_lock) {
+        write_exec_lock(lock_path, &channel_urls, args.platform, &packages)?;
+    }
+
+    let info = ExecEnvironmentInfo {
+        command: command.clone(),
+        specs: specs
+            .iter()
+            .map(ToString::to_string)
+            .chain(
+                args.pypi
+                    .iter()
+                    .map(|requirement| format!("{requirement} (pypi)")),
+            )
+            .collect(),
+        channels: channel_urls,
+        platform: args.platform,
+        snapshot: args.snapshot,
+        lock_file: args
+            .lock
+            .as_ref()
+            .map(|path| dunce::canonicalize(path).unwrap_or_else(|_| path.clone())),
     };
+    let info = serde_json::to_string_pretty(&info).into_diagnostic()?;
+    fs_err::write(prefix.root().join(EXEC_INFO_FILE), info).into_diagnostic()?;
 
-    let channels = args.channels.resolve_from_config(config)?;
+    let _ = write_guard.finish();
+    Ok(prefix)
+}
+
+/// Converts the pypi requirements of `pixi exec` into the dependencies that
+/// are passed to the resolver.
+fn exec_pypi_dependencies(
+    requirements: &[Requirement],
+) -> miette::Result<IndexMap<uv_normalize::PackageName, IndexSet<PyPiRequirement>>> {
+    let mut dependencies = IndexMap::<_, IndexSet<_>>::new();
+    for requirement in requirements {
+        let name = to_uv_normalize(&requirement.name).into_diagnostic()?;
+        let requirement = PyPiRequirement::try_from(requirement.clone()).into_diagnostic()?;
+        dependencies.entry(name).or_default().insert(requirement);
+    }
+    Ok(dependencies)
+}
+
+/// Solves the specs of the `pixi exec` environment.
+async fn solve_exec_environment(
+    args: &Args,
+    config: &Config,
+    client: &ClientWithMiddleware,
+    prefix: &Prefix,
+    channels: IndexSet<Channel>,
+    specs: Vec<MatchSpec>,
+) -> miette::Result<Vec<RepoDataRecord>> {
+    // Construct a gateway to get repodata.
+    let gateway = config.gateway(client.clone());
 
     // Get the repodata for the specs
     let repodata = await_in_progress("fetching repodata for environment", |_| async {
@@ -174,36 +463,221 @@ pub async fn create_exec_prefix(
             .unwrap_or(prefix.root())
             .display()
     );
-    let solved_records = wrap_in_progress("solving environment", move || {
+    let exclude_newer = args.snapshot;
+    wrap_in_progress("solving environment", move || {
         Solver.solve(SolverTask {
             specs,
             virtual_packages,
+            exclude_newer,
             ..SolverTask::from_iter(&repodata)
         })
     })
     .into_diagnostic()
-    .context("failed to solve environment")?;
+    .context("failed to solve environment")
+}
 
-    // Install the environment
-    Installer::new()
-        .with_target_platform(args.platform)
-        .with_download_client(client.clone())
-        .with_reporter(
-            IndicatifReporter::builder()
-                .with_multi_progress(global_multi_progress())
-                .clear_when_done(true)
-                .finish(),
-        )
-        .with_package_cache(PackageCache::new(
-            cache_dir.join(pixi_consts::consts::CONDA_PACKAGE_CACHE_DIR),
-        ))
-        .install(prefix.root(), solved_records)
-        .await
+/// Returns true if one of the locked pypi packages satisfies the requirement.
+fn satisfies_pypi_requirement(packages: &[PypiRecord], requirement: &Requirement) -> bool {
+    packages.iter().any(|(data, _)| {
+        data.name == requirement.name
+            && match &requirement.version_or_url {
+                None => true,
+                Some(VersionOrUrl::VersionSpecifier(specifiers)) => {
+                    specifiers.contains(&data.version)
+                }
+                Some(VersionOrUrl::Url(_)) => false,
+            }
+    })
+}
+
+/// Reads the packages for the given platform from a `pixi exec` lock file.
+/// Returns `None` if the platform is not locked or if the locked packages
+/// don't satisfy the requirements.
+fn read_exec_lock(
+    path: &Path,
+    platform: Platform,
+    specs: &[MatchSpec],
+    pypi_requirements: &[Requirement],
+) -> miette::Result<Option<ExecPackages>> {
+    let lock_file = LockFile::from_path(path)
         .into_diagnostic()
-        .context("failed to create environment")?;
+        .with_context(|| format!("failed to read lock file '{}'", path.display()))?;
+    let Some(locked) = lock_file
+        .environment(consts::DEFAULT_ENVIRONMENT_NAME)
+        .and_then(|env| env.packages(platform))
+    else {
+        return Ok(None);
+    };
 
-    let _ = write_guard.finish();
-    Ok(prefix)
+    let mut packages = ExecPackages::default();
+    for package in locked {
+        match package {
+            LockedPackageRef::Conda(CondaPackageData::Binary(data)) => packages
+                .conda
+                .push(RepoDataRecord::try_from(data.clone()).into_diagnostic()?),
+            LockedPackageRef::Conda(CondaPackageData::Source(_)) => miette::bail!(
+                "'{}' can only contain binary conda packages",
+                path.display()
+            ),
+            LockedPackageRef::Pypi(data, env_data) => {
+                packages.pypi.push((data.clone(), env_data.clone()))
+            }
+        }
+    }
+
+    let satisfied = specs.iter().all(|spec| {
+        packages
+            .conda
+            .iter()
+            .any(|record| spec.matches(&record.package_record))
+    }) && pypi_requirements
+        .iter()
+        .all(|requirement| satisfies_pypi_requirement(&packages.pypi, requirement));
+    Ok(satisfied.then_some(packages))
+}
+
+/// Writes the packages for the given platform to a `pixi exec` lock file.
+/// Packages that are locked for other platforms are kept.
+fn write_exec_lock(
+    path: &Path,
+    channels: &[String],
+    platform: Platform,
+    packages: &ExecPackages,
+) -> miette::Result<()> {
+    let environment_name = consts::DEFAULT_ENVIRONMENT_NAME;
+    let mut builder = LockFileBuilder::new();
+    builder.set_channels(environment_name, channels.to_vec());
+
+    let mut has_pypi_packages = !packages.pypi.is_empty();
+    if path.is_file() {
+        let existing = LockFile::from_path(path)
+            .into_diagnostic()
+            .with_context(|| format!("failed to read lock file '{}'", path.display()))?;
+        if let Some(env) = existing.environment(environment_name) {
+            for (locked_platform, locked) in env.packages_by_platform() {
+                if locked_platform == platform {
+                    continue;
+                }
+                for package in locked {
+                    has_pypi_packages |= matches!(package, LockedPackageRef::Pypi(..));
+                    builder.add_package(environment_name, locked_platform, package.into());
+                }
+            }
+        }
+    }
+
+    for record in &packages.conda {
+        builder.add_conda_package(environment_name, platform, record.clone().into());
+    }
+    for (data, env_data) in &packages.pypi {
+        builder.add_pypi_package(environment_name, platform, data.clone(), env_data.clone());
+    }
+    if has_pypi_packages {
+        builder.set_pypi_indexes(environment_name, (&PypiOptions::default()).into());
+    }
+
+    builder
+        .finish()
+        .to_path(path)
+        .into_diagnostic()
+        .with_context(|| format!("failed to write lock file '{}'", path.display()))?;
+    tracing::info!("locked the environment in {}", path.display());
+    Ok(())
+}
+
+/// Returns the cached environments, optionally only those of the given
+/// command.
+fn cached_environments(cache_dir: &Path, command: Option<&str>) -> miette::Result<Vec<PathBuf>> {
+    let envs_dir = cache_dir.join(consts::CACHED_ENVS_DIR);
+    if !envs_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let environments = fs_err::read_dir(&envs_dir)
+        .into_diagnostic()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter(|path| match command {
+            Some(command) => path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.rsplit_once('-'))
+                .is_some_and(|(name, _)| name == command),
+            None => true,
+        })
+        .sorted()
+        .collect();
+    Ok(environments)
+}
+
+/// Prints the cached environments of `pixi exec`.
+fn list_cached_environments(cache_dir: &Path, command: Option<&str>) -> miette::Result<()> {
+    let environments = cached_environments(cache_dir, command)?;
+    if environments.is_empty() {
+        eprintln!("No cached environments found");
+        return Ok(());
+    }
+
+    let mut writer = TabWriter::new(stdout());
+    writeln!(
+        writer,
+        "{}",
+        console::style("Environment\tSpecs\tSize").bold()
+    )
+    .into_diagnostic()?;
+    for path in environments {
+        let info = fs_err::read_to_string(path.join(EXEC_INFO_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str::<ExecEnvironmentInfo>(&contents).ok());
+        let specs = info.map_or_else(|| String::from("-"), |info| info.specs.join(" "));
+        let size = fs_extra::dir::get_size(&path).unwrap_or_default();
+        writeln!(
+            writer,
+            "{}\t{}\t{}",
+            consts::ENVIRONMENT_STYLE.apply_to(
+                path.file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default()
+            ),
+            specs,
+            HumanBytes(size)
+        )
+        .into_diagnostic()?;
+    }
+    writer.flush().into_diagnostic()?;
+    Ok(())
+}
+
+/// Removes the cached environments of `pixi exec`.
+async fn clean_cached_environments(cache_dir: &Path, command: Option<&str>) -> miette::Result<()> {
+    let environments = cached_environments(cache_dir, command)?;
+    if environments.is_empty() {
+        eprintln!("{}", console::style("Nothing to remove.").green());
+        return Ok(());
+    }
+
+    let count = environments.len();
+    for path in environments {
+        super::clean::remove_folder_with_progress(path, false).await?;
+    }
+    eprintln!(
+        "{}Removed {count} cached environment{}",
+        console::style(console::Emoji("✔ ", "")).green(),
+        if count == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+/// Parses the date of a channel snapshot. A plain date is interpreted as
+/// midnight UTC.
+fn parse_snapshot(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_time(chrono::NaiveTime::MIN).and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|err| format!("expected a date (YYYY-MM-DD) or an RFC 3339 timestamp: {err}"))
 }
 
 /// This function is used to guess the package name from the command.
@@ -228,3 +702,46 @@ async fn run_activation(
 ) -> miette::Result<std::collections::HashMap<String, String>> {
     wrap_in_progress("running activation", move || prefix.run_activation()).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_snapshot() {
+        assert_eq!(
+            parse_snapshot("2024-06-01").unwrap().to_rfc3339(),
+            "2024-06-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_snapshot("2024-06-01T12:30:00+02:00")
+                .unwrap()
+                .to_rfc3339(),
+            "2024-06-01T10:30:00+00:00"
+        );
+        assert!(parse_snapshot("last week").is_err());
+    }
+
+    #[test]
+    fn test_cached_environments() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let envs_dir = cache_dir.path().join(consts::CACHED_ENVS_DIR);
+        for name in ["python-1a2b", "ruff-3c4d", "pre-commit-5e6f"] {
+            fs_err::create_dir_all(envs_dir.join(name)).unwrap();
+        }
+
+        let names = |command| {
+            cached_environments(cache_dir.path(), command)
+                .unwrap()
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+                .collect_vec()
+        };
+        assert_eq!(
+            names(None),
+            vec!["pre-commit-5e6f", "python-1a2b", "ruff-3c4d"]
+        );
+        assert_eq!(names(Some("pre-commit")), vec!["pre-commit-5e6f"]);
+        assert!(names(Some("pre")).is_empty());
+    }
+}
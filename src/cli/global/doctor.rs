@@ -0,0 +1,49 @@
+use crate::global::{trampoline::diagnose_bin_dir, BinDir};
+use clap::Parser;
+use itertools::Itertools;
+
+/// Validate the exposed executables and their trampoline configurations.
+///
+/// Reports executables whose environment or original executable is missing,
+/// configurations that can't be read and configurations without a trampoline.
+/// Most problems are fixed by running `pixi global sync`.
+#[derive(Parser, Debug)]
+pub struct Args {}
+
+pub async fn execute(_args: Args) -> miette::Result<()> {
+    let bin_dir = BinDir::from_env().await?;
+    let issues = diagnose_bin_dir(bin_dir.path()).await?;
+
+    for (path, issues) in &issues.iter().chunk_by(|(path, _)| path) {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        eprintln!("{}", console::style(name).bold());
+        for (_, issue) in issues {
+            let marker = if issue.is_error() {
+                console::style(console::Emoji("✘ ", "x ")).red()
+            } else {
+                console::style(console::Emoji("⚠ ", "! ")).yellow()
+            };
+            eprintln!("  {marker}{issue}");
+        }
+    }
+
+    let errors = issues.iter().filter(|(_, issue)| issue.is_error()).count();
+    if errors > 0 {
+        miette::bail!(
+            help = "run `pixi global sync` to recreate the trampolines",
+            "found {errors} problem{} in {}",
+            if errors == 1 { "" } else { "s" },
+            bin_dir.path().display()
+        );
+    }
+
+    eprintln!(
+        "{}All exposed executables in {} are valid",
+        console::style(console::Emoji("✔ ", "")).green(),
+        bin_dir.path().display()
+    );
+    Ok(())
+}
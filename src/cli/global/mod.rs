@@ -3,6 +3,7 @@ use clap::Parser;
 use crate::global::{self, EnvironmentName};
 
 mod add;
+mod doctor;
 mod edit;
 mod expose;
 mod install;
@@ -28,6 +29,7 @@ pub enum Command {
     List(list::Args),
     #[clap(visible_alias = "s")]
     Sync(sync::Args),
+    Doctor(doctor::Args),
     #[clap(visible_alias = "e")]
     #[command(subcommand)]
     Expose(expose::SubCommand),
@@ -60,6 +62,7 @@ pub async fn execute(cmd: Args) -> miette::Result<()> {
         Command::Remove(args) => remove::execute(args).await?,
         Command::List(args) => list::execute(args).await?,
         Command::Sync(args) => sync::execute(args).await?,
+        Command::Doctor(args) => doctor::execute(args).await?,
         Command::Expose(subcommand) => expose::execute(subcommand).await?,
         Command::Update(args) => update::execute(args).await?,
         Command::Upgrade(args) => upgrade::execute(args).await?,
@@ -346,9 +346,26 @@ fn last_updated(path: impl Into<PathBuf>) -> miette::Result<String> {
 
 pub async fn execute(args: Args) -> miette::Result<()> {
     let project = Project::load_or_else_discover(args.project_config.manifest_path.as_deref()).ok();
+    let info = collect_info(project.as_ref(), args.extended).await?;
 
-    let (pixi_folder_size, cache_size) = if args.extended {
-        let env_dir = project.as_ref().map(|p| p.pixi_dir());
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&info).into_diagnostic()?);
+    } else {
+        println!("{}", info);
+    }
+
+    Project::warn_on_discovered_from_env(args.project_config.manifest_path.as_deref());
+    Ok(())
+}
+
+/// Collects the information about the system, the project and its
+/// environments. Directory sizes are only computed if `extended` is set.
+pub(crate) async fn collect_info(
+    project: Option<&Project>,
+    extended: bool,
+) -> miette::Result<Info> {
+    let (pixi_folder_size, cache_size) = if extended {
+        let env_dir = project.map(|p| p.pixi_dir());
         let cache_dir = pixi_config::get_cache_dir()?;
         await_in_progress("fetching directory sizes", |_| {
             spawn_blocking(move || {
@@ -363,7 +380,7 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         (None, None)
     };
 
-    let project_info = project.clone().map(|p| ProjectInfo {
+    let project_info = project.map(|p| ProjectInfo {
         name: p.name().to_string(),
         manifest_path: p.manifest_path(),
         last_updated: last_updated(p.lock_file_path()).ok(),
@@ -372,7 +389,6 @@ pub async fn execute(args: Args) -> miette::Result<()> {
     });
 
     let environments_info: Vec<EnvironmentInfo> = project
-        .as_ref()
         .map(|p| {
             p.environments()
                 .iter()
@@ -436,7 +452,7 @@ pub async fn execute(args: Args) -> miette::Result<()> {
                 .clone()
         });
 
-    let info = Info {
+    Ok(Info {
         platform: Platform::current().to_string(),
         virtual_packages,
         version: consts::PIXI_VERSION.to_string(),
@@ -447,17 +463,5 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         environments_info,
         global_info,
         config_locations: config.loaded_from.clone(),
-    };
-
-    if args.json {
-        println!("{}", serde_json::to_string_pretty(&info).into_diagnostic()?);
-
-        Project::warn_on_discovered_from_env(args.project_config.manifest_path.as_deref());
-        Ok(())
-    } else {
-        println!("{}", info);
-
-        Project::warn_on_discovered_from_env(args.project_config.manifest_path.as_deref());
-        Ok(())
-    }
+    })
 }
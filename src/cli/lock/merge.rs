@@ -0,0 +1,144 @@
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use fancy_display::FancyDisplay;
+use itertools::Itertools;
+use miette::{Context, IntoDiagnostic};
+use pixi_config::ConfigCli;
+use pixi_consts::consts;
+use rattler_lock::LockFile;
+
+use crate::{
+    cli::cli_config::ProjectConfig,
+    lock_file::{merge_lock_files, OutdatedEnvironments, UpdateContext},
+    Project,
+};
+
+/// Merge two diverged versions of a lock file.
+///
+/// This command is designed to be used as a git merge driver. Register it with:
+///
+///   git config merge.pixi.driver "pixi lock merge %O %A %B"
+///
+/// and add `pixi.lock merge=pixi` to your `.gitattributes`.
+///
+/// Environments and platforms that were only modified on one side are merged
+/// directly. Targets that were modified on both sides, and environments that
+/// were removed on one side and modified on the other side, are re-solved
+/// using the manifest of the project.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The lock file of the common ancestor (`%O`).
+    pub base: PathBuf,
+
+    /// Our version of the lock file (`%A`). The merged result is written to
+    /// this file.
+    pub ours: PathBuf,
+
+    /// Their version of the lock file (`%B`).
+    pub theirs: PathBuf,
+
+    #[clap(flatten)]
+    pub project_config: ProjectConfig,
+
+    #[clap(flatten)]
+    pub config: ConfigCli,
+
+    /// Don't re-solve conflicting environments, fail instead.
+    #[arg(long)]
+    pub no_resolve: bool,
+
+    /// Don't install the (solve) environments needed for pypi-dependencies
+    /// solving.
+    #[arg(long)]
+    pub no_install: bool,
+}
+
+fn read_lock_file(path: &Path) -> miette::Result<LockFile> {
+    LockFile::from_path(path)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("failed to load lock file from `{}`", path.display()))
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let base = read_lock_file(&args.base)?;
+    let ours = read_lock_file(&args.ours)?;
+    let theirs = read_lock_file(&args.theirs)?;
+
+    let merged = merge_lock_files(&base, &ours, &theirs);
+    if merged.is_clean() {
+        merged
+            .lock_file
+            .to_path(&args.ours)
+            .into_diagnostic()
+            .context("failed to write merged lock file")?;
+        eprintln!(
+            "{}Merged lock files without conflicts",
+            console::style(console::Emoji("✔ ", "")).green()
+        );
+        return Ok(());
+    }
+
+    let conflicts = merged
+        .conflicts
+        .iter()
+        .map(|(env, platform)| {
+            format!(
+                "{} ({})",
+                consts::ENVIRONMENT_STYLE.apply_to(env),
+                consts::PLATFORM_STYLE.apply_to(platform)
+            )
+        })
+        .format(", ");
+    if args.no_resolve {
+        miette::bail!("the lock files have conflicting changes in {conflicts}");
+    }
+    tracing::info!("re-solving conflicting targets: {conflicts}");
+
+    // Re-solve only the conflicting targets, everything else is taken from the
+    // merged lock-file as is.
+    let project = Project::load_or_else_discover(args.project_config.manifest_path.as_deref())?
+        .with_cli_config(args.config);
+    let mut outdated = OutdatedEnvironments::from_project_and_lock_file(
+        &project,
+        &merged.lock_file,
+        Default::default(),
+    )
+    .await;
+    outdated.retain(|env, platform| {
+        merged
+            .conflicts
+            .contains(&(env.name().as_str().to_string(), platform))
+    });
+
+    let updated = UpdateContext::builder(&project)
+        .with_lock_file(merged.lock_file)
+        .with_outdated_environments(outdated)
+        .with_no_install(args.no_install)
+        .finish()
+        .await?
+        .update()
+        .await?;
+
+    updated
+        .lock_file
+        .to_path(&args.ours)
+        .into_diagnostic()
+        .context("failed to write merged lock file")?;
+
+    let resolved_environments = merged
+        .conflicts
+        .iter()
+        .map(|(env, _)| env.as_str())
+        .unique()
+        .filter_map(|env| project.environment(env))
+        .map(|env| env.name().fancy_display().to_string())
+        .format(", ");
+    eprintln!(
+        "{}Merged lock files, re-solved {}",
+        console::style(console::Emoji("✔ ", "")).green(),
+        resolved_environments
+    );
+
+    Ok(())
+}
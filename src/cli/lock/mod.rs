@@ -0,0 +1,175 @@
+use clap::Parser;
+use fancy_display::FancyDisplay;
+use indexmap::IndexSet;
+use itertools::Itertools;
+use miette::{Context, IntoDiagnostic};
+use pixi_config::ConfigCli;
+use pixi_consts::consts;
+use pixi_manifest::{EnvironmentName, FeaturesExt};
+use rattler_conda_types::Platform;
+
+use crate::{
+    cli::cli_config::ProjectConfig,
+    load_lock_file,
+    lock_file::{
+        replace_targets, stale_targets, LockedTarget, OutdatedEnvironments, UpdateContext,
+    },
+    Project,
+};
+
+pub mod merge;
+
+#[derive(Debug, Parser)]
+pub enum Command {
+    Merge(merge::Args),
+}
+
+/// Commands to manage the lock file of a project.
+///
+/// Without a subcommand the lock file is updated to match the manifest. Use
+/// `--environment` and `--platform` to only re-solve a part of the lock file,
+/// all other environments and platforms are left untouched.
+#[derive(Debug, Parser)]
+#[clap(args_conflicts_with_subcommands = true)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[clap(flatten)]
+    pub project_config: ProjectConfig,
+
+    #[clap(flatten)]
+    pub config: ConfigCli,
+
+    /// The environments to lock. Defaults to all environments.
+    #[arg(short, long)]
+    pub environment: Option<Vec<EnvironmentName>>,
+
+    /// The platforms to lock. Defaults to all platforms of the selected
+    /// environments.
+    #[arg(short, long)]
+    pub platform: Option<Vec<Platform>>,
+
+    /// Don't install the (solve) environments needed for pypi-dependencies
+    /// solving.
+    #[arg(long)]
+    pub no_install: bool,
+}
+
+pub async fn execute(mut args: Args) -> miette::Result<()> {
+    match args.command.take() {
+        Some(Command::Merge(args)) => merge::execute(args).await?,
+        None => lock(args).await?,
+    };
+    Ok(())
+}
+
+/// Re-solves the outdated targets of the lock file that match the selected
+/// environments and platforms.
+async fn lock(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.project_config.manifest_path.as_deref())?
+        .with_cli_config(args.config);
+
+    let environments = match &args.environment {
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                project.environment(name).ok_or_else(|| {
+                    miette::miette!("unknown environment '{}'", name.fancy_display())
+                })
+            })
+            .collect::<miette::Result<Vec<_>>>()?,
+        None => project.environments(),
+    };
+
+    // Determine the targets that are allowed to change.
+    let mut targets: IndexSet<LockedTarget> = IndexSet::new();
+    for environment in &environments {
+        for platform in environment
+            .platforms()
+            .into_iter()
+            .sorted_by_key(|p| p.as_str())
+        {
+            if args
+                .platform
+                .as_ref()
+                .is_some_and(|platforms| !platforms.contains(&platform))
+            {
+                continue;
+            }
+            targets.insert((environment.name().as_str().to_string(), platform));
+        }
+    }
+    if targets.is_empty() {
+        miette::bail!("none of the selected environments support the selected platforms");
+    }
+
+    let lock_file = load_lock_file(&project).await?;
+    let mut outdated =
+        OutdatedEnvironments::from_project_and_lock_file(&project, &lock_file, Default::default())
+            .await;
+    outdated.retain(|env, platform| targets.contains(&(env.name().as_str().to_string(), platform)));
+    if outdated.is_empty() {
+        eprintln!(
+            "{}Lock file is up-to-date",
+            console::style(console::Emoji("✔ ", "")).green()
+        );
+        return Ok(());
+    }
+
+    let solved = outdated
+        .conda
+        .iter()
+        .chain(outdated.pypi.iter())
+        .flat_map(|(env, platforms)| platforms.iter().map(move |platform| (env, *platform)))
+        .map(|(env, platform)| (env.name().as_str().to_string(), platform))
+        .collect::<IndexSet<_>>();
+
+    let updated = UpdateContext::builder(&project)
+        .with_lock_file(lock_file.clone())
+        .with_outdated_environments(outdated)
+        .with_no_install(args.no_install)
+        .finish()
+        .await?
+        .update()
+        .await?;
+
+    // Only take the re-solved targets from the updated lock-file so that all other
+    // targets are written exactly as they were, even if they are out of date.
+    // That includes the other platforms of an environment whose channels changed.
+    let stale = stale_targets(&lock_file, &updated.lock_file, &solved);
+    if !stale.is_empty() {
+        tracing::warn!(
+            "the channels of the environment changed, the lock file for {} is kept as it was and is re-solved by the next update",
+            stale
+                .iter()
+                .map(|(env, platform)| format!(
+                    "{} ({})",
+                    consts::ENVIRONMENT_STYLE.apply_to(env),
+                    consts::PLATFORM_STYLE.apply_to(platform)
+                ))
+                .format(", ")
+        );
+    }
+    let lock_file = replace_targets(&lock_file, &updated.lock_file, &solved);
+    lock_file
+        .to_path(&project.lock_file_path())
+        .into_diagnostic()
+        .context("failed to write lock-file to disk")?;
+
+    eprintln!(
+        "{}Updated the lock file for {}",
+        console::style(console::Emoji("✔ ", "")).green(),
+        solved
+            .iter()
+            .sorted_by_key(|(env, platform)| (env.clone(), platform.as_str()))
+            .map(|(env, platform)| format!(
+                "{} ({})",
+                consts::ENVIRONMENT_STYLE.apply_to(env),
+                consts::PLATFORM_STYLE.apply_to(platform)
+            ))
+            .format(", ")
+    );
+
+    Ok(())
+}
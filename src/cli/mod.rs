@@ -3,20 +3,23 @@ use clap_verbosity_flag::Verbosity;
 use indicatif::ProgressDrawTarget;
 use miette::IntoDiagnostic;
 use pixi_consts::consts;
+use pixi_manifest::UnresolvedRemoteInclude;
 use pixi_progress::global_multi_progress;
 use pixi_utils::indicatif::IndicatifWriter;
-use std::{env, io::IsTerminal};
+use std::{collections::HashSet, env, io::IsTerminal};
 use tracing_subscriber::{
     filter::LevelFilter, prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt,
     EnvFilter,
 };
 
 pub mod add;
+pub mod audit;
 mod build;
 pub mod clean;
 pub mod cli_config;
 pub mod completion;
 pub mod config;
+pub mod doctor;
 mod exec;
 pub mod global;
 pub mod has_specs;
@@ -24,6 +27,8 @@ pub mod info;
 pub mod init;
 pub mod install;
 pub mod list;
+pub mod lock;
+pub mod pack;
 pub mod project;
 pub mod remove;
 pub mod run;
@@ -120,11 +125,18 @@ pub enum Command {
     Project(project::Args),
     Task(task::Args),
 
+    // Lock file commands
+    Lock(lock::Args),
+
     // Environment inspection
     #[clap(visible_alias = "ls")]
     List(list::Args),
     #[clap(visible_alias = "t")]
     Tree(tree::Args),
+    Audit(audit::Args),
+
+    // Deployment
+    Pack(pack::Args),
 
     // Global level commands
     #[clap(visible_alias = "g")]
@@ -132,6 +144,7 @@ pub enum Command {
     Auth(rattler::cli::auth::Args),
     Config(config::Args),
     Info(info::Args),
+    Doctor(doctor::Args),
     Upload(upload::Args),
     Search(search::Args),
     #[cfg_attr(not(feature = "self_update"), clap(hide = true))]
@@ -263,8 +276,28 @@ pub async fn execute() -> miette::Result<()> {
         .with(fmt_layer)
         .init();
 
-    // Execute the command
-    execute_command(args.command).await
+    // Execute the command. Remote files included by the manifest are fetched
+    // when they aren't cached yet, after which the command runs again.
+    let mut fetched = HashSet::new();
+    let mut result = execute_command(args.command).await;
+    while let Some(unresolved) = result
+        .as_ref()
+        .err()
+        .and_then(|err| err.downcast_ref::<UnresolvedRemoteInclude>())
+        .filter(|unresolved| !fetched.contains(&unresolved.include))
+        .cloned()
+    {
+        crate::project::remote_includes::fetch(&unresolved).await?;
+        fetched.insert(unresolved.include);
+        result = execute_command(Args::parse().command).await;
+    }
+
+    // A workspace that requires another version of pixi can be handled by
+    // updating pixi.
+    match result {
+        Err(err) => Err(self_update::offer_update(err).await),
+        Ok(()) => Ok(()),
+    }
 }
 
 /// Execute the actual command
@@ -283,6 +316,7 @@ pub async fn execute_command(command: Command) -> miette::Result<()> {
         Command::ShellHook(cmd) => shell_hook::execute(cmd).await,
         Command::Task(cmd) => task::execute(cmd),
         Command::Info(cmd) => info::execute(cmd).await,
+        Command::Doctor(cmd) => doctor::execute(cmd).await,
         Command::Upload(cmd) => upload::execute(cmd).await,
         Command::Search(cmd) => search::execute(cmd).await,
         Command::Project(cmd) => project::execute(cmd).await,
@@ -292,7 +326,10 @@ pub async fn execute_command(command: Command) -> miette::Result<()> {
         #[cfg(not(feature = "self_update"))]
         Command::SelfUpdate(cmd) => self_update::execute_stub(cmd).await,
         Command::List(cmd) => list::execute(cmd).await,
+        Command::Lock(cmd) => lock::execute(cmd).await,
         Command::Tree(cmd) => tree::execute(cmd).await,
+        Command::Audit(cmd) => audit::execute(cmd).await,
+        Command::Pack(cmd) => pack::execute(cmd).await,
         Command::Update(cmd) => update::execute(cmd).await,
         Command::Upgrade(cmd) => upgrade::execute(cmd).await,
         Command::Exec(args) => exec::execute(args).await,
@@ -0,0 +1,506 @@
+use std::{
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use fancy_display::FancyDisplay;
+use futures::{StreamExt, TryStreamExt};
+use miette::{Context, IntoDiagnostic};
+use pixi_config::ConfigCli;
+use pixi_consts::consts;
+use pixi_progress::await_in_progress;
+use pixi_utils::reqwest::default_retry_policy;
+use rattler::package_cache::PackageCache;
+use rattler_conda_types::{PackageRecord, Platform, RepoDataRecord};
+use rattler_digest::{compute_file_digest, Sha256, Sha256Hash};
+use rattler_lock::{CondaPackageData, LockFileBuilder, LockedPackageRef, UrlOrPath};
+use reqwest_middleware::ClientWithMiddleware;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use url::Url;
+
+use crate::{
+    cli::{cli_config::ProjectConfig, LockFileUsageArgs},
+    install_pypi::utils::strip_direct_scheme,
+    lock_file::UpdateLockFileOptions,
+    Project,
+};
+
+/// The version of the layout of the archives. It is increased when the layout
+/// changes in a way that older versions of pixi can't unpack.
+pub(super) const ARCHIVE_VERSION: u32 = 2;
+
+/// The file in the archive that describes its contents.
+pub(super) const METADATA_FILE: &str = "pixi-pack.json";
+
+/// The directory in the archive that contains the extracted conda packages, as
+/// they are stored in the package cache.
+pub(super) const CONDA_DIR: &str = "conda";
+
+/// The directory in the archive that contains the PyPI wheels.
+pub(super) const PYPI_DIR: &str = "pypi";
+
+/// Describes the environment that is stored in an archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(super) struct ArchiveMetadata {
+    /// The version of the layout of the archive.
+    pub version: u32,
+
+    /// The name of the environment in the lock file of the archive.
+    pub environment: String,
+
+    /// The platform the packages of the archive are for.
+    pub platform: Platform,
+
+    /// The version of pixi that created the archive.
+    pub pixi_version: String,
+}
+
+/// Create an archive of an environment that can be installed without network
+/// access.
+///
+/// The archive contains the locked packages of the environment for a single
+/// platform: all conda packages and PyPI wheels are included. Install it
+/// with `pixi pack unpack`.
+#[derive(Parser, Debug)]
+pub struct Args {
+    #[clap(flatten)]
+    pub project_config: ProjectConfig,
+
+    #[clap(flatten)]
+    pub lock_file_usage: LockFileUsageArgs,
+
+    #[clap(flatten)]
+    pub config: ConfigCli,
+
+    /// The environment to pack
+    #[arg(long, short)]
+    pub environment: Option<String>,
+
+    /// The platform to pack the environment for, defaults to the current
+    /// platform
+    #[arg(long, short)]
+    pub platform: Option<Platform>,
+
+    /// The path of the archive to write, defaults to
+    /// `<environment>-<platform>.tar` in the current directory
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+/// A package that is added to the archive.
+#[derive(Debug)]
+enum ArchivePackage {
+    /// A conda package, which is taken from the package cache.
+    Conda(RepoDataRecord),
+
+    /// A PyPI wheel, which is downloaded or copied.
+    Wheel(PackageFile),
+}
+
+/// A package file that is added to the archive.
+#[derive(Debug)]
+struct PackageFile {
+    /// Where to get the file from.
+    url: Url,
+
+    /// The path of the file in the archive.
+    archive_path: String,
+
+    /// The expected hash of the file, if it is known.
+    sha256: Option<Sha256Hash>,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.project_config.manifest_path.as_deref())?
+        .with_cli_config(args.config);
+    let environment = project.environment_from_name_or_env_var(args.environment)?;
+    let platform = args.platform.unwrap_or_else(|| environment.best_platform());
+    if !environment.platforms().contains(&platform) {
+        miette::bail!(
+            "the environment {} doesn't support the platform {}",
+            environment.name().fancy_display(),
+            platform
+        );
+    }
+
+    let lock_file = project
+        .update_lock_file(UpdateLockFileOptions {
+            lock_file_usage: args.lock_file_usage.into(),
+            no_install: true,
+            max_concurrent_solves: project.config().max_concurrent_solves(),
+        })
+        .await?
+        .lock_file;
+    let environment_name = environment.name().as_str();
+    let locked_environment = lock_file.environment(environment_name).ok_or_else(|| {
+        miette::miette!(
+            "the environment {} is missing from the lock file",
+            environment.name().fancy_display()
+        )
+    })?;
+
+    // Create a lock file with only the environment and platform that is
+    // packed, and collect the files of its packages.
+    let mut builder = LockFileBuilder::new();
+    builder.set_channels(environment_name, locked_environment.channels().to_vec());
+    if let Some(indexes) = locked_environment.pypi_indexes() {
+        builder.set_pypi_indexes(environment_name, indexes.clone());
+    }
+    let mut conda_records = Vec::new();
+    let mut wheels = Vec::new();
+    for package in locked_environment.packages(platform).into_iter().flatten() {
+        match archive_package(&package, project.root())? {
+            ArchivePackage::Conda(record) => conda_records.push(record),
+            ArchivePackage::Wheel(file) => wheels.push(file),
+        }
+        builder.add_package(environment_name, platform, package.into());
+    }
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("{}-{}.tar", environment_name, platform)));
+
+    // The conda packages are taken from the package cache, so the packages that
+    // are already installed are not downloaded again. The cache locks are held
+    // until the archive is written.
+    let package_cache =
+        PackageCache::new(pixi_config::get_cache_dir()?.join(consts::CONDA_PACKAGE_CACHE_DIR));
+    let client = project.authenticated_client();
+    let package_count = conda_records.len() + wheels.len();
+    let cached_packages = await_in_progress(
+        format!("fetching {} conda packages", conda_records.len()),
+        |_| {
+            futures::stream::iter(conda_records)
+                .map(|record| {
+                    let package_cache = &package_cache;
+                    async move {
+                        let cache_lock = package_cache
+                            .get_or_fetch_from_url_with_retry(
+                                &record.package_record,
+                                record.url.clone(),
+                                client.clone(),
+                                default_retry_policy(),
+                                None,
+                            )
+                            .await
+                            .into_diagnostic()
+                            .wrap_err_with(|| format!("failed to fetch '{}'", record.url))?;
+                        Ok::<_, miette::Report>((
+                            package_dir_name(&record.package_record),
+                            cache_lock,
+                        ))
+                    }
+                })
+                .buffer_unordered(project.config().max_concurrent_downloads())
+                .try_collect::<Vec<_>>()
+        },
+    )
+    .await?;
+
+    // Download the wheels to a staging directory next to the environments of
+    // the project, which is large enough to hold them.
+    fs_err::create_dir_all(project.pixi_dir()).into_diagnostic()?;
+    let staging = tempfile::Builder::new()
+        .prefix("pack-")
+        .tempdir_in(project.pixi_dir())
+        .into_diagnostic()?;
+    fs_err::create_dir_all(staging.path().join(PYPI_DIR)).into_diagnostic()?;
+    await_in_progress(format!("downloading {} wheels", wheels.len()), |_| {
+        futures::stream::iter(wheels)
+            .map(|file| {
+                let destination = staging.path().join(&file.archive_path);
+                async move { download_package_file(client, &file, &destination).await }
+            })
+            .buffer_unordered(project.config().max_concurrent_downloads())
+            .try_collect::<Vec<_>>()
+    })
+    .await?;
+
+    builder
+        .finish()
+        .to_path(&staging.path().join(consts::PROJECT_LOCK_FILE))
+        .into_diagnostic()
+        .context("failed to write the lock file of the archive")?;
+    let metadata = ArchiveMetadata {
+        version: ARCHIVE_VERSION,
+        environment: environment_name.to_string(),
+        platform,
+        pixi_version: consts::PIXI_VERSION.to_string(),
+    };
+
+    let output_path = output.clone();
+    let conda_packages = cached_packages
+        .iter()
+        .map(|(dir_name, cache_lock)| (dir_name.clone(), cache_lock.path().to_path_buf()))
+        .collect::<Vec<_>>();
+    await_in_progress("writing the archive", |_| {
+        tokio::task::spawn_blocking(move || {
+            write_archive(&output_path, staging.path(), &conda_packages, &metadata)
+        })
+    })
+    .await
+    .into_diagnostic()?
+    .into_diagnostic()
+    .wrap_err_with(|| format!("failed to write the archive to '{}'", output.display()))?;
+
+    eprintln!(
+        "{}Packed {} packages of environment {} for {} in {}",
+        console::style(console::Emoji("✔ ", "")).green(),
+        package_count,
+        environment.name().fancy_display(),
+        platform,
+        console::style(output.display()).bold(),
+    );
+    eprintln!(
+        "Install it with `pixi pack unpack {} --prefix <DIR>`",
+        output.display()
+    );
+
+    Project::warn_on_discovered_from_env(args.project_config.manifest_path.as_deref());
+    Ok(())
+}
+
+/// Determines how a locked package is added to the archive. Only packages that
+/// can be installed without building them can be packed.
+fn archive_package(
+    package: &LockedPackageRef<'_>,
+    project_root: &Path,
+) -> miette::Result<ArchivePackage> {
+    match package {
+        LockedPackageRef::Conda(CondaPackageData::Binary(data)) => Ok(ArchivePackage::Conda(
+            RepoDataRecord::try_from((*data).clone()).into_diagnostic()?,
+        )),
+        LockedPackageRef::Conda(CondaPackageData::Source(data)) => miette::bail!(
+            help = "only binary packages can be installed without network access",
+            "'{}' is built from source and can't be packed",
+            data.package_record.name.as_source()
+        ),
+        LockedPackageRef::Pypi(data, _) => {
+            let not_a_wheel = || {
+                miette::miette!(
+                    help = "only wheels can be installed without network access",
+                    "'{}' is not a wheel and can't be packed",
+                    data.name
+                )
+            };
+            let url = match &data.location {
+                UrlOrPath::Url(url) => {
+                    let url = strip_direct_scheme(url).into_owned();
+                    if url.scheme().starts_with("git+") {
+                        return Err(not_a_wheel());
+                    }
+                    url
+                }
+                UrlOrPath::Path(path) => {
+                    let path = Path::new(path.as_str());
+                    Url::from_file_path(project_root.join(path))
+                        .map_err(|_| miette::miette!("invalid path '{}'", path.display()))?
+                }
+            };
+            let file_name = wheel_file_name(&url).ok_or_else(not_a_wheel)?;
+            Ok(ArchivePackage::Wheel(PackageFile {
+                archive_path: format!("{PYPI_DIR}/{file_name}"),
+                sha256: data.hash.as_ref().and_then(|hash| hash.sha256()).cloned(),
+                url,
+            }))
+        }
+    }
+}
+
+/// Returns the name of the directory of an extracted conda package in the
+/// archive.
+pub(super) fn package_dir_name(record: &PackageRecord) -> String {
+    format!(
+        "{}-{}-{}",
+        record.name.as_normalized(),
+        record.version,
+        record.build
+    )
+}
+
+/// Returns the decoded file name of the wheel the url points to, or `None` if
+/// it doesn't point to a wheel.
+fn wheel_file_name(url: &Url) -> Option<String> {
+    let file_name = url.path_segments()?.last()?;
+    let file_name = percent_encoding::percent_decode_str(file_name)
+        .decode_utf8_lossy()
+        .into_owned();
+    file_name.ends_with(".whl").then_some(file_name)
+}
+
+/// Downloads or copies a wheel and verifies its hash.
+async fn download_package_file(
+    client: &ClientWithMiddleware,
+    file: &PackageFile,
+    destination: &Path,
+) -> miette::Result<()> {
+    if file.url.scheme() == "file" {
+        let path = file
+            .url
+            .to_file_path()
+            .map_err(|_| miette::miette!("invalid file url '{}'", file.url))?;
+        tokio::fs::copy(&path, destination)
+            .await
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to copy '{}'", path.display()))?;
+    } else {
+        let mut response = client
+            .get(file.url.clone())
+            .send()
+            .await
+            .into_diagnostic()
+            .and_then(|response| response.error_for_status().into_diagnostic())
+            .wrap_err_with(|| format!("failed to download '{}'", file.url))?;
+        let mut writer = tokio::fs::File::create(destination)
+            .await
+            .into_diagnostic()?;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to download '{}'", file.url))?
+        {
+            writer.write_all(&chunk).await.into_diagnostic()?;
+        }
+        writer.flush().await.into_diagnostic()?;
+    }
+
+    if let Some(expected) = &file.sha256 {
+        let actual = compute_file_digest::<Sha256>(destination).into_diagnostic()?;
+        if &actual != expected {
+            miette::bail!(
+                "the sha256 of '{}' is {:x} but the lock file expects {:x}",
+                file.url,
+                actual,
+                expected
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Writes the metadata, the staged lock file and wheels, and the extracted
+/// `conda_packages` to a tar archive. The metadata is the first entry so it can
+/// be read without unpacking everything.
+fn write_archive(
+    output: &Path,
+    staging: &Path,
+    conda_packages: &[(String, PathBuf)],
+    metadata: &ArchiveMetadata,
+) -> io::Result<()> {
+    let mut builder = tar::Builder::new(BufWriter::new(fs_err::File::create(output)?));
+    builder.mode(tar::HeaderMode::Deterministic);
+
+    let metadata = serde_json::to_vec_pretty(metadata)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(metadata.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, METADATA_FILE, metadata.as_slice())?;
+
+    builder.append_path_with_name(
+        staging.join(consts::PROJECT_LOCK_FILE),
+        consts::PROJECT_LOCK_FILE,
+    )?;
+    for (dir_name, path) in conda_packages {
+        builder.append_dir_all(format!("{CONDA_DIR}/{dir_name}"), path)?;
+    }
+    builder.append_dir_all(PYPI_DIR, staging.join(PYPI_DIR))?;
+
+    builder.into_inner()?.flush()
+}
+
+/// Reads the metadata of an archive without unpacking it.
+pub(super) fn read_metadata(archive: &Path) -> miette::Result<ArchiveMetadata> {
+    let not_an_archive = || {
+        miette::miette!(
+            help = "create an archive with `pixi pack archive`",
+            "'{}' is not an archive of an environment",
+            archive.display()
+        )
+    };
+
+    let mut archive_reader = tar::Archive::new(fs_err::File::open(archive).into_diagnostic()?);
+    let mut entries = archive_reader.entries().into_diagnostic()?;
+    let mut entry = entries
+        .next()
+        .ok_or_else(not_an_archive)?
+        .into_diagnostic()?;
+    if entry.path().into_diagnostic()?.as_ref() != Path::new(METADATA_FILE) {
+        return Err(not_an_archive());
+    }
+    let mut contents = String::new();
+    io::Read::read_to_string(&mut entry, &mut contents).into_diagnostic()?;
+    let metadata: ArchiveMetadata =
+        serde_json::from_str(&contents).map_err(|_| not_an_archive())?;
+
+    if metadata.version > ARCHIVE_VERSION {
+        miette::bail!(
+            help = "update pixi with `pixi self-update`",
+            "the archive was created by pixi {} and can't be unpacked by this version of pixi",
+            metadata.pixi_version
+        );
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wheel_file_name() {
+        let url = Url::parse("https://example.com/torch-2.3.0%2Bcu121-cp312-cp312-win_amd64.whl")
+            .unwrap();
+        assert_eq!(
+            wheel_file_name(&url).as_deref(),
+            Some("torch-2.3.0+cu121-cp312-cp312-win_amd64.whl")
+        );
+
+        let url = Url::parse("https://example.com/flask-3.0.0.tar.gz").unwrap();
+        assert_eq!(wheel_file_name(&url), None);
+    }
+
+    #[test]
+    fn test_write_archive() {
+        let staging = tempfile::tempdir().unwrap();
+        fs_err::create_dir_all(staging.path().join(PYPI_DIR)).unwrap();
+        fs_err::write(staging.path().join(consts::PROJECT_LOCK_FILE), "version: 6").unwrap();
+        let package = tempfile::tempdir().unwrap();
+        fs_err::create_dir_all(package.path().join("info")).unwrap();
+        fs_err::write(package.path().join("info/index.json"), "{}").unwrap();
+        let conda_packages = [(String::from("foo-1.0-h0_0"), package.path().to_path_buf())];
+
+        let metadata = ArchiveMetadata {
+            version: ARCHIVE_VERSION,
+            environment: String::from("prod"),
+            platform: Platform::Linux64,
+            pixi_version: consts::PIXI_VERSION.to_string(),
+        };
+        let output = staging.path().join("archive.tar");
+        write_archive(&output, staging.path(), &conda_packages, &metadata).unwrap();
+
+        let mut archive = tar::Archive::new(fs_err::File::open(&output).unwrap());
+        let paths = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                entry
+                    .unwrap()
+                    .path()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(paths[0], METADATA_FILE);
+        assert!(paths.contains(&consts::PROJECT_LOCK_FILE.to_string()));
+        assert!(paths.contains(&format!("{CONDA_DIR}/foo-1.0-h0_0/info/index.json")));
+
+        let read = read_metadata(&output).unwrap();
+        assert_eq!(read, metadata);
+    }
+}
@@ -0,0 +1,223 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use clap::Parser;
+use fancy_display::FancyDisplay;
+use miette::{Context, IntoDiagnostic};
+use pixi_config::ConfigCli;
+use pixi_manifest::EnvironmentName;
+use pixi_progress::await_in_progress;
+
+use super::oci::{self, ImageConfig, ImageReference};
+use crate::{
+    activation::{run_activation, CurrentEnvVarBehavior},
+    cli::{cli_config::ProjectConfig, LockFileUsageArgs},
+    environment::get_update_lock_file_and_prefix,
+    lock_file::UpdateMode,
+    Project, UpdateLockFileOptions,
+};
+
+/// Build a container image from an environment of the project.
+///
+/// The image is written as an OCI archive that can be loaded with
+/// `docker load`, `podman load` or pushed with tools like `skopeo`, no
+/// container runtime is required to build it. The environment is placed at
+/// the same path in the image as on this machine because conda environments
+/// can't be relocated, and the variables of its activation are set in the
+/// image configuration.
+#[derive(Parser, Debug)]
+#[clap(trailing_var_arg = true)]
+pub struct Args {
+    #[clap(flatten)]
+    pub project_config: ProjectConfig,
+
+    #[clap(flatten)]
+    pub lock_file_usage: LockFileUsageArgs,
+
+    #[clap(flatten)]
+    pub config: ConfigCli,
+
+    /// The environment to put in the image
+    #[arg(long, short)]
+    pub environment: Option<String>,
+
+    /// The name and tag of the image, defaults to the name of the project and
+    /// environment with the tag `latest`
+    #[arg(long, short)]
+    pub tag: Option<ImageReference>,
+
+    /// The path of the archive to write, defaults to `<name>-<tag>.oci.tar`
+    /// in the current directory
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+
+    /// Files or directories of the project to add to the image, relative to
+    /// the project root. The working directory of the image is set to the
+    /// project root if any are added.
+    #[arg(long)]
+    pub include: Vec<PathBuf>,
+
+    /// The default command of the container, e.g. `python app.py`
+    pub cmd: Vec<String>,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.project_config.manifest_path.as_deref())?
+        .with_cli_config(args.config);
+    let environment = project.environment_from_name_or_env_var(args.environment)?;
+
+    // The installed prefix is put in the image, so it has to be built for a
+    // platform that runs in a Linux container.
+    let platform = environment.best_platform();
+    if oci::oci_architecture(platform).is_none() {
+        miette::bail!(
+            help = "build the image on a Linux machine, e.g. in CI",
+            "images can only be built from environments for linux platforms, the environment {} is installed for {}",
+            environment.name().fancy_display(),
+            platform
+        );
+    }
+    if !environment.platforms().contains(&platform) {
+        miette::bail!(
+            "the environment {} doesn't support the platform {}",
+            environment.name().fancy_display(),
+            platform
+        );
+    }
+
+    let include = args
+        .include
+        .iter()
+        .map(|path| {
+            let path = dunce::canonicalize(project.root().join(path))
+                .into_diagnostic()
+                .wrap_err_with(|| format!("failed to find '{}'", path.display()))?;
+            if !path.starts_with(project.root()) {
+                miette::bail!("'{}' is not part of the project", path.display());
+            }
+            if project.pixi_dir().starts_with(&path) {
+                miette::bail!(
+                    help = "include the files and directories of the project individually",
+                    "'{}' contains the environments of the project",
+                    path.display()
+                );
+            }
+            Ok(path)
+        })
+        .collect::<miette::Result<Vec<_>>>()?;
+
+    let reference = match args.tag {
+        Some(reference) => reference,
+        None => default_reference(project.name(), environment.name())?,
+    };
+    let output = args.output.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "{}-{}.oci.tar",
+            reference.name.rsplit('/').next().unwrap_or(&reference.name),
+            reference.tag
+        ))
+    });
+
+    // Make sure the environment is installed and matches the lock file.
+    let (lock_file_data, prefix) = get_update_lock_file_and_prefix(
+        &environment,
+        UpdateMode::Revalidate,
+        UpdateLockFileOptions {
+            lock_file_usage: args.lock_file_usage.into(),
+            no_install: false,
+            max_concurrent_solves: project.config().max_concurrent_solves(),
+        },
+    )
+    .await?;
+
+    // Replace the `PATH` instead of prepending to it, the `PATH` of the host
+    // has no meaning inside the image.
+    let activation_env = run_activation(
+        &environment,
+        &CurrentEnvVarBehavior::Clean,
+        Some(&lock_file_data.lock_file),
+        true,
+        false,
+    )
+    .await?;
+
+    let mut labels = BTreeMap::from([(
+        String::from("org.opencontainers.image.title"),
+        project.name().to_string(),
+    )]);
+    if let Some(version) = project.version() {
+        labels.insert(
+            String::from("org.opencontainers.image.version"),
+            version.to_string(),
+        );
+    }
+    let config = ImageConfig {
+        env: activation_env.into_iter().collect(),
+        cmd: args.cmd,
+        working_dir: (!include.is_empty()).then(|| project.root().to_path_buf()),
+        labels,
+    }
+    .with_system_path();
+
+    let mut paths = vec![prefix.root().to_path_buf()];
+    paths.extend(include);
+    let layer = await_in_progress("creating the image layer", |_| {
+        tokio::task::spawn_blocking(move || oci::build_layer(&paths))
+    })
+    .await
+    .into_diagnostic()?
+    .into_diagnostic()
+    .wrap_err("failed to create the image layer")?;
+
+    let output_path = output.clone();
+    let image_reference = reference.clone();
+    await_in_progress("writing the image", |_| {
+        tokio::task::spawn_blocking(move || {
+            oci::write_image(&output_path, &image_reference, platform, &config, layer)
+        })
+    })
+    .await
+    .into_diagnostic()?
+    .into_diagnostic()
+    .wrap_err_with(|| format!("failed to write the image to '{}'", output.display()))?;
+
+    eprintln!(
+        "{}Built image {} from environment {} in {}",
+        console::style(console::Emoji("✔ ", "")).green(),
+        console::style(&reference).bold(),
+        environment.name().fancy_display(),
+        console::style(output.display()).bold(),
+    );
+    eprintln!(
+        "Load it with `docker load --input {}` or `podman load --input {}`",
+        output.display(),
+        output.display()
+    );
+
+    Project::warn_on_discovered_from_env(args.project_config.manifest_path.as_deref());
+    Ok(())
+}
+
+/// Returns the default name of the image of an environment of the project.
+fn default_reference(
+    project_name: &str,
+    environment: &EnvironmentName,
+) -> miette::Result<ImageReference> {
+    let name = match environment {
+        EnvironmentName::Default => project_name.to_string(),
+        EnvironmentName::Named(name) => format!("{project_name}-{name}"),
+    };
+    // Image names only allow lowercase alphanumeric characters and separators.
+    let name = name
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    name.parse::<ImageReference>()
+        .map_err(|err| miette::miette!(help = "set the name of the image with `--tag`", "{err}"))
+}
@@ -0,0 +1,29 @@
+use clap::Parser;
+
+pub mod archive;
+pub mod image;
+mod oci;
+pub mod unpack;
+
+#[derive(Debug, Parser)]
+pub enum Command {
+    Archive(archive::Args),
+    Image(image::Args),
+    Unpack(unpack::Args),
+}
+
+/// Package an environment of the project so it can be deployed elsewhere.
+#[derive(Debug, Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+pub async fn execute(cmd: Args) -> miette::Result<()> {
+    match cmd.command {
+        Command::Archive(args) => archive::execute(args).await?,
+        Command::Image(args) => image::execute(args).await?,
+        Command::Unpack(args) => unpack::execute(args).await?,
+    };
+    Ok(())
+}
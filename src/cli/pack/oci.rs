@@ -0,0 +1,419 @@
+//! Writing of container images in the [OCI image layout] format, packed in a
+//! single tar archive that can be loaded by `docker load`, `podman load` and
+//! other tools that understand OCI archives.
+//!
+//! [OCI image layout]: https://github.com/opencontainers/image-spec/blob/main/image-layout.md
+
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    fs::File,
+    io::{self, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use flate2::{write::GzEncoder, Compression};
+use rattler_conda_types::Platform;
+use rattler_digest::{HashingWriter, Sha256};
+use serde_json::json;
+
+const MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+const CONFIG_MEDIA_TYPE: &str = "application/vnd.oci.image.config.v1+json";
+const LAYER_MEDIA_TYPE: &str = "application/vnd.oci.image.layer.v1.tar+gzip";
+
+/// The directories that are searched for executables after the directories of
+/// the environment.
+const SYSTEM_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// The name and tag of an image, e.g. `my-app:1.0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageReference {
+    pub name: String,
+    pub tag: String,
+}
+
+impl FromStr for ImageReference {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // A colon before the last slash belongs to the port of a registry.
+        let name_start = s.rfind('/').map_or(0, |idx| idx + 1);
+        let (name, tag) = match s[name_start..].rfind(':') {
+            Some(idx) => (&s[..name_start + idx], &s[name_start + idx + 1..]),
+            None => (s, "latest"),
+        };
+
+        if name.is_empty() || name.ends_with('/') {
+            return Err(format!("'{s}' is missing the name of the image"));
+        }
+        if tag.is_empty() {
+            return Err(format!("'{s}' has an empty tag"));
+        }
+        if name.chars().any(|c| c.is_ascii_uppercase()) {
+            return Err(format!("the name of the image '{name}' must be lowercase"));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            tag: tag.to_string(),
+        })
+    }
+}
+
+impl Display for ImageReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.name, self.tag)
+    }
+}
+
+/// Returns the OCI architecture and variant of a conda platform, or `None`
+/// if the platform can't run in a Linux container.
+pub fn oci_architecture(platform: Platform) -> Option<(&'static str, Option<&'static str>)> {
+    match platform {
+        Platform::Linux64 => Some(("amd64", None)),
+        Platform::Linux32 => Some(("386", None)),
+        Platform::LinuxAarch64 => Some(("arm64", Some("v8"))),
+        Platform::LinuxArmV7l => Some(("arm", Some("v7"))),
+        Platform::LinuxArmV6l => Some(("arm", Some("v6"))),
+        Platform::LinuxPpc64le => Some(("ppc64le", None)),
+        Platform::LinuxS390X => Some(("s390x", None)),
+        _ => None,
+    }
+}
+
+/// The configuration of the container that is started from the image.
+#[derive(Debug, Default)]
+pub struct ImageConfig {
+    /// The environment variables of the container.
+    pub env: BTreeMap<String, String>,
+
+    /// The default command of the container.
+    pub cmd: Vec<String>,
+
+    /// The working directory of the container.
+    pub working_dir: Option<PathBuf>,
+
+    /// Annotations of the image, e.g. its title and version.
+    pub labels: BTreeMap<String, String>,
+}
+
+impl ImageConfig {
+    /// Appends the default system directories to the `PATH`, so that the
+    /// executables of the environment take precedence.
+    pub fn with_system_path(mut self) -> Self {
+        let path = match self.env.remove("PATH") {
+            Some(path) if !path.is_empty() => format!("{path}:{SYSTEM_PATH}"),
+            _ => SYSTEM_PATH.to_string(),
+        };
+        self.env.insert(String::from("PATH"), path);
+        self
+    }
+}
+
+/// A compressed layer of an image, stored in a temporary file.
+pub struct Layer {
+    file: File,
+
+    /// The digest of the compressed layer.
+    digest: String,
+
+    /// The digest of the uncompressed layer.
+    diff_id: String,
+
+    /// The size of the compressed layer in bytes.
+    size: u64,
+}
+
+/// Formats a sha256 hash as an OCI digest.
+fn digest(hash: impl std::fmt::LowerHex) -> String {
+    format!("sha256:{hash:x}")
+}
+
+/// Returns the path of `path` inside of an archive that is extracted at the
+/// root of the file system.
+fn archive_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| matches!(component, std::path::Component::Normal(_)))
+        .collect()
+}
+
+/// Creates a layer that contains the given files and directories at the same
+/// absolute path as on this machine. Symbolic links are kept as is and the
+/// metadata of the entries is normalized so that building the same
+/// directories twice results in the same layer.
+pub fn build_layer(paths: &[PathBuf]) -> io::Result<Layer> {
+    let file = tempfile::tempfile()?;
+    let compressed = HashingWriter::<_, Sha256>::new(file);
+    let encoder = GzEncoder::new(compressed, Compression::default());
+    let uncompressed = HashingWriter::<_, Sha256>::new(encoder);
+
+    let mut builder = tar::Builder::new(uncompressed);
+    builder.mode(tar::HeaderMode::Deterministic);
+    builder.follow_symlinks(false);
+
+    // Add the parent directories first, so they get sensible permissions.
+    let mut directories = paths
+        .iter()
+        .flat_map(|path| path.ancestors().skip(1))
+        .map(archive_path)
+        .filter(|path| !path.as_os_str().is_empty())
+        .collect::<Vec<_>>();
+    directories.sort();
+    directories.dedup();
+    for directory in directories {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_mode(0o755);
+        header.set_size(0);
+        builder.append_data(&mut header, directory, io::empty())?;
+    }
+
+    for path in paths {
+        if path.is_dir() {
+            builder.append_dir_all(archive_path(path), path)?;
+        } else {
+            builder.append_path_with_name(path, archive_path(path))?;
+        }
+    }
+
+    let (encoder, diff_id) = builder.into_inner()?.finalize();
+    let (mut file, layer_digest) = encoder.finish()?.finalize();
+    let size = file.stream_position()?;
+
+    Ok(Layer {
+        file,
+        digest: digest(layer_digest),
+        diff_id: digest(diff_id),
+        size,
+    })
+}
+
+/// Appends a file with the given contents to the archive.
+fn append_bytes<W: Write>(
+    builder: &mut tar::Builder<W>,
+    path: &str,
+    data: &[u8],
+) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    builder.append_data(&mut header, path, data)
+}
+
+/// Returns the path of a blob in the image layout.
+fn blob_path(digest: &str) -> String {
+    format!("blobs/{}", digest.replacen(':', "/", 1))
+}
+
+/// Writes an image with a single layer to `output` as an OCI archive.
+pub fn write_image(
+    output: &Path,
+    reference: &ImageReference,
+    platform: Platform,
+    config: &ImageConfig,
+    mut layer: Layer,
+) -> io::Result<()> {
+    let (architecture, variant) = oci_architecture(platform).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{platform} is not supported by Linux containers"),
+        )
+    })?;
+
+    let mut image_config = json!({
+        "architecture": architecture,
+        "os": "linux",
+        "config": {
+            "Env": config.env.iter().map(|(key, value)| format!("{key}={value}")).collect::<Vec<_>>(),
+            "Labels": config.labels,
+        },
+        "rootfs": {
+            "type": "layers",
+            "diff_ids": [layer.diff_id],
+        },
+        "history": [{
+            "created_by": "pixi pack image",
+        }],
+    });
+    if let Some(variant) = variant {
+        image_config["variant"] = json!(variant);
+    }
+    if !config.cmd.is_empty() {
+        image_config["config"]["Cmd"] = json!(config.cmd);
+    }
+    if let Some(working_dir) = &config.working_dir {
+        image_config["config"]["WorkingDir"] = json!(working_dir);
+    }
+    let image_config = serde_json::to_vec(&image_config)?;
+    let config_digest = digest(rattler_digest::compute_bytes_digest::<Sha256>(
+        &image_config,
+    ));
+
+    let manifest = serde_json::to_vec(&json!({
+        "schemaVersion": 2,
+        "mediaType": MANIFEST_MEDIA_TYPE,
+        "config": {
+            "mediaType": CONFIG_MEDIA_TYPE,
+            "digest": config_digest,
+            "size": image_config.len(),
+        },
+        "layers": [{
+            "mediaType": LAYER_MEDIA_TYPE,
+            "digest": layer.digest,
+            "size": layer.size,
+        }],
+    }))?;
+    let manifest_digest = digest(rattler_digest::compute_bytes_digest::<Sha256>(&manifest));
+
+    let index = serde_json::to_vec(&json!({
+        "schemaVersion": 2,
+        "manifests": [{
+            "mediaType": MANIFEST_MEDIA_TYPE,
+            "digest": manifest_digest,
+            "size": manifest.len(),
+            "annotations": {
+                "io.containerd.image.name": reference.to_string(),
+                "org.opencontainers.image.ref.name": reference.tag,
+            },
+        }],
+    }))?;
+
+    // The manifest of the `docker save` format, so that versions of docker
+    // that don't understand the OCI layout can also load the image.
+    let docker_manifest = serde_json::to_vec(&json!([{
+        "Config": blob_path(&config_digest),
+        "RepoTags": [reference.to_string()],
+        "Layers": [blob_path(&layer.digest)],
+    }]))?;
+
+    let mut builder = tar::Builder::new(File::create(output)?);
+    append_bytes(
+        &mut builder,
+        "oci-layout",
+        br#"{"imageLayoutVersion":"1.0.0"}"#,
+    )?;
+    append_bytes(&mut builder, "index.json", &index)?;
+    append_bytes(&mut builder, "manifest.json", &docker_manifest)?;
+    append_bytes(&mut builder, &blob_path(&config_digest), &image_config)?;
+    append_bytes(&mut builder, &blob_path(&manifest_digest), &manifest)?;
+
+    layer.file.seek(SeekFrom::Start(0))?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(layer.size);
+    header.set_mode(0o644);
+    builder.append_data(&mut header, blob_path(&layer.digest), &mut layer.file)?;
+
+    builder.into_inner()?.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    #[test]
+    fn test_image_reference() {
+        let reference = ImageReference::from_str("my-app").unwrap();
+        assert_eq!(reference.name, "my-app");
+        assert_eq!(reference.tag, "latest");
+
+        let reference = ImageReference::from_str("localhost:5000/team/my-app:1.0").unwrap();
+        assert_eq!(reference.name, "localhost:5000/team/my-app");
+        assert_eq!(reference.tag, "1.0");
+        assert_eq!(reference.to_string(), "localhost:5000/team/my-app:1.0");
+
+        assert!(ImageReference::from_str(":1.0").is_err());
+        assert!(ImageReference::from_str("my-app:").is_err());
+        assert!(ImageReference::from_str("My-App").is_err());
+    }
+
+    #[test]
+    fn test_oci_architecture() {
+        assert_eq!(oci_architecture(Platform::Linux64), Some(("amd64", None)));
+        assert_eq!(
+            oci_architecture(Platform::LinuxAarch64),
+            Some(("arm64", Some("v8")))
+        );
+        assert_eq!(oci_architecture(Platform::OsxArm64), None);
+        assert_eq!(oci_architecture(Platform::Win64), None);
+    }
+
+    #[test]
+    fn test_system_path() {
+        let config = ImageConfig {
+            env: BTreeMap::from([(String::from("PATH"), String::from("/env/bin"))]),
+            ..ImageConfig::default()
+        }
+        .with_system_path();
+        assert_eq!(config.env["PATH"], format!("/env/bin:{SYSTEM_PATH}"));
+
+        let config = ImageConfig::default().with_system_path();
+        assert_eq!(config.env["PATH"], SYSTEM_PATH);
+    }
+
+    #[test]
+    fn test_write_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = dir.path().join("env");
+        fs_err::create_dir_all(prefix.join("bin")).unwrap();
+        fs_err::write(prefix.join("bin/tool"), "#!/bin/sh\necho hello\n").unwrap();
+
+        let layer = build_layer(&[prefix.clone()]).unwrap();
+        let output = dir.path().join("image.tar");
+        let reference = ImageReference::from_str("my-app:1.0").unwrap();
+        let config = ImageConfig {
+            cmd: vec![String::from("tool")],
+            ..ImageConfig::default()
+        }
+        .with_system_path();
+        write_image(&output, &reference, Platform::Linux64, &config, layer).unwrap();
+
+        let mut archive = tar::Archive::new(File::open(&output).unwrap());
+        let mut files = BTreeMap::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().to_string();
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents).unwrap();
+            files.insert(path, contents);
+        }
+
+        assert!(files.contains_key("oci-layout"));
+        let index: serde_json::Value = serde_json::from_slice(&files["index.json"]).unwrap();
+        let manifest_digest = index["manifests"][0]["digest"].as_str().unwrap();
+        assert_eq!(
+            index["manifests"][0]["annotations"]["io.containerd.image.name"],
+            "my-app:1.0"
+        );
+
+        // Every blob is stored under its digest.
+        let manifest: serde_json::Value =
+            serde_json::from_slice(&files[&blob_path(manifest_digest)]).unwrap();
+        let config_digest = manifest["config"]["digest"].as_str().unwrap();
+        let layer_digest = manifest["layers"][0]["digest"].as_str().unwrap();
+        for (digest_str, path) in [
+            (manifest_digest, blob_path(manifest_digest)),
+            (config_digest, blob_path(config_digest)),
+            (layer_digest, blob_path(layer_digest)),
+        ] {
+            let hash = rattler_digest::compute_bytes_digest::<Sha256>(&files[&path]);
+            assert_eq!(digest(hash), digest_str);
+        }
+
+        let image_config: serde_json::Value =
+            serde_json::from_slice(&files[&blob_path(config_digest)]).unwrap();
+        assert_eq!(image_config["architecture"], "amd64");
+        assert_eq!(image_config["config"]["Cmd"][0], "tool");
+
+        // The layer contains the prefix at its absolute path.
+        let layer = flate2::read::GzDecoder::new(&files[&blob_path(layer_digest)][..]);
+        let layer_paths = tar::Archive::new(layer)
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_path_buf())
+            .collect::<Vec<_>>();
+        assert!(layer_paths.contains(&archive_path(&prefix.join("bin/tool"))));
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use clap::Parser;
+use fancy_display::FancyDisplay;
+use fs_extra::dir::CopyOptions;
+use miette::{Context, IntoDiagnostic};
+use pixi_build_frontend::ToolContext;
+use pixi_config::{Config, ConfigCli};
+use pixi_consts::consts;
+use pixi_manifest::{EnvironmentName, SystemRequirements};
+use pixi_progress::await_in_progress;
+use pixi_record::PixiRecord;
+use pixi_utils::reqwest::build_reqwest_clients;
+use rattler::package_cache::PackageCache;
+use rattler_conda_types::{Platform, RepoDataRecord};
+use rattler_lock::{CondaPackageData, LockFile, LockedPackageRef, UrlOrPath};
+use typed_path::Utf8TypedPathBuf;
+
+use super::archive::{package_dir_name, read_metadata, CONDA_DIR, PYPI_DIR};
+use crate::{
+    build::BuildContext,
+    environment::{update_prefix_conda, update_prefix_pypi},
+    lock_file::{IoConcurrencyLimit, UvResolutionContext},
+    prefix::Prefix,
+};
+
+/// Install an environment from an archive created with `pixi pack archive`.
+///
+/// No network access is required, the packages are installed from the
+/// archive through the package cache like any other environment.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The archive to install
+    pub archive: PathBuf,
+
+    /// The directory to install the environment in
+    #[arg(long, default_value = "env")]
+    pub prefix: PathBuf,
+
+    #[clap(flatten)]
+    pub config: ConfigCli,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let config = Config::with_cli_config(&args.config);
+    let cache_dir = pixi_config::get_cache_dir().context("failed to determine cache directory")?;
+
+    let metadata = read_metadata(&args.archive)?;
+    let platform = Platform::current();
+    if metadata.platform != platform && metadata.platform != Platform::NoArch {
+        miette::bail!(
+            "the archive contains packages for {} and can't be installed on {}",
+            metadata.platform,
+            platform
+        );
+    }
+    let environment_name = EnvironmentName::from_str(&metadata.environment)?;
+
+    fs_err::create_dir_all(&args.prefix).into_diagnostic()?;
+    let prefix = Prefix::new(dunce::canonicalize(&args.prefix).into_diagnostic()?);
+
+    // Unpack the archive next to the prefix, the package files are linked into
+    // the package cache from there.
+    let unpacked = tempfile::Builder::new()
+        .prefix(".pixi-unpack-")
+        .tempdir_in(prefix.root().parent().unwrap_or(prefix.root()))
+        .into_diagnostic()?;
+    let archive_path = args.archive.clone();
+    let unpacked_dir = unpacked.path().to_path_buf();
+    await_in_progress("unpacking the archive", |_| {
+        tokio::task::spawn_blocking(move || {
+            tar::Archive::new(fs_err::File::open(archive_path)?).unpack(unpacked_dir)
+        })
+    })
+    .await
+    .into_diagnostic()?
+    .into_diagnostic()
+    .wrap_err_with(|| format!("failed to unpack '{}'", args.archive.display()))?;
+
+    let lock_file_path = unpacked.path().join(consts::PROJECT_LOCK_FILE);
+    let lock_file = LockFile::from_path(&lock_file_path)
+        .into_diagnostic()
+        .context("failed to read the lock file of the archive")?;
+    let locked_environment = lock_file
+        .environment(&metadata.environment)
+        .ok_or_else(|| {
+            miette::miette!(
+                "the lock file of the archive doesn't contain the environment {}",
+                environment_name.fancy_display()
+            )
+        })?;
+
+    // Add the conda packages of the archive to the package cache and point the
+    // wheels to their files in the archive, so nothing is downloaded.
+    let package_cache = PackageCache::new(cache_dir.join(consts::CONDA_PACKAGE_CACHE_DIR));
+    let mut conda_records = Vec::new();
+    let mut pypi_records = Vec::new();
+    for package in locked_environment
+        .packages(metadata.platform)
+        .into_iter()
+        .flatten()
+    {
+        match package {
+            LockedPackageRef::Conda(CondaPackageData::Binary(data)) => {
+                let record = RepoDataRecord::try_from(data.clone()).into_diagnostic()?;
+                let source = unpacked
+                    .path()
+                    .join(CONDA_DIR)
+                    .join(package_dir_name(&record.package_record));
+                package_cache
+                    .get_or_fetch(
+                        &record.package_record,
+                        move |destination| {
+                            let source = source.clone();
+                            async move { copy_package_dir(&source, &destination) }
+                        },
+                        None,
+                    )
+                    .await
+                    .into_diagnostic()
+                    .wrap_err_with(|| {
+                        format!(
+                            "failed to add '{}' to the package cache",
+                            record.package_record.name.as_source()
+                        )
+                    })?;
+                conda_records.push(PixiRecord::Binary(record));
+            }
+            LockedPackageRef::Conda(CondaPackageData::Source(data)) => miette::bail!(
+                "the archive contains the source package '{}' which can't be installed",
+                data.package_record.name.as_source()
+            ),
+            LockedPackageRef::Pypi(data, env_data) => {
+                let file_name = match &data.location {
+                    UrlOrPath::Url(url) => url.path_segments().and_then(|mut s| s.next_back()),
+                    UrlOrPath::Path(path) => path.file_name(),
+                }
+                .map(|name| percent_encoding::percent_decode_str(name).decode_utf8_lossy())
+                .ok_or_else(|| miette::miette!("the archive doesn't contain '{}'", data.name))?;
+                let path = unpacked.path().join(PYPI_DIR).join(file_name.as_ref());
+                let mut data = data.clone();
+                data.location =
+                    UrlOrPath::Path(Utf8TypedPathBuf::from(path.to_string_lossy().to_string()));
+                pypi_records.push((data, env_data.clone()));
+            }
+        }
+    }
+
+    let (client, authenticated_client) = build_reqwest_clients(Some(&config));
+    let installed_packages = prefix.find_installed_packages(None).await?;
+    let build_context = BuildContext::new(
+        cache_dir.clone(),
+        unpacked.path().to_path_buf(),
+        config.global_channel_config().clone(),
+        Arc::new(ToolContext::default()),
+    )
+    .into_diagnostic()?;
+    let python_status = update_prefix_conda(
+        &prefix,
+        package_cache,
+        authenticated_client,
+        installed_packages,
+        conda_records.clone(),
+        Vec::new(),
+        Vec::new(),
+        platform,
+        &format!(
+            "unpacking environment '{}'",
+            environment_name.fancy_display()
+        ),
+        "",
+        IoConcurrencyLimit::default().into(),
+        build_context,
+    )
+    .await?;
+
+    let uv_context = UvResolutionContext::from_config(&config, client)?;
+    let environment_variables = prefix.run_activation().await?;
+    update_prefix_pypi(
+        &environment_name,
+        &prefix,
+        platform,
+        &conda_records,
+        &pypi_records,
+        &python_status,
+        &SystemRequirements::default(),
+        &uv_context,
+        None,
+        &environment_variables,
+        unpacked.path(),
+        platform,
+        None,
+    )
+    .await
+    .context("failed to install the PyPI packages of the archive")?;
+
+    eprintln!(
+        "{}Installed environment {} in {}",
+        console::style(console::Emoji("✔ ", "")).green(),
+        environment_name.fancy_display(),
+        console::style(prefix.root().display()).bold(),
+    );
+    Ok(())
+}
+
+/// Copies an extracted package from the archive into the package cache.
+fn copy_package_dir(source: &Path, destination: &Path) -> Result<(), fs_extra::error::Error> {
+    fs_err::create_dir_all(destination)?;
+    fs_extra::dir::copy(source, destination, &CopyOptions::new().content_only(true))?;
+    Ok(())
+}
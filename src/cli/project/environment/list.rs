@@ -1,18 +1,41 @@
-use crate::Project;
+use crate::{project::Environment, Project};
 use fancy_display::FancyDisplay;
 use itertools::Itertools;
+use pixi_manifest::{FeaturesExt, HasFeaturesIter};
 
 pub async fn execute(project: Project) -> miette::Result<()> {
-    println!(
-        "{}",
-        project
-            .environments()
-            .iter()
-            .format_with("\n", |e, f| f(&format_args!(
-                "- {}",
-                e.name().fancy_display()
-            )))
-    );
+    let environments = &project.manifest.workspace.environments;
+    for manifest_env in environments
+        .iter()
+        .chain(environments.abstract_environments())
+    {
+        let env = Environment::new(&project, manifest_env);
+        let mut header = format!("- {}", env.name().fancy_display());
+        if let Some(extends) = &manifest_env.extends {
+            header.push_str(&format!(" (extends {})", extends.fancy_display()));
+        }
+        if manifest_env.is_abstract {
+            header.push_str(&format!(" {}", console::style("(abstract)").dim()));
+        }
+        println!("{header}");
+
+        // Show the composition after inheritance, the features in the order in
+        // which they take precedence.
+        println!(
+            "    features: {}",
+            env.features()
+                .map(|feature| feature.name.fancy_display())
+                .format(", ")
+        );
+        println!(
+            "    platforms: {}",
+            env.platforms()
+                .into_iter()
+                .sorted_by_key(|platform| platform.as_str())
+                .format(", ")
+        );
+        println!("    channels: {}", env.channels().into_iter().format(", "));
+    }
 
     Ok(())
 }
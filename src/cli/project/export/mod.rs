@@ -1,5 +1,6 @@
 pub mod conda_environment;
 pub mod conda_explicit_spec;
+pub mod sbom;
 
 use clap::Parser;
 
@@ -17,12 +18,15 @@ pub enum Command {
     CondaExplicitSpec(conda_explicit_spec::Args),
     /// Export project environment to a conda environment.yaml file
     CondaEnvironment(conda_environment::Args),
+    /// Export a software bill of materials (SBOM) of a project environment
+    Sbom(sbom::Args),
 }
 
 pub async fn execute(args: Args) -> miette::Result<()> {
     match args.command {
         Command::CondaExplicitSpec(args) => conda_explicit_spec::execute(args).await?,
         Command::CondaEnvironment(args) => conda_environment::execute(args).await?,
+        Command::Sbom(args) => sbom::execute(args).await?,
     };
     Ok(())
 }
@@ -0,0 +1,555 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use clap::{Parser, ValueEnum};
+use itertools::Itertools;
+use miette::{Context, IntoDiagnostic};
+use pixi_consts::consts;
+use rattler_conda_types::Platform;
+use rattler_lock::{LockedPackageRef, PackageHashes};
+use serde_json::{json, Value};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::{
+    cli::cli_config::{PrefixUpdateConfig, ProjectConfig},
+    lock_file::{PypiPackageIdentifier, UpdateLockFileOptions},
+    Project,
+};
+
+/// The SBOM formats that can be exported.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum SbomFormat {
+    /// CycloneDX 1.5 (JSON)
+    Cyclonedx,
+    /// SPDX 2.3 (JSON)
+    Spdx,
+}
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    #[clap(flatten)]
+    pub project_config: ProjectConfig,
+
+    /// Explicit path to export the SBOM to. Defaults to standard out.
+    pub output_path: Option<PathBuf>,
+
+    /// The format of the SBOM.
+    #[arg(long, value_enum, default_value = "cyclonedx")]
+    pub format: SbomFormat,
+
+    /// The platform to render the SBOM for.
+    /// Defaults to the current platform.
+    #[arg(short, long)]
+    pub platform: Option<Platform>,
+
+    /// The environment to render the SBOM for.
+    /// Defaults to the default environment.
+    #[arg(short, long)]
+    pub environment: Option<String>,
+
+    #[clap(flatten)]
+    pub prefix_update_config: PrefixUpdateConfig,
+}
+
+/// The ecosystem of a package in the SBOM.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum PackageKind {
+    Conda,
+    Pypi,
+}
+
+/// A locked package with all the information that ends up in the SBOM.
+#[derive(Debug, Clone)]
+struct Component {
+    /// A reference that uniquely identifies the component in the SBOM.
+    id: String,
+    name: String,
+    version: String,
+    purl: String,
+    license: Option<String>,
+    /// Pairs of (algorithm, hex digest)
+    hashes: Vec<(&'static str, String)>,
+    location: String,
+    /// The PyPI packages that are provided by a conda package.
+    provided_purls: Vec<String>,
+    /// The ids of the components this component depends on.
+    dependencies: Vec<String>,
+}
+
+/// Information about the document itself.
+struct SbomMetadata {
+    project_name: String,
+    project_version: Option<String>,
+    environment: String,
+    platform: Platform,
+    timestamp: String,
+}
+
+/// Extracts the components from the packages of a single locked target.
+fn collect_components<'a>(
+    packages: impl IntoIterator<Item = LockedPackageRef<'a>>,
+) -> Vec<Component> {
+    let mut components = Vec::new();
+    let mut dependency_names = Vec::new();
+    let mut ids_by_name: HashMap<(PackageKind, String), String> = HashMap::new();
+
+    for package in packages {
+        let component = match package {
+            LockedPackageRef::Conda(conda) => {
+                let record = conda.record();
+                let name = record.name.as_normalized().to_string();
+                let version = record.version.to_string();
+                let location = conda.location().to_string();
+                let archive_type = if location.ends_with(".conda") {
+                    "conda"
+                } else {
+                    "tar.bz2"
+                };
+                let purl = format!(
+                    "pkg:conda/{name}@{version}?build={}&subdir={}&type={archive_type}",
+                    record.build, record.subdir
+                );
+                let mut hashes = Vec::new();
+                if let Some(sha256) = record.sha256 {
+                    hashes.push(("SHA-256", format!("{:x}", sha256)));
+                }
+                if let Some(md5) = record.md5 {
+                    hashes.push(("MD5", format!("{:x}", md5)));
+                }
+                dependency_names.push(
+                    record
+                        .depends
+                        .iter()
+                        .map(|spec| {
+                            let name = spec.split_once(' ').map_or(spec.as_str(), |(name, _)| name);
+                            (PackageKind::Conda, name.to_string())
+                        })
+                        .collect_vec(),
+                );
+                ids_by_name.insert((PackageKind::Conda, name.clone()), purl.clone());
+
+                // PyPI packages can depend on the PyPI packages that are provided by
+                // a conda package. A locked PyPI package with the same name takes
+                // precedence.
+                for provided in
+                    PypiPackageIdentifier::from_package_record(record).unwrap_or_default()
+                {
+                    ids_by_name
+                        .entry((PackageKind::Pypi, provided.name.as_normalized().to_string()))
+                        .or_insert_with(|| purl.clone());
+                }
+                Component {
+                    id: purl.clone(),
+                    name,
+                    version,
+                    purl,
+                    license: record.license.clone(),
+                    hashes,
+                    location,
+                    provided_purls: record
+                        .purls
+                        .iter()
+                        .flatten()
+                        .map(|purl| purl.to_string())
+                        .collect(),
+                    dependencies: Vec::new(),
+                }
+            }
+            LockedPackageRef::Pypi(data, _) => {
+                let name = data.name.to_string();
+                let version = data.version.to_string();
+                let purl = format!("pkg:pypi/{name}@{version}");
+                let hashes = match &data.hash {
+                    Some(PackageHashes::Md5(md5)) => vec![("MD5", format!("{:x}", md5))],
+                    Some(PackageHashes::Sha256(sha256)) => {
+                        vec![("SHA-256", format!("{:x}", sha256))]
+                    }
+                    Some(PackageHashes::Md5Sha256(md5, sha256)) => vec![
+                        ("SHA-256", format!("{:x}", sha256)),
+                        ("MD5", format!("{:x}", md5)),
+                    ],
+                    None => Vec::new(),
+                };
+                dependency_names.push(
+                    data.requires_dist
+                        .iter()
+                        .map(|req| (PackageKind::Pypi, req.name.to_string()))
+                        .collect_vec(),
+                );
+                ids_by_name.insert((PackageKind::Pypi, name.clone()), purl.clone());
+                Component {
+                    id: purl.clone(),
+                    name,
+                    version,
+                    purl,
+                    license: None,
+                    hashes,
+                    location: data.location.to_string(),
+                    provided_purls: Vec::new(),
+                    dependencies: Vec::new(),
+                }
+            }
+        };
+        components.push(component);
+    }
+
+    // Resolve the dependency names to the components in the lock-file. Names
+    // that are not locked (e.g. virtual packages) are skipped.
+    for (component, names) in components.iter_mut().zip(dependency_names) {
+        component.dependencies = names
+            .into_iter()
+            .filter_map(|name| ids_by_name.get(&name).cloned())
+            .unique()
+            .sorted()
+            .collect();
+    }
+
+    components.sort_by(|a, b| a.id.cmp(&b.id));
+    components
+}
+
+/// Returns the license if it is a valid SPDX expression.
+fn spdx_expression(license: Option<&str>) -> Option<&str> {
+    license.filter(|license| spdx::Expression::parse(license).is_ok())
+}
+
+fn render_cyclonedx(metadata: &SbomMetadata, components: &[Component]) -> Value {
+    let components_json = components
+        .iter()
+        .map(|component| {
+            let mut value = json!({
+                "type": "library",
+                "bom-ref": component.id,
+                "name": component.name,
+                "version": component.version,
+                "purl": component.purl,
+                "hashes": component.hashes.iter().map(|(alg, content)| json!({
+                    "alg": alg,
+                    "content": content,
+                })).collect_vec(),
+                "externalReferences": [{
+                    "type": "distribution",
+                    "url": component.location,
+                }],
+            });
+            if let Some(license) = &component.license {
+                value["licenses"] = match spdx_expression(Some(license)) {
+                    Some(expression) => json!([{ "expression": expression }]),
+                    None => json!([{ "license": { "name": license } }]),
+                };
+            }
+            if !component.provided_purls.is_empty() {
+                value["properties"] = component
+                    .provided_purls
+                    .iter()
+                    .map(|purl| json!({ "name": "pixi:provides", "value": purl }))
+                    .collect();
+            }
+            value
+        })
+        .collect_vec();
+
+    let root_ref = format!("{}:{}", metadata.project_name, metadata.environment);
+    let dependencies = std::iter::once(json!({
+        "ref": root_ref,
+        "dependsOn": components.iter().map(|c| &c.id).collect_vec(),
+    }))
+    .chain(components.iter().map(|component| {
+        json!({
+            "ref": component.id,
+            "dependsOn": component.dependencies,
+        })
+    }))
+    .collect_vec();
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "timestamp": metadata.timestamp,
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": "pixi",
+                    "version": consts::PIXI_VERSION,
+                }],
+            },
+            "component": {
+                "type": "application",
+                "bom-ref": root_ref,
+                "name": metadata.project_name,
+                "version": metadata.project_version,
+                "properties": [
+                    { "name": "pixi:environment", "value": metadata.environment },
+                    { "name": "pixi:platform", "value": metadata.platform.as_str() },
+                ],
+            },
+        },
+        "components": components_json,
+        "dependencies": dependencies,
+    })
+}
+
+/// Converts a component id into a valid SPDX identifier, which may only
+/// contain letters, numbers, `.` and `-`.
+fn spdx_id(id: &str) -> String {
+    let sanitized: String = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("SPDXRef-Package-{sanitized}")
+}
+
+fn render_spdx(metadata: &SbomMetadata, components: &[Component]) -> Value {
+    // The namespace must be unique for every document, derive it from its
+    // content so the same lock-file results in the same document.
+    let mut hasher = Xxh3::new();
+    for component in components {
+        hasher.update(component.id.as_bytes());
+    }
+    let namespace = format!(
+        "https://pixi.sh/spdxdocs/{}-{}-{}-{:x}",
+        metadata.project_name,
+        metadata.environment,
+        metadata.platform,
+        hasher.digest()
+    );
+
+    let packages = components
+        .iter()
+        .map(|component| {
+            json!({
+                "name": component.name,
+                "SPDXID": spdx_id(&component.id),
+                "versionInfo": component.version,
+                "downloadLocation": component.location,
+                "filesAnalyzed": false,
+                "licenseConcluded": "NOASSERTION",
+                "licenseDeclared": spdx_expression(component.license.as_deref()).unwrap_or("NOASSERTION"),
+                "copyrightText": "NOASSERTION",
+                "checksums": component.hashes.iter().map(|(alg, content)| json!({
+                    "algorithm": alg.replace('-', ""),
+                    "checksumValue": content,
+                })).collect_vec(),
+                "externalRefs": std::iter::once(&component.purl)
+                    .chain(component.provided_purls.iter())
+                    .map(|purl| json!({
+                        "referenceCategory": "PACKAGE-MANAGER",
+                        "referenceType": "purl",
+                        "referenceLocator": purl,
+                    }))
+                    .collect_vec(),
+            })
+        })
+        .collect_vec();
+
+    let relationships = components
+        .iter()
+        .map(|component| {
+            json!({
+                "spdxElementId": "SPDXRef-DOCUMENT",
+                "relationshipType": "DESCRIBES",
+                "relatedSpdxElement": spdx_id(&component.id),
+            })
+        })
+        .chain(components.iter().flat_map(|component| {
+            component.dependencies.iter().map(|dependency| {
+                json!({
+                    "spdxElementId": spdx_id(&component.id),
+                    "relationshipType": "DEPENDS_ON",
+                    "relatedSpdxElement": spdx_id(dependency),
+                })
+            })
+        }))
+        .collect_vec();
+
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": format!("{}-{}-{}", metadata.project_name, metadata.environment, metadata.platform),
+        "documentNamespace": namespace,
+        "creationInfo": {
+            "created": metadata.timestamp,
+            "creators": [format!("Tool: pixi-{}", consts::PIXI_VERSION)],
+        },
+        "packages": packages,
+        "relationships": relationships,
+    })
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.project_config.manifest_path.as_deref())?
+        .with_cli_config(args.prefix_update_config.config.clone());
+    let environment = project.environment_from_name_or_env_var(args.environment)?;
+    let platform = args.platform.unwrap_or_else(|| environment.best_platform());
+
+    let lock_file = project
+        .update_lock_file(UpdateLockFileOptions {
+            lock_file_usage: args.prefix_update_config.lock_file_usage(),
+            no_install: args.prefix_update_config.no_install,
+            max_concurrent_solves: project.config().max_concurrent_solves(),
+        })
+        .await?
+        .lock_file;
+
+    let packages = lock_file
+        .environment(environment.name().as_str())
+        .and_then(|env| env.packages(platform))
+        .ok_or_else(|| {
+            miette::miette!(
+                "platform '{platform}' not found for environment {}",
+                environment.name()
+            )
+        })?;
+    let components = collect_components(packages);
+
+    let metadata = SbomMetadata {
+        project_name: project.name().to_string(),
+        project_version: project.version().as_ref().map(|v| v.to_string()),
+        environment: environment.name().to_string(),
+        platform,
+        timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    };
+    let sbom = match args.format {
+        SbomFormat::Cyclonedx => render_cyclonedx(&metadata, &components),
+        SbomFormat::Spdx => render_spdx(&metadata, &components),
+    };
+    let sbom = serde_json::to_string_pretty(&sbom).into_diagnostic()?;
+
+    if let Some(output_path) = args.output_path {
+        fs_err::write(&output_path, sbom)
+            .into_diagnostic()
+            .with_context(|| "failed to write SBOM")?;
+    } else {
+        println!("{}", sbom);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use rattler_lock::LockFile;
+
+    use super::*;
+
+    fn test_components() -> Vec<Component> {
+        environment_components("default")
+    }
+
+    fn environment_components(environment: &str) -> Vec<Component> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/data/mock-projects/test-project-export/pixi.lock");
+        let lock_file = LockFile::from_path(&path).unwrap();
+        let packages = lock_file
+            .environment(environment)
+            .unwrap()
+            .packages(Platform::Linux64)
+            .unwrap();
+        collect_components(packages)
+    }
+
+    fn test_metadata() -> SbomMetadata {
+        SbomMetadata {
+            project_name: "test-project-export".to_string(),
+            project_version: Some("0.1.0".to_string()),
+            environment: "default".to_string(),
+            platform: Platform::Linux64,
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_collect_components() {
+        let components = test_components();
+        assert!(!components.is_empty());
+
+        // Every dependency must point to a component in the SBOM.
+        for component in &components {
+            assert!(
+                !component.hashes.is_empty(),
+                "{} has no hashes",
+                component.id
+            );
+            for dependency in &component.dependencies {
+                assert!(components.iter().any(|c| &c.id == dependency));
+            }
+        }
+
+        let python = components
+            .iter()
+            .find(|c| c.name == "python")
+            .expect("python should be locked");
+        assert!(python.purl.starts_with("pkg:conda/python@"));
+
+        let rich = components
+            .iter()
+            .find(|c| c.name == "rich")
+            .expect("rich should be locked");
+        assert!(rich
+            .dependencies
+            .iter()
+            .any(|d| d.starts_with("pkg:pypi/markdown-it-py@")));
+    }
+
+    #[test]
+    fn test_pypi_dependency_on_conda_package() {
+        // `pygments` is installed from PyPI and depends on `colorama`, which is
+        // provided by a conda package.
+        let components = environment_components("test");
+        let pygments = components
+            .iter()
+            .find(|c| c.name == "pygments")
+            .expect("pygments should be locked");
+        assert!(pygments
+            .dependencies
+            .iter()
+            .any(|d| d.starts_with("pkg:conda/colorama@")));
+    }
+
+    #[test]
+    fn test_render_cyclonedx() {
+        let components = test_components();
+        let sbom = render_cyclonedx(&test_metadata(), &components);
+        assert_eq!(sbom["bomFormat"], "CycloneDX");
+        assert_eq!(
+            sbom["components"].as_array().unwrap().len(),
+            components.len()
+        );
+        assert_eq!(
+            sbom["dependencies"].as_array().unwrap().len(),
+            components.len() + 1
+        );
+    }
+
+    #[test]
+    fn test_render_spdx() {
+        let components = test_components();
+        let sbom = render_spdx(&test_metadata(), &components);
+        assert_eq!(sbom["spdxVersion"], "SPDX-2.3");
+
+        let packages = sbom["packages"].as_array().unwrap();
+        assert_eq!(packages.len(), components.len());
+        for package in packages {
+            let id = package["SPDXID"].as_str().unwrap();
+            assert!(id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-'));
+        }
+
+        // Rendering twice results in the same namespace.
+        assert_eq!(
+            sbom["documentNamespace"],
+            render_spdx(&test_metadata(), &components)["documentNamespace"]
+        );
+    }
+}
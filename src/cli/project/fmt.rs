@@ -0,0 +1,47 @@
+use clap::Parser;
+
+use crate::{cli::cli_config::ProjectConfig, Project};
+
+/// Format the manifest: sort the tables and dependencies and write the specs
+/// in their shortest form.
+#[derive(Parser, Debug)]
+pub struct Args {
+    #[clap(flatten)]
+    pub project_config: ProjectConfig,
+
+    /// Don't write the manifest, exit with an error if it is not formatted.
+    #[clap(long)]
+    pub check: bool,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let mut project = Project::load_or_else_discover(args.project_config.manifest_path.as_deref())?;
+    let manifest_path = project.manifest_path();
+
+    project.manifest.document.format();
+    let formatted = project.manifest.document.to_string();
+    if project.manifest.contents.as_deref() == Some(formatted.as_str()) {
+        eprintln!(
+            "{}The manifest '{}' is formatted",
+            console::style(console::Emoji("✔ ", "")).green(),
+            manifest_path.display()
+        );
+        return Ok(());
+    }
+
+    if args.check {
+        miette::bail!(
+            help = "run `pixi project fmt` to format it",
+            "the manifest '{}' is not formatted",
+            manifest_path.display()
+        );
+    }
+
+    project.save()?;
+    eprintln!(
+        "{}Formatted the manifest '{}'",
+        console::style(console::Emoji("✔ ", "")).green(),
+        manifest_path.display()
+    );
+    Ok(())
+}
@@ -5,7 +5,9 @@ pub mod channel;
 pub mod description;
 pub mod environment;
 pub mod export;
+pub mod fmt;
 pub mod platform;
+pub mod validate;
 pub mod version;
 
 #[derive(Debug, Parser)]
@@ -16,6 +18,8 @@ pub enum Command {
     Version(version::Args),
     Environment(environment::Args),
     Export(export::Args),
+    Fmt(fmt::Args),
+    Validate(validate::Args),
 }
 
 /// Modify the project configuration file through the command line.
@@ -36,6 +40,8 @@ pub async fn execute(cmd: Args) -> miette::Result<()> {
         Command::Version(args) => version::execute(args).await?,
         Command::Environment(args) => environment::execute(args).await?,
         Command::Export(cmd) => export::execute(cmd).await?,
+        Command::Fmt(args) => fmt::execute(args).await?,
+        Command::Validate(args) => validate::execute(args).await?,
     };
     Ok(())
 }
@@ -0,0 +1,71 @@
+use clap::Parser;
+use miette::{IntoDiagnostic, WrapErr};
+use pixi_manifest::{FixableError, Manifest};
+
+use crate::{cli::cli_config::ProjectConfig, Project};
+
+/// The maximum number of fixes that are applied to a manifest, this guards
+/// against fixes that don't resolve the error they are made for.
+const MAX_FIXES: usize = 100;
+
+/// Validate the manifest and suggest fixes for misspelled keys and names.
+#[derive(Parser, Debug)]
+pub struct Args {
+    #[clap(flatten)]
+    pub project_config: ProjectConfig,
+
+    /// Apply the fixes that have a single likely correction to the manifest.
+    #[clap(long)]
+    pub fix: bool,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let manifest_path = match args.project_config.manifest_path {
+        Some(path) => path,
+        None => Project::discover_manifest_path()?,
+    };
+    let contents = fs_err::read_to_string(&manifest_path).into_diagnostic()?;
+
+    let mut fixed = contents.clone();
+    let mut fixes = Vec::new();
+    let result = loop {
+        let err = match Manifest::from_str(&manifest_path, fixed.as_str()) {
+            Ok(_) => break Ok(()),
+            Err(err) if !args.fix || fixes.len() >= MAX_FIXES => break Err(err),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<FixableError>() {
+            Ok(err) => err,
+            Err(err) => break Err(err),
+        };
+        match err.fix.apply(&fixed) {
+            Some(source) => {
+                fixed = source;
+                fixes.push(err.fix);
+            }
+            None => break Err(err.into()),
+        }
+    };
+
+    if fixed != contents {
+        fs_err::write(&manifest_path, &fixed)
+            .into_diagnostic()
+            .wrap_err("failed to write the fixed manifest")?;
+        for fix in &fixes {
+            eprintln!(
+                "{}Replaced '{}' with '{}'",
+                console::style(console::Emoji("✔ ", "")).green(),
+                fix.original,
+                fix.replacement
+            );
+        }
+    }
+
+    result?;
+    eprintln!(
+        "{}The manifest '{}' is valid",
+        console::style(console::Emoji("✔ ", "")).green(),
+        manifest_path.display()
+    );
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use clap::Parser;
+use console::Alignment;
 use dialoguer::theme::ColorfulTheme;
 use fancy_display::FancyDisplay;
 use itertools::Itertools;
@@ -6,6 +7,7 @@ use miette::{Diagnostic, IntoDiagnostic};
 use std::collections::hash_map::Entry;
 use std::collections::HashSet;
 use std::convert::identity;
+use std::io::IsTerminal;
 use std::{collections::HashMap, string::String};
 
 use crate::cli::cli_config::{PrefixUpdateConfig, ProjectConfig};
@@ -16,7 +18,8 @@ use crate::project::virtual_packages::verify_current_platform_has_required_virtu
 use crate::project::Environment;
 use crate::task::{
     get_task_env, AmbiguousTask, CanSkip, ExecutableTask, FailedToParseShellScript,
-    InvalidWorkingDirectory, SearchEnvironments, TaskAndEnvironment, TaskGraph,
+    InvalidWorkingDirectory, SearchEnvironments, TaskAndEnvironment, TaskGraph, TaskRunOutcome,
+    TaskRunStatus,
 };
 use crate::Project;
 use pixi_config::ConfigCliActivation;
@@ -45,6 +48,11 @@ pub struct Args {
     #[arg(long, short)]
     pub environment: Option<String>,
 
+    /// The member of the workspace to run the task of, the task runs in the
+    /// directory of the member.
+    #[arg(long = "package", short = 'p', value_name = "MEMBER")]
+    pub member: Option<String>,
+
     /// Use a clean environment to run the task
     ///
     /// Using this flag will ignore your current shell environment and use bare
@@ -56,7 +64,7 @@ pub struct Args {
 /// CLI entry point for `pixi run`
 /// When running the sigints are ignored and child can react to them. As it
 /// pleases.
-pub async fn execute(args: Args) -> miette::Result<()> {
+pub async fn execute(mut args: Args) -> miette::Result<()> {
     let cli_config = args
         .activation_config
         .merge_config(args.prefix_update_config.config.clone().into());
@@ -65,26 +73,46 @@ pub async fn execute(args: Args) -> miette::Result<()> {
     let project = Project::load_or_else_discover(args.project_config.manifest_path.as_deref())?
         .with_cli_config(cli_config);
 
+    // Find the member of the workspace to run the task of.
+    let member = args
+        .member
+        .as_deref()
+        .map(|name| {
+            project.manifest().member(name).ok_or_else(|| {
+                miette::miette!(
+                    help = format!(
+                        "the members of the workspace are: {}",
+                        project.manifest().member_names().format(", ")
+                    ),
+                    "the workspace has no member named '{name}'"
+                )
+            })
+        })
+        .transpose()?;
+
     // Extract the passed in environment name.
-    let environment = project.environment_from_name_or_env_var(args.environment.clone())?;
+    let mut environment = project.environment_from_name_or_env_var(args.environment.clone())?;
 
     // Find the environment to run the task in, if any were specified.
-    let explicit_environment = if args.environment.is_none() && environment.is_default() {
+    let mut explicit_environment = if args.environment.is_none() && environment.is_default() {
         None
     } else {
         Some(environment.clone())
     };
 
-    // Print all available tasks if no task is provided
+    // Let the user pick a task if no task is provided, or print all available
+    // tasks if we can't ask.
     if args.task.is_empty() {
-        command_not_found(&project, explicit_environment);
-        return Ok(());
-    }
-
-    // Print all available tasks if no task is provided
-    if args.task.is_empty() {
-        command_not_found(&project, explicit_environment);
-        return Ok(());
+        if !(std::io::stdin().is_terminal() && std::io::stderr().is_terminal()) {
+            command_not_found(&project, explicit_environment);
+            return Ok(());
+        }
+        let Some((task_name, task_environment)) = pick_task(&project, explicit_environment)? else {
+            return Ok(());
+        };
+        args.task = vec![task_name.as_str().to_string()];
+        environment = task_environment.clone();
+        explicit_environment = Some(task_environment);
     }
 
     // Sanity check of prefix location
@@ -114,6 +142,7 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         explicit_environment.clone(),
         Some(best_platform),
     )
+    .with_member(member)
     .with_disambiguate_fn(disambiguate_task_interactive);
 
     let task_graph = TaskGraph::from_cmd_args(&project, &search_environment, args.task)?;
@@ -173,6 +202,7 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         {
             CanSkip::No(cache) => cache,
             CanSkip::Yes => {
+                save_run_status(&executable_task, TaskRunOutcome::Skipped).await;
                 eprintln!(
                     "Task '{}' can be skipped (cache hit) 🚀",
                     console::style(executable_task.name().unwrap_or("")).bold()
@@ -213,9 +243,11 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         // the same code.
         match execute_task(&executable_task, task_env).await {
             Ok(_) => {
+                save_run_status(&executable_task, TaskRunOutcome::Succeeded).await;
                 task_idx += 1;
             }
             Err(TaskExecutionError::NonZeroExitCode(code)) => {
+                save_run_status(&executable_task, TaskRunOutcome::Failed { exit_code: code }).await;
                 if code == 127 {
                     command_not_found(&project, explicit_environment);
                 }
@@ -262,6 +294,126 @@ fn command_not_found<'p>(project: &'p Project, explicit_environment: Option<Envi
     }
 }
 
+/// Lets the user interactively select one of the available tasks and the
+/// environment to run it in. Returns `None` if there are no tasks or the user
+/// cancelled the selection.
+fn pick_task<'p>(
+    project: &'p Project,
+    explicit_environment: Option<Environment<'p>>,
+) -> miette::Result<Option<(TaskName, Environment<'p>)>> {
+    let environments = if let Some(explicit_environment) = explicit_environment {
+        vec![explicit_environment]
+    } else {
+        project
+            .environments()
+            .into_iter()
+            .filter(|env| verify_current_platform_has_required_virtual_packages(env).is_ok())
+            .collect_vec()
+    };
+
+    let candidates = environments
+        .iter()
+        .flat_map(|env| {
+            env.get_filtered_tasks()
+                .into_iter()
+                .sorted()
+                .map(move |name| (name, env.clone()))
+        })
+        .collect_vec();
+    if candidates.is_empty() {
+        eprintln!(
+            "{}No tasks are available in the project",
+            console::style(console::Emoji("⚠️ ", "")).yellow(),
+        );
+        return Ok(None);
+    }
+
+    // Only show the environment if there is more than one.
+    let show_environment = project.environments().len() > 1;
+    let rows = candidates
+        .iter()
+        .map(|(name, env)| {
+            let description = env
+                .task(name, Some(env.best_platform()))
+                .ok()
+                .and_then(|task| task.description())
+                .unwrap_or_default();
+            let status = TaskRunStatus::read(project, env.name(), name)
+                .map(|status| console::strip_ansi_codes(&status.to_string()).into_owned())
+                .unwrap_or_default();
+            let environment = if show_environment {
+                env.name().as_str().to_string()
+            } else {
+                String::new()
+            };
+            (name, environment, status, description)
+        })
+        .collect_vec();
+
+    // The items are plain text, the fuzzy search highlights the matches.
+    let name_width = rows
+        .iter()
+        .map(|(name, ..)| name.as_str().len())
+        .max()
+        .unwrap_or_default();
+    let environment_width = rows
+        .iter()
+        .map(|(_, environment, ..)| environment.len())
+        .max()
+        .unwrap_or_default();
+    let status_width = rows
+        .iter()
+        .map(|(_, _, status, _)| console::measure_text_width(status))
+        .max()
+        .unwrap_or_default();
+    let items = rows
+        .iter()
+        .map(|(name, environment, status, description)| {
+            format!(
+                "{}  {}  {}  {}",
+                console::pad_str(name.as_str(), name_width, Alignment::Left, None),
+                console::pad_str(environment, environment_width, Alignment::Left, None),
+                console::pad_str(status, status_width, Alignment::Left, None),
+                description
+            )
+            .trim_end()
+            .to_string()
+        })
+        .collect_vec();
+
+    let theme = ColorfulTheme {
+        active_item_style: console::Style::new().for_stderr().magenta(),
+        ..ColorfulTheme::default()
+    };
+    let selection = dialoguer::FuzzySelect::with_theme(&theme)
+        .with_prompt("Select a task to run")
+        .items(&items)
+        .default(0)
+        .max_length(15)
+        .report(false)
+        .interact_opt()
+        .into_diagnostic()?;
+    Ok(selection.map(|idx| candidates[idx].clone()))
+}
+
+/// Records how a run of the task ended so it can be shown when the user picks
+/// a task to run. Failing to do so doesn't fail the run.
+async fn save_run_status(task: &ExecutableTask<'_>, outcome: TaskRunOutcome) {
+    let Some(name) = &task.name else {
+        return;
+    };
+    let status = TaskRunStatus::new(outcome);
+    if let Err(err) = status
+        .write(task.project(), task.run_environment.name(), name)
+        .await
+    {
+        tracing::debug!(
+            "failed to save the run status of '{}': {err}",
+            name.as_str()
+        );
+    }
+}
+
 #[derive(Debug, Error, Diagnostic)]
 enum TaskExecutionError {
     #[error("the script exited with a non-zero exit code {0}")]
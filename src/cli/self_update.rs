@@ -1,10 +1,15 @@
-use std::io::{Seek, Write};
+use std::{
+    io::{Seek, Write},
+    str::FromStr,
+};
 
 use flate2::read::GzDecoder;
 use tar::Archive;
 
 use miette::{Context, IntoDiagnostic};
 use pixi_consts::consts;
+use pixi_manifest::UnsupportedPixiVersion;
+use rattler_conda_types::{version_spec::EqualityOperator, Version, VersionSpec};
 use reqwest::Client;
 use serde::Deserialize;
 
@@ -19,6 +24,10 @@ pub struct Args {
 #[derive(Debug, Deserialize)]
 struct GithubRelease {
     tag_name: String,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    draft: bool,
     assets: Vec<GithubReleaseAsset>,
 }
 
@@ -208,6 +217,106 @@ async fn retrieve_target_version(version: &Option<String>) -> miette::Result<Git
         .with_context(|| format!("Failed to parse the Release from github: {:#?}", body))
 }
 
+/// Offers interactive users to update pixi when the workspace requires another
+/// version of pixi. An exact requirement updates to that version, otherwise
+/// pixi is updated to the newest release that matches the requirement. No
+/// update is offered if there is no such release.
+///
+/// Returns the error to report, which is the original error unless pixi was
+/// updated.
+pub async fn offer_update(err: miette::Report) -> miette::Report {
+    let Some(unsupported) = err.downcast_ref::<UnsupportedPixiVersion>() else {
+        return err;
+    };
+    if !cfg!(feature = "self_update") || !console::Term::stderr().is_term() {
+        return err;
+    }
+
+    let version = match &unsupported.requirement {
+        VersionSpec::Exact(EqualityOperator::Equals, version) => Ok(Some(version.clone())),
+        requirement => newest_release_matching(requirement).await,
+    };
+    let version = match version {
+        Ok(Some(version)) => version.to_string(),
+        Ok(None) => {
+            tracing::warn!(
+                "there is no release of pixi that matches {}, update pixi manually",
+                unsupported.requirement
+            );
+            return err;
+        }
+        Err(fetch_err) => {
+            tracing::warn!("failed to find a release of pixi to update to: {fetch_err}");
+            return err;
+        }
+    };
+
+    let confirmed = dialoguer::Confirm::new()
+        .with_prompt(format!(
+            "The workspace requires pixi {}, do you want to update pixi to {version} now?",
+            unsupported.requirement
+        ))
+        .report(false)
+        .default(false)
+        .interact_opt()
+        .ok()
+        .flatten();
+    if confirmed != Some(true) {
+        return err;
+    }
+
+    match execute(Args {
+        version: Some(version),
+    })
+    .await
+    {
+        Ok(()) => {
+            miette::miette!("pixi has been updated, run the command again to use the new version")
+        }
+        Err(update_err) => {
+            tracing::warn!("failed to update pixi: {update_err}");
+            err
+        }
+    }
+}
+
+/// Returns the newest released version of pixi that matches `requirement`.
+/// Only the most recent releases are considered.
+async fn newest_release_matching(requirement: &VersionSpec) -> miette::Result<Option<Version>> {
+    let res = Client::new()
+        .get("https://api.github.com/repos/prefix-dev/pixi/releases?per_page=100")
+        .header("User-Agent", user_agent())
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .into_diagnostic()
+        .wrap_err("Failed to fetch the releases from github")?;
+    let releases: Vec<GithubRelease> = res
+        .json()
+        .await
+        .into_diagnostic()
+        .wrap_err("Failed to parse the releases from github")?;
+
+    Ok(newest_matching_version(
+        releases
+            .iter()
+            .filter(|release| !release.prerelease && !release.draft)
+            .map(|release| release.tag_name.as_str()),
+        requirement,
+    ))
+}
+
+/// Returns the newest version of the release tags that matches `requirement`.
+fn newest_matching_version<'a>(
+    tags: impl IntoIterator<Item = &'a str>,
+    requirement: &VersionSpec,
+) -> Option<Version> {
+    tags.into_iter()
+        .filter_map(|tag| Version::from_str(tag.trim_start_matches('v')).ok())
+        .filter(|version| requirement.matches(version))
+        .max()
+}
+
 fn pixi_binary_name() -> String {
     format!("pixi{}", std::env::consts::EXE_SUFFIX)
 }
@@ -218,3 +327,26 @@ pub async fn execute_stub(_: Args) -> miette::Result<()> {
         message.unwrap_or("This version of pixi was built without self-update support. Please use your package manager to update pixi.")
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use rattler_conda_types::ParseStrictness;
+
+    use super::*;
+
+    #[test]
+    fn test_newest_matching_version() {
+        let tags = ["v0.41.0", "v0.40.3", "v0.39.5", "v0.40.1", "not-a-version"];
+        let newest = |requirement: &str| {
+            newest_matching_version(
+                tags,
+                &VersionSpec::from_str(requirement, ParseStrictness::Lenient).unwrap(),
+            )
+            .map(|version| version.to_string())
+        };
+        assert_eq!(newest(">=0.40").as_deref(), Some("0.41.0"));
+        assert_eq!(newest(">=0.40,<0.41").as_deref(), Some("0.40.3"));
+        assert_eq!(newest("0.39.*").as_deref(), Some("0.39.5"));
+        assert_eq!(newest(">=1"), None);
+    }
+}
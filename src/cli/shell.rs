@@ -1,6 +1,7 @@
 use std::{collections::HashMap, io::Write};
 
 use clap::Parser;
+use itertools::Itertools;
 use miette::IntoDiagnostic;
 use rattler_conda_types::Platform;
 use rattler_shell::{
@@ -16,7 +17,7 @@ use crate::{
     Project, UpdateLockFileOptions,
 };
 use pixi_config::{ConfigCliActivation, ConfigCliPrompt};
-use pixi_manifest::EnvironmentName;
+use pixi_manifest::{EnvironmentName, HasFeaturesIter};
 #[cfg(target_family = "unix")]
 use pixi_pty::unix::PtySession;
 
@@ -33,6 +34,10 @@ pub struct Args {
     #[arg(long, short)]
     environment: Option<String>,
 
+    /// Interactively select the environment to activate in the shell
+    #[arg(long, short, conflicts_with = "environment")]
+    interactive: bool,
+
     #[clap(flatten)]
     prompt_config: ConfigCliPrompt,
 
@@ -55,6 +60,7 @@ fn ignore_ctrl_c() {
 fn start_powershell(
     pwsh: PowerShell,
     env: &HashMap<String, String>,
+    unset: &[String],
     prompt: String,
 ) -> miette::Result<Option<i32>> {
     // create a tempfile for activation
@@ -64,6 +70,9 @@ fn start_powershell(
         .into_diagnostic()?;
 
     let mut shell_script = ShellScript::new(pwsh.clone(), Platform::current());
+    for name in unset {
+        shell_script.unset_env_var(name).into_diagnostic()?;
+    }
     for (key, value) in env {
         shell_script.set_env_var(key, value).into_diagnostic()?;
     }
@@ -94,6 +103,7 @@ fn start_powershell(
 fn start_cmdexe(
     cmdexe: CmdExe,
     env: &HashMap<String, String>,
+    unset: &[String],
     prompt: String,
 ) -> miette::Result<Option<i32>> {
     // create a tempfile for activation
@@ -104,6 +114,9 @@ fn start_cmdexe(
 
     // TODO: Should we just execute the activation scripts directly for cmd.exe?
     let mut shell_script = ShellScript::new(cmdexe, Platform::current());
+    for name in unset {
+        shell_script.unset_env_var(name).into_diagnostic()?;
+    }
     for (key, value) in env {
         shell_script.set_env_var(key, value).into_diagnostic()?;
     }
@@ -129,11 +142,13 @@ fn start_cmdexe(
 /// - `shell`: The type of shell to start. Must implement the `Shell` and `Copy` traits.
 /// - `args`: A vector of arguments to pass to the shell.
 /// - `env`: A HashMap containing environment variables to set in the shell.
+/// - `unset`: The names of the environment variables to remove from the shell.
 #[cfg(target_family = "unix")]
 async fn start_unix_shell<T: Shell + Copy + 'static>(
     shell: T,
     args: Vec<&str>,
     env: &HashMap<String, String>,
+    unset: &[String],
     prompt: String,
 ) -> miette::Result<Option<i32>> {
     // create a tempfile for activation
@@ -145,6 +160,9 @@ async fn start_unix_shell<T: Shell + Copy + 'static>(
         .into_diagnostic()?;
 
     let mut shell_script = ShellScript::new(shell, Platform::current());
+    for name in unset {
+        shell_script.unset_env_var(name).into_diagnostic()?;
+    }
     for (key, value) in env {
         shell_script.set_env_var(key, value).into_diagnostic()?;
     }
@@ -184,9 +202,11 @@ async fn start_unix_shell<T: Shell + Copy + 'static>(
 /// # Arguments
 /// - `shell`: The Nushell (also contains executable location)
 /// - `env`: A HashMap containing environment variables to set in the shell.
+/// - `unset`: The names of the environment variables to remove from the shell.
 async fn start_nu_shell(
     shell: rattler_shell::shell::NuShell,
     env: &HashMap<String, String>,
+    unset: &[String],
     prompt: String,
 ) -> miette::Result<Option<i32>> {
     // create a tempfile for activation
@@ -198,6 +218,9 @@ async fn start_nu_shell(
         .into_diagnostic()?;
 
     let mut shell_script = ShellScript::new(shell, Platform::current());
+    for name in unset {
+        shell_script.unset_env_var(name).into_diagnostic()?;
+    }
     for (key, value) in env {
         if key == "PATH" {
             // split path with PATHSEP
@@ -225,6 +248,53 @@ async fn start_nu_shell(
     Ok(process.wait().into_diagnostic()?.code())
 }
 
+/// Lets the user interactively select the environment to activate. Returns
+/// `None` if the user cancelled the selection.
+fn pick_environment(project: &Project) -> miette::Result<Option<String>> {
+    let environments = project
+        .environments()
+        .into_iter()
+        .filter(|env| verify_current_platform_has_required_virtual_packages(env).is_ok())
+        .collect_vec();
+
+    let name_width = environments
+        .iter()
+        .map(|env| env.name().as_str().len())
+        .max()
+        .unwrap_or_default();
+    let items = environments
+        .iter()
+        .map(|env| {
+            format!(
+                "{}  {}",
+                console::pad_str(
+                    env.name().as_str(),
+                    name_width,
+                    console::Alignment::Left,
+                    None
+                ),
+                env.features()
+                    .map(|feature| feature.name.as_str())
+                    .join(", ")
+            )
+        })
+        .collect_vec();
+
+    let theme = dialoguer::theme::ColorfulTheme {
+        active_item_style: console::Style::new().for_stderr().magenta(),
+        ..dialoguer::theme::ColorfulTheme::default()
+    };
+    let selection = dialoguer::FuzzySelect::with_theme(&theme)
+        .with_prompt("Select an environment to activate")
+        .items(&items)
+        .default(0)
+        .max_length(15)
+        .report(false)
+        .interact_opt()
+        .into_diagnostic()?;
+    Ok(selection.map(|idx| environments[idx].name().as_str().to_string()))
+}
+
 pub async fn execute(args: Args) -> miette::Result<()> {
     let config = args
         .activation_config
@@ -234,7 +304,15 @@ pub async fn execute(args: Args) -> miette::Result<()> {
     let project = Project::load_or_else_discover(args.project_config.manifest_path.as_deref())?
         .with_cli_config(config);
 
-    let environment = project.environment_from_name_or_env_var(args.environment)?;
+    let environment_name = if args.interactive {
+        match pick_environment(&project)? {
+            Some(name) => Some(name),
+            None => return Ok(()),
+        }
+    } else {
+        args.environment
+    };
+    let environment = project.environment_from_name_or_env_var(environment_name)?;
 
     verify_current_platform_has_required_virtual_packages(&environment).into_diagnostic()?;
 
@@ -268,6 +346,10 @@ pub async fn execute(args: Args) -> miette::Result<()> {
 
     tracing::debug!("Pixi environment activation:\n{:?}", env);
 
+    // The shell inherits the variables of this process, so the variables that
+    // are unset by the activation have to be removed explicitly.
+    let unset = environment.unset_activation_env(Platform::current());
+
     // Start the shell as the last part of the activation script based on the default shell.
     let interactive_shell: ShellEnum = ShellEnum::from_parent_process()
         .or_else(ShellEnum::from_env)
@@ -291,9 +373,9 @@ pub async fn execute(args: Args) -> miette::Result<()> {
 
     #[cfg(target_family = "windows")]
     let res = match interactive_shell {
-        ShellEnum::NuShell(nushell) => start_nu_shell(nushell, env, prompt).await,
-        ShellEnum::PowerShell(pwsh) => start_powershell(pwsh, env, prompt),
-        ShellEnum::CmdExe(cmdexe) => start_cmdexe(cmdexe, env, prompt),
+        ShellEnum::NuShell(nushell) => start_nu_shell(nushell, env, &unset, prompt).await,
+        ShellEnum::PowerShell(pwsh) => start_powershell(pwsh, env, &unset, prompt),
+        ShellEnum::CmdExe(cmdexe) => start_cmdexe(cmdexe, env, &unset, prompt),
         _ => {
             miette::bail!("Unsupported shell: {:?}", interactive_shell);
         }
@@ -301,12 +383,14 @@ pub async fn execute(args: Args) -> miette::Result<()> {
 
     #[cfg(target_family = "unix")]
     let res = match interactive_shell {
-        ShellEnum::NuShell(nushell) => start_nu_shell(nushell, env, prompt).await,
-        ShellEnum::PowerShell(pwsh) => start_powershell(pwsh, env, prompt),
-        ShellEnum::Bash(bash) => start_unix_shell(bash, vec!["-l", "-i"], env, prompt).await,
-        ShellEnum::Zsh(zsh) => start_unix_shell(zsh, vec!["-l", "-i"], env, prompt).await,
-        ShellEnum::Fish(fish) => start_unix_shell(fish, vec![], env, prompt).await,
-        ShellEnum::Xonsh(xonsh) => start_unix_shell(xonsh, vec![], env, prompt).await,
+        ShellEnum::NuShell(nushell) => start_nu_shell(nushell, env, &unset, prompt).await,
+        ShellEnum::PowerShell(pwsh) => start_powershell(pwsh, env, &unset, prompt),
+        ShellEnum::Bash(bash) => {
+            start_unix_shell(bash, vec!["-l", "-i"], env, &unset, prompt).await
+        }
+        ShellEnum::Zsh(zsh) => start_unix_shell(zsh, vec!["-l", "-i"], env, &unset, prompt).await,
+        ShellEnum::Fish(fish) => start_unix_shell(fish, vec![], env, &unset, prompt).await,
+        ShellEnum::Xonsh(xonsh) => start_unix_shell(xonsh, vec![], env, &unset, prompt).await,
         _ => {
             miette::bail!("Unsupported shell: {:?}", interactive_shell)
         }
@@ -1,12 +1,18 @@
-use std::{collections::HashMap, default::Default};
+use std::{
+    collections::{BTreeMap, HashMap},
+    default::Default,
+    path::Path,
+};
 
 use clap::Parser;
-use miette::IntoDiagnostic;
-use pixi_config::{ConfigCliActivation, ConfigCliPrompt};
+use fancy_display::FancyDisplay;
+use miette::{Context, IntoDiagnostic};
+use pixi_config::{Config, ConfigCliActivation, ConfigCliPrompt};
+use rattler_conda_types::Platform;
 use rattler_lock::LockFile;
 use rattler_shell::{
     activation::{ActivationVariables, PathModificationBehavior},
-    shell::ShellEnum,
+    shell::{Shell, ShellEnum, ShellScript},
 };
 use serde::Serialize;
 use serde_json;
@@ -14,16 +20,29 @@ use serde_json;
 use crate::activation::CurrentEnvVarBehavior;
 use crate::environment::get_update_lock_file_and_prefix;
 use crate::{
-    activation::get_activator,
+    activation::{get_activator, EnvironmentDiff, DEACTIVATION_VAR},
     cli::cli_config::{PrefixUpdateConfig, ProjectConfig},
-    project::{Environment, HasProjectRef},
-    Project, UpdateLockFileOptions,
+    project::{find_project_manifest, Environment, HasProjectRef},
+    prompt, Project, UpdateLockFileOptions,
 };
 
+/// The manifest of the workspace that was activated by the `--auto` hook.
+const AUTO_MANIFEST_VAR: &str = "PIXI_AUTO_MANIFEST";
+
+/// The environment that was activated by the `--auto` hook.
+const AUTO_ENVIRONMENT_VAR: &str = "PIXI_AUTO_ENVIRONMENT";
+
 /// Print the pixi environment activation script.
 ///
 /// You can source the script to activate the environment without needing pixi
-/// itself.
+/// itself. The script records the previous values of all variables it changes,
+/// an environment that was activated before is deactivated first. Use
+/// `--deactivate` to print the script that restores these values.
+///
+/// With `--auto` a hook is printed instead that activates the environment
+/// whenever you enter the directory of a workspace and deactivates it again
+/// when you leave it. Add it to the configuration of your shell, e.g.
+/// `eval "$(pixi shell-hook --auto --shell bash)"` in `~/.bashrc`.
 #[derive(Parser, Debug)]
 pub struct Args {
     /// Sets the shell, options: [`bash`,  `zsh`,  `xonsh`,  `cmd`,
@@ -50,6 +69,79 @@ pub struct Args {
 
     #[clap(flatten)]
     prompt_config: ConfigCliPrompt,
+
+    /// Print a hook that automatically activates the environment of the
+    /// workspace in the current directory, supports `bash`, `zsh`, `fish`
+    /// and `nushell`
+    #[clap(long, conflicts_with = "json")]
+    auto: bool,
+
+    /// Print the changes to the shell environment for the current directory,
+    /// this is invoked by the hook of `--auto`
+    #[clap(long, hide = true)]
+    auto_switch: bool,
+
+    /// Print a script that deactivates the environment that was activated
+    /// by a previous `shell-hook`, restoring the previous values of all
+    /// variables it changed
+    #[clap(long, conflicts_with_all = ["json", "auto", "auto_switch"])]
+    deactivate: bool,
+}
+
+/// The changes to the variables of a shell.
+#[derive(Debug, Default, Serialize)]
+struct EnvironmentChanges {
+    set: BTreeMap<String, String>,
+    unset: Vec<String>,
+}
+
+impl EnvironmentChanges {
+    fn set(&mut self, name: &str, value: &str) {
+        self.unset.retain(|unset| unset != name);
+        self.set.insert(name.to_string(), value.to_string());
+    }
+
+    fn unset(&mut self, name: &str) {
+        self.set.remove(name);
+        if !self.unset.iter().any(|unset| unset == name) {
+            self.unset.push(name.to_string());
+        }
+    }
+
+    /// Restores the variables changed by an activation.
+    fn restore(&mut self, diff: &EnvironmentDiff) {
+        for (name, value) in diff.restore() {
+            match value {
+                Some(value) => self.set(name, value),
+                None => self.unset(name),
+            }
+        }
+        self.unset(DEACTIVATION_VAR);
+    }
+
+    /// Returns the value the variable has in the shell after the changes are
+    /// applied to the variables of this process.
+    fn var(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.set.get(name) {
+            return Some(value.clone());
+        }
+        if self.unset.iter().any(|unset| unset == name) {
+            return None;
+        }
+        std::env::var(name).ok()
+    }
+
+    /// Renders the changes as a script for the given shell.
+    fn to_script(&self, shell: ShellEnum) -> miette::Result<String> {
+        let mut script = ShellScript::new(shell, Platform::current());
+        for name in &self.unset {
+            script.unset_env_var(name).into_diagnostic()?;
+        }
+        for (name, value) in &self.set {
+            script.set_env_var(name, value).into_diagnostic()?;
+        }
+        script.contents().into_diagnostic()
+    }
 }
 
 #[derive(Serialize)]
@@ -57,36 +149,225 @@ struct ShellEnv<'a> {
     environment_variables: &'a HashMap<String, String>,
 }
 
-/// Generates the activation script.
-async fn generate_activation_script(
-    shell: Option<ShellEnum>,
-    environment: &Environment<'_>,
-) -> miette::Result<String> {
-    // Get shell from the arguments or from the current process or use default if
-    // all fails
-    let shell = shell.unwrap_or_else(|| {
+/// Get shell from the arguments or from the current process or use default if
+/// all fails
+fn resolve_shell(shell: Option<ShellEnum>) -> ShellEnum {
+    shell.unwrap_or_else(|| {
         ShellEnum::from_parent_process()
             .unwrap_or_else(|| ShellEnum::from_env().unwrap_or_default())
-    });
+    })
+}
+
+/// Generates the hook that automatically activates the environment of the
+/// workspace in the current directory.
+fn generate_auto_hook(
+    shell: Option<ShellEnum>,
+    environment: Option<&str>,
+) -> miette::Result<String> {
+    let shell = resolve_shell(shell);
+    let exe = std::env::current_exe().into_diagnostic()?;
+    let exe = exe.to_string_lossy().into_owned();
+
+    let mut switch_args = vec!["shell-hook", "--auto-switch"];
+    if let Some(environment) = environment {
+        switch_args.extend(["--environment", environment]);
+    }
+    let quote = |arg: &str| format!("'{}'", arg.replace('\'', "'\\''"));
+    let switch_command = |shell_name: &str| {
+        std::iter::once(exe.as_str())
+            .chain(switch_args.iter().copied())
+            .chain(["--shell", shell_name])
+            .map(quote)
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    Ok(match shell {
+        ShellEnum::Bash(_) => prompt::get_bash_auto_hook(&switch_command("bash")),
+        ShellEnum::Zsh(_) => prompt::get_zsh_auto_hook(&switch_command("zsh")),
+        ShellEnum::Fish(_) => prompt::get_fish_auto_hook(&switch_command("fish")),
+        ShellEnum::NuShell(_) => {
+            let args = switch_args
+                .iter()
+                .copied()
+                .chain(["--json"])
+                .map(quote)
+                .collect::<Vec<_>>()
+                .join(" ");
+            prompt::get_nu_auto_hook(&format!("^{} {}", quote(&exe), args))
+        }
+        shell => miette::bail!(
+            "automatic activation is not supported for {}, use `bash`, `zsh`, `fish` or `nushell`",
+            shell.executable()
+        ),
+    })
+}
 
-    let activator = get_activator(environment, shell).into_diagnostic()?;
+/// Determines the changes to the shell environment when the current directory
+/// changed. The environment that was activated before is deactivated by
+/// restoring the previous values of its variables, and the environment of the
+/// workspace in the current directory (if any) is activated.
+async fn auto_switch(
+    environment_name: Option<String>,
+    config: Config,
+) -> miette::Result<EnvironmentChanges> {
+    let mut changes = EnvironmentChanges::default();
+    let manifest_path = find_project_manifest(std::env::current_dir().into_diagnostic()?);
+
+    // Nothing changes while we stay within the same workspace.
+    let active_manifest = std::env::var(AUTO_MANIFEST_VAR).ok();
+    let active_environment = std::env::var(AUTO_ENVIRONMENT_VAR).ok();
+    if let (Some(manifest_path), Some(active_manifest)) = (&manifest_path, &active_manifest) {
+        if Path::new(active_manifest) == manifest_path
+            && (environment_name.is_none() || environment_name == active_environment)
+        {
+            return Ok(changes);
+        }
+    }
 
-    let path = std::env::var("PATH")
-        .ok()
-        .map(|p| std::env::split_paths(&p).collect::<Vec<_>>());
+    // Deactivate the previous environment.
+    if active_manifest.is_some() {
+        if let Some(diff) = EnvironmentDiff::from_env() {
+            changes.restore(&diff);
+        }
+        for name in [AUTO_MANIFEST_VAR, AUTO_ENVIRONMENT_VAR] {
+            changes.unset(name);
+        }
+    }
+
+    let Some(manifest_path) = manifest_path else {
+        return Ok(changes);
+    };
+    let project = Project::from_path(&manifest_path)?.with_cli_config(config);
+    let environment = project.environment_from_name_or_env_var(environment_name)?;
+    if !environment.dir().is_dir() {
+        tracing::warn!(
+            "the environment '{}' is not installed, run `pixi install` to activate it automatically",
+            environment.name().fancy_display()
+        );
+        return Ok(changes);
+    }
+
+    // Use the lock-file as is, so the activation cache can be used without
+    // updating anything.
+    let lock_file = project.get_lock_file().await.ok();
+    let variables = project
+        .get_activated_environment_variables(
+            &environment,
+            CurrentEnvVarBehavior::Exclude,
+            lock_file.as_ref(),
+            project.config().force_activate(),
+            project.config().experimental_activation_cache_usage(),
+        )
+        .await?;
+
+    let unset = environment.unset_activation_env(Platform::current());
+    // The previous values are the values after the deactivation above.
+    let diff = EnvironmentDiff::from_activation(variables, |name| changes.var(name))
+        .with_unset(&unset, |name| changes.var(name));
+    for (name, value) in variables {
+        changes.set(name, value);
+    }
+    for name in &unset {
+        changes.unset(name);
+    }
+    changes.set(AUTO_MANIFEST_VAR, &manifest_path.to_string_lossy());
+    changes.set(AUTO_ENVIRONMENT_VAR, environment.name().as_str());
+    changes.set(DEACTIVATION_VAR, &diff.encode());
+
+    Ok(changes)
+}
+
+/// Returns the variables of the shell that the activation starts from, `var`
+/// looks up the value of a variable in the shell.
+fn activation_variables(var: impl Fn(&str) -> Option<String>) -> ActivationVariables {
+    let path = var("PATH").map(|p| std::env::split_paths(&p).collect::<Vec<_>>());
 
     // If we are in a conda environment, we need to deactivate it before activating
     // the host / build prefix
-    let conda_prefix = std::env::var("CONDA_PREFIX").ok().map(|p| p.into());
+    let conda_prefix = var("CONDA_PREFIX").map(|p| p.into());
+    ActivationVariables {
+        conda_prefix,
+        path,
+        path_modification_behavior: PathModificationBehavior::default(),
+    }
+}
+
+/// Generates the activation script.
+async fn generate_activation_script(
+    shell: Option<ShellEnum>,
+    environment: &Environment<'_>,
+) -> miette::Result<String> {
+    let shell = resolve_shell(shell);
+
+    let activator =
+        get_activator(environment, shell.clone(), &CurrentEnvVarBehavior::Include).await?;
     let result = activator
-        .activation(ActivationVariables {
-            conda_prefix,
-            path,
-            path_modification_behavior: PathModificationBehavior::default(),
-        })
+        .activation(activation_variables(|name| std::env::var(name).ok()))
         .into_diagnostic()?;
 
-    result.script.contents().into_diagnostic()
+    let mut script = result.script.contents().into_diagnostic()?;
+    script.push_str(&unset_script(shell, environment)?);
+    Ok(script)
+}
+
+/// Generates the script that removes the variables that are unset by the
+/// activation, the activation script itself only sets them to an empty value.
+fn unset_script(shell: ShellEnum, environment: &Environment<'_>) -> miette::Result<String> {
+    let mut changes = EnvironmentChanges::default();
+    for name in environment.unset_activation_env(Platform::current()) {
+        changes.unset(&name);
+    }
+    changes.to_script(shell)
+}
+
+/// Generates the activation script for an interactive shell. An environment
+/// that was activated before is deactivated first and the previous values of
+/// the variables changed by the activation are recorded, so the environment can
+/// be deactivated again with `--deactivate`.
+///
+/// The activation is run once and the script sets the variables it changed,
+/// so the activation scripts of the packages don't run a second time in the
+/// shell.
+async fn generate_shell_script(
+    shell: Option<ShellEnum>,
+    environment: &Environment<'_>,
+) -> miette::Result<String> {
+    let shell = resolve_shell(shell);
+
+    let mut changes = EnvironmentChanges::default();
+    if let Some(diff) = EnvironmentDiff::from_env() {
+        changes.restore(&diff);
+    }
+
+    let activator =
+        get_activator(environment, shell.clone(), &CurrentEnvVarBehavior::Include).await?;
+    let start = activation_variables(|name| changes.var(name));
+    let variables = tokio::task::spawn_blocking(move || activator.run_activation(start, None))
+        .await
+        .into_diagnostic()?
+        .into_diagnostic()
+        .wrap_err_with(|| {
+            format!(
+                "failed to run the activation of {}",
+                environment.name().fancy_display()
+            )
+        })?;
+
+    let unset = environment.unset_activation_env(Platform::current());
+    let diff = EnvironmentDiff::from_activation(&variables, |name| changes.var(name))
+        .with_unset(&unset, |name| changes.var(name));
+    for (name, value) in &variables {
+        if changes.var(name).as_ref() != Some(value) {
+            changes.set(name, value);
+        }
+    }
+    for name in &unset {
+        changes.unset(name);
+    }
+    changes.set(DEACTIVATION_VAR, &diff.encode());
+
+    changes.to_script(shell)
 }
 
 /// Generates a JSON object describing the changes to the shell environment when
@@ -121,6 +402,33 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         .prompt_config
         .merge_config(args.activation_config.into())
         .merge_config(args.prefix_update_config.config.clone().into());
+
+    if args.auto {
+        println!(
+            "{}",
+            generate_auto_hook(args.shell, args.environment.as_deref())?
+        );
+        return Ok(());
+    }
+    if args.deactivate {
+        let Some(diff) = EnvironmentDiff::from_env() else {
+            miette::bail!("no environment was activated by `pixi shell-hook`");
+        };
+        let mut changes = EnvironmentChanges::default();
+        changes.restore(&diff);
+        println!("{}", changes.to_script(resolve_shell(args.shell))?);
+        return Ok(());
+    }
+    if args.auto_switch {
+        let changes = auto_switch(args.environment, config).await?;
+        let output = match args.json {
+            true => serde_json::to_string(&changes).into_diagnostic()?,
+            false => changes.to_script(resolve_shell(args.shell))?,
+        };
+        println!("{}", output);
+        return Ok(());
+    }
+
     let project = Project::load_or_else_discover(args.project_config.manifest_path.as_deref())?
         .with_cli_config(config);
     let environment = project.environment_from_name_or_env_var(args.environment)?;
@@ -148,7 +456,7 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         }
         // Skipping the activated environment caching for the script.
         // As it can still run scripts.
-        false => generate_activation_script(args.shell, &environment).await?,
+        false => generate_shell_script(args.shell, &environment).await?,
     };
 
     // Print the output - either a JSON object or a shell script
@@ -215,4 +523,91 @@ mod tests {
         assert!(script.contains(&format!("$env.{path_var_name} = ")));
         assert!(script.contains("$env.CONDA_PREFIX = "));
     }
+
+    #[test]
+    fn test_environment_changes() {
+        let mut changes = EnvironmentChanges::default();
+        changes.unset("FOO");
+        changes.set("FOO", "bar");
+        changes.set("BAZ", "qux");
+        changes.unset("BAZ");
+        assert_eq!(changes.set.get("FOO").map(String::as_str), Some("bar"));
+        assert_eq!(changes.unset, vec!["BAZ".to_string()]);
+
+        let script = changes.to_script(ShellEnum::Bash(Bash)).unwrap();
+        assert!(script.contains("export FOO="));
+        assert!(script.contains("unset BAZ"));
+    }
+
+    #[test]
+    fn test_unset_script() {
+        let project = Project::from_str(
+            Path::new("pixi.toml"),
+            r#"
+        [project]
+        name = "pixi"
+        channels = []
+        platforms = []
+
+        [activation.env]
+        PIXI_TEST_UNSET = { unset = true }
+        "#,
+        )
+        .unwrap();
+        let script = unset_script(ShellEnum::Bash(Bash), &project.default_environment()).unwrap();
+        assert!(script.contains("unset PIXI_TEST_UNSET"));
+    }
+
+    #[test]
+    fn test_restore() {
+        let current = HashMap::from([(
+            "PIXI_TEST_RESTORE_CHANGED".to_string(),
+            "before".to_string(),
+        )]);
+        let activated = HashMap::from([
+            ("PIXI_TEST_RESTORE_CHANGED".to_string(), "after".to_string()),
+            ("PIXI_TEST_RESTORE_ADDED".to_string(), "after".to_string()),
+        ]);
+        let diff = EnvironmentDiff::from_activation(&activated, |name| current.get(name).cloned());
+
+        let mut changes = EnvironmentChanges::default();
+        changes.restore(&diff);
+        assert_eq!(
+            changes
+                .set
+                .get("PIXI_TEST_RESTORE_CHANGED")
+                .map(String::as_str),
+            Some("before")
+        );
+        assert!(changes
+            .unset
+            .contains(&"PIXI_TEST_RESTORE_ADDED".to_string()));
+
+        // The restored values are only visible through the changes, the
+        // variables of this process are left alone.
+        assert_eq!(
+            changes.var("PIXI_TEST_RESTORE_CHANGED").as_deref(),
+            Some("before")
+        );
+        assert_eq!(changes.var("PIXI_TEST_RESTORE_ADDED"), None);
+        assert!(std::env::var_os("PIXI_TEST_RESTORE_CHANGED").is_none());
+
+        let script = changes.to_script(ShellEnum::Bash(Bash)).unwrap();
+        assert!(script.contains("export PIXI_TEST_RESTORE_CHANGED="));
+        assert!(script.contains("unset PIXI_TEST_RESTORE_ADDED"));
+        assert!(script.contains(&format!("unset {DEACTIVATION_VAR}")));
+    }
+
+    #[test]
+    fn test_auto_hook() {
+        let hook = generate_auto_hook(Some(ShellEnum::Bash(Bash)), Some("test")).unwrap();
+        assert!(hook.contains("PROMPT_COMMAND"));
+        assert!(hook.contains("'--auto-switch' '--environment' 'test' '--shell' 'bash'"));
+        assert!(!hook.contains("__PIXI_AUTO_SWITCH__"));
+
+        let hook = generate_auto_hook(Some(ShellEnum::NuShell(NuShell)), None).unwrap();
+        assert!(hook.contains("'--auto-switch' '--json'"));
+
+        assert!(generate_auto_hook(Some(ShellEnum::CmdExe(CmdExe)), None).is_err());
+    }
 }
@@ -52,6 +52,16 @@ use xxhash_rust::xxh3::Xxh3;
 /// path does not align with the defined prefix. Returns false when the file is
 /// not present.
 pub async fn verify_prefix_location_unchanged(environment_dir: &Path) -> miette::Result<()> {
+    match previous_prefix_location(environment_dir).into_diagnostic()? {
+        None => Ok(()),
+        Some(previous_dir) => prefix_location_changed(environment_dir, &previous_dir).await,
+    }
+}
+
+/// Returns the location the prefix was created at if the prefix has since
+/// been moved to `environment_dir`, or `None` if it is still in the same place
+/// or there is no record of its location.
+pub(crate) fn previous_prefix_location(environment_dir: &Path) -> io::Result<Option<PathBuf>> {
     let prefix_file = environment_dir
         .join("conda-meta")
         .join(consts::PREFIX_FILE_NAME);
@@ -63,17 +73,17 @@ pub async fn verify_prefix_location_unchanged(environment_dir: &Path) -> miette:
 
     match std::fs::read_to_string(prefix_file.clone()) {
         // Not found is fine as it can be new or backwards compatible.
-        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
         // Scream the error if we don't know it.
         Err(e) => {
             tracing::error!("failed to read prefix file: {}", prefix_file.display());
-            Err(e).into_diagnostic()
+            Err(e)
         }
         // Check if the path in the file aligns with the current path.
-        Ok(p) if prefix_file.starts_with(&p) => Ok(()),
+        Ok(p) if prefix_file.starts_with(&p) => Ok(None),
         Ok(p) => {
             let path = Path::new(&p);
-            prefix_location_changed(environment_dir, path.parent().unwrap_or(path)).await
+            Ok(Some(path.parent().unwrap_or(path).to_path_buf()))
         }
     }
 }
@@ -10,7 +10,7 @@ use crate::{
 use indexmap::IndexSet;
 use itertools::Itertools;
 use miette::IntoDiagnostic;
-use pixi_utils::{executable_from_path, is_binary_folder};
+use pixi_utils::{executable_from_path, is_binary_folder, PrefixActivation};
 use rattler_conda_types::{
     MatchSpec, Matches, PackageName, ParseStrictness, Platform, RepoDataRecord,
 };
@@ -102,6 +102,17 @@ pub(crate) async fn create_executable_trampolines(
 
     let activation_variables = prefix.run_activation().await?;
 
+    // Cache the activation in the prefix, the trampolines use it when the
+    // activation they recorded is stale.
+    if let Err(err) =
+        PrefixActivation::new(prefix.root(), activation_variables.clone()).write(prefix.root())
+    {
+        tracing::debug!(
+            "failed to cache the activation of {}: {err}",
+            prefix.root().display()
+        );
+    }
+
     for ScriptExecMapping {
         global_script_path,
         original_executable,
@@ -117,7 +128,8 @@ pub(crate) async fn create_executable_trampolines(
                     original_executable.display()
                 )
             })?);
-        let metadata = Configuration::new(exe, path, Some(activation_variables.clone()));
+        let metadata = Configuration::new(exe, path, Some(activation_variables.clone()))
+            .with_prefix(prefix.root());
 
         let parent_dir = global_script_path.parent().ok_or_else(|| {
             miette::miette!(
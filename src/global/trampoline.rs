@@ -24,9 +24,10 @@ use std::{
     sync::LazyLock,
 };
 
+use is_executable::IsExecutable;
 use miette::IntoDiagnostic;
 use once_cell::sync::Lazy;
-use pixi_utils::executable_from_path;
+use pixi_utils::{activation_hash, executable_from_path, PrefixActivation};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
@@ -149,6 +150,14 @@ pub struct Configuration {
     pub path: PathBuf,
     /// Environment variables to be set before executing the original executable.
     pub env: HashMap<String, String>,
+    /// Prefix of the environment the executable belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<PathBuf>,
+    /// Activation hash of the prefix at the time `env` was computed. If the
+    /// hash of the prefix changes, the trampoline uses the activation that pixi
+    /// cached in the prefix instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_hash: Option<String>,
 }
 
 impl Configuration {
@@ -158,6 +167,17 @@ impl Configuration {
             exe,
             path,
             env: env.unwrap_or_default(),
+            prefix: None,
+            env_hash: None,
+        }
+    }
+
+    /// Records the prefix of the environment and its current activation hash.
+    pub fn with_prefix(self, prefix: &Path) -> Self {
+        Self {
+            prefix: Some(prefix.to_path_buf()),
+            env_hash: Some(activation_hash(prefix)),
+            ..self
         }
     }
 
@@ -440,6 +460,189 @@ impl Trampoline {
     }
 }
 
+/// A problem with an exposed executable in the bin directory, as reported by
+/// `pixi global doctor`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrampolineIssue {
+    /// The configuration of the trampoline can't be read.
+    InvalidConfiguration(String),
+    /// The trampoline binary was installed by an older version of pixi.
+    OutdatedBinary,
+    /// The executable the trampoline points to doesn't exist.
+    MissingExecutable(PathBuf),
+    /// The prefix of the environment doesn't exist.
+    MissingPrefix(PathBuf),
+    /// The configuration doesn't record the prefix and its activation hash,
+    /// it was written by an older version of pixi. The trampoline still
+    /// works but doesn't detect changes of the activation.
+    MissingHash,
+    /// The activation of the prefix changed since the configuration was
+    /// written and pixi didn't cache the new activation of the prefix, so the
+    /// trampoline keeps using the recorded one.
+    StaleActivation,
+    /// A configuration without a trampoline.
+    OrphanedConfiguration,
+    /// An executable script from an older version of pixi.
+    Script,
+}
+
+impl TrampolineIssue {
+    /// Returns true if the issue requires a `pixi global sync` to be fixed.
+    pub fn is_error(&self) -> bool {
+        !matches!(
+            self,
+            TrampolineIssue::StaleActivation | TrampolineIssue::MissingHash
+        )
+    }
+}
+
+impl std::fmt::Display for TrampolineIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrampolineIssue::InvalidConfiguration(err) => {
+                write!(f, "the configuration can't be read: {err}")
+            }
+            TrampolineIssue::OutdatedBinary => {
+                write!(f, "the trampoline was installed by another version of pixi")
+            }
+            TrampolineIssue::MissingExecutable(exe) => {
+                write!(f, "the executable '{}' doesn't exist", exe.display())
+            }
+            TrampolineIssue::MissingPrefix(prefix) => {
+                write!(f, "the environment '{}' doesn't exist", prefix.display())
+            }
+            TrampolineIssue::MissingHash => write!(
+                f,
+                "the configuration doesn't record the environment, so changes of its activation are not detected"
+            ),
+            TrampolineIssue::StaleActivation => write!(
+                f,
+                "the activation of the environment changed, run `pixi global update` to recompute it"
+            ),
+            TrampolineIssue::OrphanedConfiguration => {
+                write!(f, "the configuration doesn't belong to a trampoline")
+            }
+            TrampolineIssue::Script => {
+                write!(f, "the executable is a script instead of a trampoline")
+            }
+        }
+    }
+}
+
+impl Configuration {
+    /// Validates the executable and environment of the configuration.
+    pub fn diagnose(&self) -> Vec<TrampolineIssue> {
+        let mut issues = Vec::new();
+        if !self.exe.is_file() {
+            issues.push(TrampolineIssue::MissingExecutable(self.exe.clone()));
+        }
+        match (&self.prefix, &self.env_hash) {
+            (Some(prefix), _) if !prefix.is_dir() => {
+                issues.push(TrampolineIssue::MissingPrefix(prefix.clone()))
+            }
+            (Some(prefix), Some(env_hash)) => {
+                // The trampoline uses the activation cached in the prefix when
+                // its own is stale.
+                if activation_hash(prefix) != *env_hash && PrefixActivation::read(prefix).is_none()
+                {
+                    issues.push(TrampolineIssue::StaleActivation)
+                }
+            }
+            _ => issues.push(TrampolineIssue::MissingHash),
+        }
+        issues
+    }
+}
+
+/// Validates all exposed executables and trampoline configurations in the bin
+/// directory.
+pub(crate) async fn diagnose_bin_dir(
+    bin_dir: &Path,
+) -> miette::Result<Vec<(PathBuf, TrampolineIssue)>> {
+    let mut issues = Vec::new();
+
+    let mut entries = tokio_fs::read_dir(bin_dir).await.into_diagnostic()?;
+    let mut paths = Vec::new();
+    while let Some(entry) = entries.next_entry().await.into_diagnostic()? {
+        let path = entry.path();
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    for path in paths {
+        let configuration = Trampoline::name(&path)
+            .ok()
+            .map(|name| Configuration::path_from_trampoline(bin_dir, &name))
+            .filter(|configuration| configuration.is_file());
+
+        let is_trampoline = Trampoline::is_trampoline(&path).await?;
+        if !is_trampoline && configuration.is_none() {
+            if path.is_executable() && !super::common::is_binary(&path)? {
+                issues.push((path, TrampolineIssue::Script));
+            }
+            continue;
+        }
+        if !is_trampoline {
+            issues.push((path.clone(), TrampolineIssue::OutdatedBinary));
+        }
+
+        let Some(configuration) = configuration else {
+            issues.push((
+                path,
+                TrampolineIssue::InvalidConfiguration(String::from("the file doesn't exist")),
+            ));
+            continue;
+        };
+        match tokio_fs::read_to_string(&configuration)
+            .await
+            .into_diagnostic()
+            .and_then(|contents| serde_json::from_str::<Configuration>(&contents).into_diagnostic())
+        {
+            Ok(configuration) => issues.extend(
+                configuration
+                    .diagnose()
+                    .into_iter()
+                    .map(|issue| (path.clone(), issue)),
+            ),
+            Err(err) => issues.push((path, TrampolineIssue::InvalidConfiguration(err.to_string()))),
+        }
+    }
+
+    // Configurations of removed trampolines
+    let configuration_dir = bin_dir.join(TRAMPOLINE_CONFIGURATION);
+    if configuration_dir.is_dir() {
+        let mut entries = tokio_fs::read_dir(&configuration_dir)
+            .await
+            .into_diagnostic()?;
+        let mut orphaned = Vec::new();
+        while let Some(entry) = entries.next_entry().await.into_diagnostic()? {
+            let path = entry.path();
+            let Some(name) = path
+                .extension()
+                .is_some_and(|ext| ext == "json")
+                .then(|| path.file_stem())
+                .flatten()
+                .and_then(|stem| ExposedName::from_str(&stem.to_string_lossy()).ok())
+            else {
+                continue;
+            };
+            if !bin_dir.join(file_name(&name)).exists() {
+                orphaned.push(path);
+            }
+        }
+        orphaned.sort();
+        issues.extend(
+            orphaned
+                .into_iter()
+                .map(|path| (path, TrampolineIssue::OrphanedConfiguration)),
+        );
+    }
+
+    Ok(issues)
+}
+
 mod tests {
     // Test is_trampoline when it is a trampoline
     #[tokio::test]
@@ -517,4 +720,82 @@ mod tests {
         // Check if the metadata is the same
         assert_eq!(shared_metadata.len(), linked_metadata.len());
     }
+
+    #[tokio::test]
+    async fn test_diagnose_bin_dir() {
+        use super::*;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let bin_dir = dir.path().join("bin");
+        let prefix = dir.path().join("envs").join("test");
+        tokio_fs::create_dir_all(prefix.join("bin")).await.unwrap();
+        let exe = prefix.join("bin").join("healthy");
+        tokio_fs::write(&exe, "").await.unwrap();
+
+        // A healthy trampoline
+        let healthy = Trampoline::new(
+            ExposedName::from_str("healthy").unwrap(),
+            bin_dir.clone(),
+            Configuration::new(exe.clone(), prefix.join("bin"), None).with_prefix(&prefix),
+        );
+        healthy.save().await.unwrap();
+
+        // A trampoline pointing to a removed executable, without a hash
+        let broken = Trampoline::new(
+            ExposedName::from_str("broken").unwrap(),
+            bin_dir.clone(),
+            Configuration::new(prefix.join("bin").join("removed"), prefix.join("bin"), None),
+        );
+        broken.save().await.unwrap();
+
+        // A configuration without a trampoline
+        tokio_fs::write(
+            Configuration::path_from_trampoline(
+                &bin_dir,
+                &ExposedName::from_str("orphan").unwrap(),
+            ),
+            "{}",
+        )
+        .await
+        .unwrap();
+
+        let issues = diagnose_bin_dir(&bin_dir).await.unwrap();
+        assert_eq!(
+            issues,
+            vec![
+                (
+                    broken.path(),
+                    TrampolineIssue::MissingExecutable(prefix.join("bin").join("removed"))
+                ),
+                (broken.path(), TrampolineIssue::MissingHash),
+                (
+                    bin_dir.join(TRAMPOLINE_CONFIGURATION).join("orphan.json"),
+                    TrampolineIssue::OrphanedConfiguration
+                ),
+            ]
+        );
+
+        // Changing the activation of the environment makes the configuration stale
+        tokio_fs::create_dir_all(prefix.join("etc/conda/activate.d"))
+            .await
+            .unwrap();
+        tokio_fs::write(prefix.join("etc/conda/activate.d/env.sh"), "export A=1")
+            .await
+            .unwrap();
+        let issues = diagnose_bin_dir(&bin_dir).await.unwrap();
+        assert!(issues.contains(&(healthy.path(), TrampolineIssue::StaleActivation)));
+        assert!(!TrampolineIssue::StaleActivation.is_error());
+
+        // Unless pixi cached the new activation of the environment
+        tokio_fs::create_dir_all(prefix.join("conda-meta"))
+            .await
+            .unwrap();
+        PrefixActivation::new(&prefix, HashMap::new())
+            .write(&prefix)
+            .unwrap();
+        let issues = diagnose_bin_dir(&bin_dir).await.unwrap();
+        assert!(!issues.contains(&(healthy.path(), TrampolineIssue::StaleActivation)));
+        assert!(!TrampolineIssue::MissingHash.is_error());
+    }
 }
@@ -1,4 +1,5 @@
 pub mod activation;
+mod audit;
 pub mod cli;
 mod diff;
 pub mod environment;
@@ -0,0 +1,525 @@
+use std::collections::BTreeSet;
+
+use indexmap::IndexSet;
+use itertools::Itertools;
+use rattler_conda_types::Platform;
+use rattler_lock::{LockFile, LockFileBuilder, LockedPackageRef};
+
+/// A single environment/platform combination in a lock-file.
+pub type LockedTarget = (String, Platform);
+
+/// The result of a three-way merge of lock-files.
+pub struct MergedLockFile {
+    /// The merged lock-file. Targets that could not be merged are not part of
+    /// this lock-file.
+    pub lock_file: LockFile,
+
+    /// The targets that were modified on both sides in incompatible ways.
+    pub conflicts: IndexSet<LockedTarget>,
+}
+
+impl MergedLockFile {
+    /// Returns true if the lock-files were merged without any conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// The outcome of merging a single value from three sides.
+enum Resolution<T> {
+    Take(T),
+    Conflict,
+}
+
+/// Performs a standard three-way merge of a single value: if only one side
+/// changed compared to `base` that side wins, if both sides made the same
+/// change that change wins, otherwise the value is in conflict.
+fn merge_three_way<T: PartialEq>(
+    base: Option<T>,
+    ours: Option<T>,
+    theirs: Option<T>,
+) -> Resolution<Option<T>> {
+    if ours == theirs || theirs == base {
+        Resolution::Take(ours)
+    } else if ours == base {
+        Resolution::Take(theirs)
+    } else {
+        Resolution::Conflict
+    }
+}
+
+/// Returns a stable identity for every package of a single target. Two
+/// targets with the same identities lock exactly the same packages.
+fn locked_package_set(
+    lock_file: &LockFile,
+    environment: &str,
+    platform: Platform,
+) -> Option<BTreeSet<String>> {
+    let packages = lock_file.environment(environment)?.packages(platform)?;
+    Some(
+        packages
+            .map(|package| match package {
+                LockedPackageRef::Conda(conda) => conda.location().to_string(),
+                LockedPackageRef::Pypi(pypi, env) => format!(
+                    "{}[{}]",
+                    pypi.location,
+                    env.extras.iter().map(|extra| extra.as_ref()).format(",")
+                ),
+            })
+            .collect(),
+    )
+}
+
+/// Merges the lock-files `ours` and `theirs` that both originate from `base`.
+///
+/// The merge is performed per environment and platform. If a target was only
+/// modified on one side the modified version is used. If both sides modified
+/// the same target in different ways the target is reported as a conflict and
+/// left out of the merged lock-file so it can be re-solved. An environment that
+/// was removed on one side and modified on the other side is in conflict as
+/// well.
+pub fn merge_lock_files(base: &LockFile, ours: &LockFile, theirs: &LockFile) -> MergedLockFile {
+    let mut builder = LockFileBuilder::new();
+    let mut conflicts = IndexSet::new();
+
+    let environment_names = ours
+        .environments()
+        .chain(theirs.environments())
+        .chain(base.environments())
+        .map(|(name, _)| name.to_string())
+        .unique()
+        .collect_vec();
+
+    for environment_name in environment_names {
+        let base_env = base.environment(&environment_name);
+        let ours_env = ours.environment(&environment_name);
+        let theirs_env = theirs.environment(&environment_name);
+
+        let platforms = [&ours_env, &theirs_env, &base_env]
+            .into_iter()
+            .flatten()
+            .flat_map(|env| env.platforms())
+            .collect::<IndexSet<_>>();
+
+        // The channels and indexes are shared by all platforms of an environment. If
+        // they conflict, none of the locked content of the environment can be trusted.
+        let channels = merge_three_way(
+            base_env.as_ref().map(|env| env.channels().to_vec()),
+            ours_env.as_ref().map(|env| env.channels().to_vec()),
+            theirs_env.as_ref().map(|env| env.channels().to_vec()),
+        );
+        let indexes = merge_three_way(
+            base_env.as_ref().map(|env| env.pypi_indexes().cloned()),
+            ours_env.as_ref().map(|env| env.pypi_indexes().cloned()),
+            theirs_env.as_ref().map(|env| env.pypi_indexes().cloned()),
+        );
+        let (channels, indexes) = match (channels, indexes) {
+            (Resolution::Take(channels), Resolution::Take(indexes)) => (channels, indexes),
+            _ => {
+                conflicts.extend(
+                    platforms
+                        .into_iter()
+                        .map(|platform| (environment_name.clone(), platform)),
+                );
+                continue;
+            }
+        };
+
+        // The environment was removed on one side. If the other side modified it,
+        // the removal and the modification are in conflict.
+        let Some(channels) = channels else {
+            let is_modified = |lock_file: &LockFile| {
+                let Some(env) = lock_file.environment(&environment_name) else {
+                    return false;
+                };
+                env.pypi_indexes().cloned()
+                    != base_env
+                        .as_ref()
+                        .and_then(|env| env.pypi_indexes().cloned())
+                    || platforms.iter().any(|&platform| {
+                        locked_package_set(lock_file, &environment_name, platform)
+                            != locked_package_set(base, &environment_name, platform)
+                    })
+            };
+            if base_env.is_some() && (is_modified(ours) || is_modified(theirs)) {
+                conflicts.extend(
+                    platforms
+                        .into_iter()
+                        .map(|platform| (environment_name.clone(), platform)),
+                );
+            }
+            continue;
+        };
+        builder.set_channels(&environment_name, channels);
+        if let Some(indexes) = indexes.flatten() {
+            builder.set_pypi_indexes(&environment_name, indexes);
+        }
+
+        for platform in platforms {
+            let ours_packages = locked_package_set(ours, &environment_name, platform);
+            let resolution = merge_three_way(
+                locked_package_set(base, &environment_name, platform),
+                ours_packages.clone(),
+                locked_package_set(theirs, &environment_name, platform),
+            );
+
+            let source = match resolution {
+                Resolution::Take(None) => continue,
+                Resolution::Take(packages) if packages == ours_packages => ours,
+                Resolution::Take(_) => theirs,
+                Resolution::Conflict => {
+                    conflicts.insert((environment_name.clone(), platform));
+                    continue;
+                }
+            };
+
+            let packages = source
+                .environment(&environment_name)
+                .and_then(|env| env.packages(platform))
+                .into_iter()
+                .flatten();
+            for package in packages {
+                builder.add_package(&environment_name, platform, package.into());
+            }
+        }
+    }
+
+    MergedLockFile {
+        lock_file: builder.finish(),
+        conflicts,
+    }
+}
+
+/// Returns the targets that are not replaced but belong to an environment whose
+/// channels or indexes differ between `original` and `updated`. These targets
+/// are kept as they were by [`replace_targets`], so their packages are still
+/// locked with the previous channels or indexes until they are re-solved.
+pub fn stale_targets(
+    original: &LockFile,
+    updated: &LockFile,
+    targets: &IndexSet<LockedTarget>,
+) -> IndexSet<LockedTarget> {
+    let mut stale = IndexSet::new();
+    for (environment_name, original_env) in original.environments() {
+        let is_replaced = targets.iter().any(|(env, _)| env == environment_name);
+        let Some(updated_env) = updated.environment(environment_name) else {
+            continue;
+        };
+        if !is_replaced
+            || (original_env.channels() == updated_env.channels()
+                && original_env.pypi_indexes() == updated_env.pypi_indexes())
+        {
+            continue;
+        }
+        stale.extend(
+            original_env
+                .platforms()
+                .map(|platform| (environment_name.to_string(), platform))
+                .filter(|target| !targets.contains(target)),
+        );
+    }
+    stale
+}
+
+/// Copies the packages of the given `targets` from `updated` into `original`.
+///
+/// All other targets are taken from `original` unmodified, so their packages
+/// serialize exactly as before, even the [`stale_targets`] of an environment
+/// whose channels changed. The channels and indexes of the environments of the
+/// targets are taken from `updated`. Targets that don't exist in `updated` are
+/// removed.
+pub fn replace_targets(
+    original: &LockFile,
+    updated: &LockFile,
+    targets: &IndexSet<LockedTarget>,
+) -> LockFile {
+    let mut builder = LockFileBuilder::new();
+
+    let environment_names = original
+        .environments()
+        .chain(updated.environments())
+        .map(|(name, _)| name.to_string())
+        .unique()
+        .collect_vec();
+
+    for environment_name in environment_names {
+        // The channels and indexes are shared by all platforms of an environment, use
+        // the updated ones if any of its platforms is replaced.
+        let is_replaced = targets.iter().any(|(env, _)| env == &environment_name);
+        let metadata_source = if is_replaced { updated } else { original };
+        let Some(metadata_env) = metadata_source.environment(&environment_name) else {
+            continue;
+        };
+
+        builder.set_channels(&environment_name, metadata_env.channels().to_vec());
+        if let Some(indexes) = metadata_env.pypi_indexes() {
+            builder.set_pypi_indexes(&environment_name, indexes.clone());
+        }
+
+        let platforms = [original, updated]
+            .into_iter()
+            .filter_map(|lock_file| lock_file.environment(&environment_name))
+            .flat_map(|env| env.platforms())
+            .collect::<IndexSet<_>>();
+        for platform in platforms {
+            let target = (environment_name.clone(), platform);
+            let source = if targets.contains(&target) {
+                updated
+            } else {
+                original
+            };
+            let packages = source
+                .environment(&environment_name)
+                .and_then(|env| env.packages(platform))
+                .into_iter()
+                .flatten();
+            for package in packages {
+                builder.add_package(&environment_name, platform, package.into());
+            }
+        }
+    }
+
+    builder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    /// Returns the lock-file exactly as it would be written to disk.
+    fn render(lock_file: &LockFile) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pixi.lock");
+        lock_file.to_path(&path).unwrap();
+        fs_err::read_to_string(path).unwrap()
+    }
+
+    fn test_lock_file() -> LockFile {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/data/mock-projects/test-project-export/pixi.lock");
+        LockFile::from_path(&path).unwrap()
+    }
+
+    /// Returns a copy of the lock-file without the packages of the given target.
+    fn without_target(lock_file: &LockFile, environment: &str, platform: Platform) -> LockFile {
+        let mut builder = LockFileBuilder::new();
+        for (name, env) in lock_file.environments() {
+            builder.set_channels(name, env.channels().to_vec());
+            if let Some(indexes) = env.pypi_indexes() {
+                builder.set_pypi_indexes(name, indexes.clone());
+            }
+            for (p, packages) in env.packages_by_platform() {
+                if name == environment && p == platform {
+                    continue;
+                }
+                for package in packages {
+                    builder.add_package(name, p, package.into());
+                }
+            }
+        }
+        builder.finish()
+    }
+
+    /// Returns a copy of the lock-file without the given environment.
+    fn without_environment(lock_file: &LockFile, environment: &str) -> LockFile {
+        let mut builder = LockFileBuilder::new();
+        for (name, env) in lock_file.environments() {
+            if name == environment {
+                continue;
+            }
+            builder.set_channels(name, env.channels().to_vec());
+            if let Some(indexes) = env.pypi_indexes() {
+                builder.set_pypi_indexes(name, indexes.clone());
+            }
+            for (p, packages) in env.packages_by_platform() {
+                for package in packages {
+                    builder.add_package(name, p, package.into());
+                }
+            }
+        }
+        builder.finish()
+    }
+
+    #[test]
+    fn test_merge_unchanged() {
+        let base = test_lock_file();
+        let merged = merge_lock_files(&base, &base, &base);
+        assert!(merged.is_clean());
+        for (name, env) in base.environments() {
+            for platform in env.platforms() {
+                assert_eq!(
+                    locked_package_set(&merged.lock_file, name, platform),
+                    locked_package_set(&base, name, platform)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge_one_sided_change() {
+        let base = test_lock_file();
+        let theirs = without_target(&base, "default", Platform::Osx64);
+
+        let merged = merge_lock_files(&base, &base, &theirs);
+        assert!(merged.is_clean());
+        assert!(merged
+            .lock_file
+            .environment("default")
+            .unwrap()
+            .packages(Platform::Osx64)
+            .is_none());
+    }
+
+    #[test]
+    fn test_merge_non_overlapping_changes() {
+        let base = test_lock_file();
+        let ours = without_target(&base, "default", Platform::Osx64);
+        let theirs = without_target(&base, "test", Platform::Linux64);
+
+        let merged = merge_lock_files(&base, &ours, &theirs);
+        assert!(merged.is_clean());
+        let lock_file = merged.lock_file;
+        assert!(lock_file
+            .environment("default")
+            .unwrap()
+            .packages(Platform::Osx64)
+            .is_none());
+        assert!(lock_file
+            .environment("test")
+            .unwrap()
+            .packages(Platform::Linux64)
+            .is_none());
+        assert!(lock_file
+            .environment("default")
+            .unwrap()
+            .packages(Platform::Linux64)
+            .is_some());
+    }
+
+    #[test]
+    fn test_merge_conflict() {
+        let base = test_lock_file();
+        let ours = without_target(&base, "default", Platform::Osx64);
+
+        // Modify the same target on the other side in a different way.
+        let mut builder = LockFileBuilder::new();
+        for (name, env) in base.environments() {
+            builder.set_channels(name, env.channels().to_vec());
+            if let Some(indexes) = env.pypi_indexes() {
+                builder.set_pypi_indexes(name, indexes.clone());
+            }
+            for (p, packages) in env.packages_by_platform() {
+                let skip = if name == "default" && p == Platform::Osx64 {
+                    1
+                } else {
+                    0
+                };
+                for package in packages.skip(skip) {
+                    builder.add_package(name, p, package.into());
+                }
+            }
+        }
+        let theirs = builder.finish();
+
+        let merged = merge_lock_files(&base, &ours, &theirs);
+        assert!(!merged.is_clean());
+        assert_eq!(
+            merged.conflicts.into_iter().collect_vec(),
+            vec![("default".to_string(), Platform::Osx64)]
+        );
+    }
+
+    #[test]
+    fn test_merge_removed_environment() {
+        let base = test_lock_file();
+        let ours = without_environment(&base, "test");
+
+        // Removing an environment that was not touched on the other side.
+        let merged = merge_lock_files(&base, &ours, &base);
+        assert!(merged.is_clean());
+        assert!(merged.lock_file.environment("test").is_none());
+
+        // Removing an environment that was modified on the other side.
+        let theirs = without_target(&base, "test", Platform::Linux64);
+        let merged = merge_lock_files(&base, &ours, &theirs);
+        assert_eq!(
+            merged.conflicts,
+            base.environment("test")
+                .unwrap()
+                .platforms()
+                .map(|platform| ("test".to_string(), platform))
+                .collect::<IndexSet<_>>()
+        );
+    }
+
+    #[test]
+    fn test_replace_targets() {
+        let original = test_lock_file();
+        let updated = without_target(&original, "default", Platform::Osx64);
+
+        // Replacing a target with identical content doesn't change the lock-file.
+        let targets = IndexSet::from([("test".to_string(), Platform::Linux64)]);
+        let replaced = replace_targets(&original, &updated, &targets);
+        assert_eq!(render(&replaced), render(&original));
+
+        // Only the selected target is taken from the updated lock-file.
+        let targets = IndexSet::from([("default".to_string(), Platform::Osx64)]);
+        let replaced = replace_targets(&updated, &original, &targets);
+        assert_eq!(render(&replaced), render(&original));
+        let replaced = replace_targets(&original, &updated, &targets);
+        assert!(replaced
+            .environment("default")
+            .unwrap()
+            .packages(Platform::Osx64)
+            .is_none());
+        assert_eq!(
+            locked_package_set(&replaced, "default", Platform::Linux64),
+            locked_package_set(&original, "default", Platform::Linux64)
+        );
+    }
+
+    #[test]
+    fn test_replace_targets_with_changed_channels() {
+        let original = test_lock_file();
+
+        // Lock the packages with other channels, and without the packages of the
+        // platform that isn't re-solved.
+        let mut builder = LockFileBuilder::new();
+        for (name, env) in original.environments() {
+            builder.set_channels(name, Vec::<rattler_lock::Channel>::new());
+            for (p, packages) in env.packages_by_platform() {
+                if name == "default" && p == Platform::Osx64 {
+                    continue;
+                }
+                for package in packages {
+                    builder.add_package(name, p, package.into());
+                }
+            }
+        }
+        let updated = builder.finish();
+
+        // The other platforms of the environment are still locked with the old
+        // channels, but they are kept as they were.
+        let targets = IndexSet::from([("default".to_string(), Platform::Linux64)]);
+        let stale = stale_targets(&original, &updated, &targets);
+        assert!(stale.contains(&("default".to_string(), Platform::Osx64)));
+        assert!(!stale.iter().any(|(env, _)| env == "test"));
+
+        let replaced = replace_targets(&original, &updated, &targets);
+        let default = replaced.environment("default").unwrap();
+        assert!(default.channels().is_empty());
+        assert!(default.packages(Platform::Linux64).is_some());
+        for platform in original.environment("default").unwrap().platforms() {
+            assert_eq!(
+                locked_package_set(&replaced, "default", platform),
+                locked_package_set(&original, "default", platform)
+            );
+        }
+        assert_eq!(
+            locked_package_set(&replaced, "test", Platform::Linux64),
+            locked_package_set(&original, "test", Platform::Linux64)
+        );
+    }
+}
@@ -1,3 +1,4 @@
+mod merge;
 mod outdated;
 mod package_identifier;
 mod records_by_name;
@@ -8,7 +9,11 @@ mod update;
 mod utils;
 
 use crate::Project;
+pub(crate) use merge::{
+    merge_lock_files, replace_targets, stale_targets, LockedTarget, MergedLockFile,
+};
 use miette::{IntoDiagnostic, WrapErr};
+pub(crate) use outdated::OutdatedEnvironments;
 pub(crate) use package_identifier::PypiPackageIdentifier;
 use pixi_record::PixiRecord;
 use rattler_lock::{LockFile, PypiPackageData, PypiPackageEnvironmentData};
@@ -22,7 +27,7 @@ pub use satisfiability::{
 };
 pub(crate) use update::{LockFileDerivedData, UpdateContext};
 pub use update::{UpdateLockFileOptions, UpdateMode};
-pub(crate) use utils::filter_lock_file;
+pub(crate) use utils::{filter_lock_file, IoConcurrencyLimit};
 
 /// A list of conda packages that are locked for a specific platform.
 pub type LockedCondaPackages = Vec<PixiRecord>;
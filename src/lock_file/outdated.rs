@@ -135,6 +135,18 @@ impl<'p> OutdatedEnvironments<'p> {
     pub(crate) fn is_empty(&self) -> bool {
         self.conda.is_empty() && self.pypi.is_empty()
     }
+
+    /// Only keep the outdated targets for which `keep` returns true. All other
+    /// targets are considered up-to-date and their locked content is left
+    /// untouched.
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&Environment<'p>, Platform) -> bool) {
+        for outdated in [&mut self.conda, &mut self.pypi] {
+            for (environment, platforms) in outdated.iter_mut() {
+                platforms.retain(|platform| keep(environment, *platform));
+            }
+            outdated.retain(|_, platforms| !platforms.is_empty());
+        }
+    }
 }
 
 /// Find all targets (combination of environment and platform) who's
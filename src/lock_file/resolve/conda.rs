@@ -1,12 +1,13 @@
 use ahash::HashMap;
 use itertools::Itertools;
-use miette::IntoDiagnostic;
+use miette::{IntoDiagnostic, Report};
 use pixi_record::{PixiRecord, SourceRecord};
 use rattler_conda_types::{GenericVirtualPackage, MatchSpec, RepoDataRecord};
 use rattler_repodata_gateway::RepoData;
-use rattler_solve::{resolvo, ChannelPriority, SolverImpl};
+use rattler_solve::{resolvo, ChannelPriority, SolveError, SolverImpl};
 use url::Url;
 
+use super::diagnostics::UnsolvableError;
 use crate::{
     build::{SourceCheckout, SourceMetadata},
     lock_file::LockedCondaPackages,
@@ -15,8 +16,15 @@ use crate::{
 /// Solves the conda package environment for the given input. This function is
 /// async because it spawns a background task for the solver. Since solving is a
 /// CPU intensive task we do not want to block the main task.
+///
+/// The `constraints` restrict the versions of packages that end up in the
+/// solution without requiring them.
+///
+/// If the requirements cannot be satisfied the returned error can be
+/// downcast to an [`UnsolvableError`].
 pub async fn resolve_conda(
     specs: Vec<MatchSpec>,
+    constraints: Vec<MatchSpec>,
     virtual_packages: Vec<GenericVirtualPackage>,
     locked_packages: Vec<RepoDataRecord>,
     available_repodata: Vec<RepoData>,
@@ -58,6 +66,7 @@ pub async fn resolve_conda(
         // Construct a solver task that we can start solving.
         let task = rattler_solve::SolverTask {
             specs,
+            constraints,
             locked_packages,
             virtual_packages,
             channel_priority,
@@ -65,7 +74,13 @@ pub async fn resolve_conda(
         };
 
         // Solve the task
-        let solved = resolvo::Solver.solve(task).into_diagnostic()?;
+        let solved = match resolvo::Solver.solve(task) {
+            Ok(solved) => solved,
+            Err(SolveError::Unsolvable(messages)) => {
+                return Err(Report::new(UnsolvableError::new(messages)))
+            }
+            Err(err) => return Err(err).into_diagnostic(),
+        };
 
         Ok(solved
             .into_iter()
@@ -0,0 +1,318 @@
+//! Diagnostics for conda environments that cannot be solved.
+//!
+//! The solver only reports which packages conflict, it doesn't know which
+//! feature of the environment requested them. The functions in this module map
+//! the conflicting packages back to the features that define them and can
+//! optionally solve subsets of the features to find the combination of
+//! features that conflicts.
+
+use std::{fmt::Write, future::Future};
+
+use fancy_display::FancyDisplay;
+use indexmap::IndexMap;
+use itertools::{Either, Itertools};
+use miette::Diagnostic;
+use pixi_manifest::{Feature, FeatureName};
+use pixi_spec::PixiSpec;
+use rattler_conda_types::{ChannelConfig, MatchSpec, PackageName, Platform};
+use thiserror::Error;
+
+/// The conda requirements of an environment cannot be satisfied.
+#[derive(Debug, Error, Diagnostic)]
+#[error("Cannot solve the request because of: {message}")]
+pub struct UnsolvableError {
+    message: String,
+
+    #[help]
+    help: Option<String>,
+}
+
+impl UnsolvableError {
+    /// Constructs the error from the messages reported by the solver.
+    pub fn new(messages: Vec<String>) -> Self {
+        Self {
+            message: messages.join(", "),
+            help: None,
+        }
+    }
+
+    /// Returns the message of the solver.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Adds a help message to the error.
+    pub fn with_help(self, help: Option<String>) -> Self {
+        Self { help, ..self }
+    }
+}
+
+/// The conda dependencies of the features of an environment for a single
+/// platform, in the order the environment includes the features.
+#[derive(Debug, Clone, Default)]
+pub struct FeatureDependencies {
+    features: Vec<(FeatureName, IndexMap<PackageName, PixiSpec>)>,
+}
+
+impl FeatureDependencies {
+    /// Collects the combined dependencies of the given features.
+    pub fn from_features<'a>(
+        features: impl IntoIterator<Item = &'a Feature>,
+        platform: Platform,
+    ) -> Self {
+        Self {
+            features: features
+                .into_iter()
+                .map(|feature| {
+                    let dependencies = feature
+                        .combined_dependencies(Some(platform))
+                        .map(|deps| deps.into_owned())
+                        .unwrap_or_default();
+                    (feature.name.clone(), dependencies)
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the number of features.
+    pub fn feature_count(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Returns the names of the features with the given indices.
+    pub fn names(&self, indices: &[usize]) -> Vec<&FeatureName> {
+        indices.iter().map(|&idx| &self.features[idx].0).collect()
+    }
+
+    /// Returns the features that define a requirement on the package with the
+    /// given name together with the requirement.
+    pub fn origins<'a>(
+        &'a self,
+        name: &'a PackageName,
+    ) -> impl Iterator<Item = (&'a FeatureName, &'a PixiSpec)> + 'a {
+        self.features
+            .iter()
+            .filter_map(move |(feature, deps)| deps.get(name).map(|spec| (feature, spec)))
+    }
+
+    /// Returns the requested packages that are mentioned in the message of
+    /// the solver.
+    pub fn conflicting_packages(&self, message: &str) -> Vec<&PackageName> {
+        let mentioned = message
+            .split(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+            .map(|word| word.trim_end_matches('.').to_ascii_lowercase())
+            .collect::<ahash::HashSet<_>>();
+
+        self.features
+            .iter()
+            .flat_map(|(_, deps)| deps.keys())
+            .unique()
+            .filter(|name| mentioned.contains(name.as_normalized()))
+            .collect()
+    }
+
+    /// Converts the dependencies of the features with the given indices into
+    /// match specs. Returns `None` if any of the dependencies is a source
+    /// dependency, because those cannot be solved without building metadata.
+    pub fn match_specs(
+        &self,
+        indices: &[usize],
+        channel_config: &ChannelConfig,
+    ) -> Option<Vec<MatchSpec>> {
+        indices
+            .iter()
+            .flat_map(|&idx| self.features[idx].1.iter())
+            .map(|(name, spec)| {
+                match spec
+                    .clone()
+                    .into_named_source_or_binary(name.clone(), channel_config)
+                {
+                    Ok(Either::Right(spec)) => Some(spec),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Formats a help message that explains which features define the
+    /// requirements on the packages in the message of the solver and which
+    /// requirement could be relaxed.
+    pub fn help(&self, message: &str, conflicting_features: Option<&[usize]>) -> Option<String> {
+        let mut help = String::new();
+
+        // Packages that are requested by more than one feature are the most
+        // likely culprits, list those first.
+        let packages = self
+            .conflicting_packages(message)
+            .into_iter()
+            .map(|name| (name, self.origins(name).collect_vec()))
+            .sorted_by_key(|(_, origins)| std::cmp::Reverse(origins.len()))
+            .collect_vec();
+
+        for (name, origins) in &packages {
+            let _ = writeln!(help, "'{}' is required by:", name.as_source());
+            for (feature, spec) in origins {
+                let _ = writeln!(
+                    help,
+                    "  - {} = {} (feature '{}')",
+                    name.as_source(),
+                    spec.to_toml_value().to_string().trim(),
+                    feature.fancy_display()
+                );
+            }
+        }
+
+        if let Some((name, origins)) = packages.first() {
+            let suggestion = match origins.as_slice() {
+                [(feature, _)] => format!(
+                    "try relaxing the requirement on '{}' in feature '{}'",
+                    name.as_source(),
+                    feature.fancy_display()
+                ),
+                origins => format!(
+                    "try relaxing the requirement on '{}' in one of the features {}",
+                    name.as_source(),
+                    origins
+                        .iter()
+                        .map(|(feature, _)| format!("'{}'", feature.fancy_display()))
+                        .join(", ")
+                ),
+            };
+            let _ = writeln!(help, "{suggestion}");
+        }
+
+        if let Some(conflicting_features) = conflicting_features {
+            let names = self
+                .names(conflicting_features)
+                .into_iter()
+                .map(|name| format!("'{}'", name.fancy_display()))
+                .join(", ");
+            let _ = match conflicting_features {
+                [_] => writeln!(help, "the feature {names} cannot be solved on its own"),
+                _ => writeln!(help, "the features {names} cannot be solved together"),
+            };
+        }
+
+        let help = help.trim_end();
+        (!help.is_empty()).then(|| help.to_string())
+    }
+}
+
+/// Finds a minimal combination of features that cannot be solved.
+///
+/// Starting from all features, which are known to be unsolvable, each feature
+/// is removed in turn. If the remaining features are still unsolvable the
+/// feature is not part of the conflict and stays removed. This requires one
+/// solve per feature. Returns `None` if the features could not be narrowed
+/// down, e.g. because every subset can be solved.
+pub async fn find_conflicting_features<F, Fut>(
+    feature_count: usize,
+    mut is_solvable: F,
+) -> Option<Vec<usize>>
+where
+    F: FnMut(Vec<usize>) -> Fut,
+    Fut: Future<Output = bool>,
+{
+    if feature_count < 2 {
+        return None;
+    }
+
+    let mut conflicting = (0..feature_count).collect_vec();
+    let mut idx = 0;
+    while idx < conflicting.len() {
+        let mut subset = conflicting.clone();
+        subset.remove(idx);
+        if !subset.is_empty() && !is_solvable(subset.clone()).await {
+            conflicting = subset;
+        } else {
+            idx += 1;
+        }
+    }
+
+    (conflicting.len() < feature_count).then_some(conflicting)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pixi_manifest::HasFeaturesIter;
+
+    use super::*;
+    use crate::Project;
+
+    const MANIFEST: &str = r#"
+        [project]
+        name = "diagnostics"
+        channels = ["conda-forge"]
+        platforms = ["linux-64"]
+
+        [dependencies]
+        python = "3.12.*"
+        numpy = ">=2"
+
+        [feature.legacy.dependencies]
+        numpy = "<2"
+
+        [feature.lint.dependencies]
+        ruff = "*"
+
+        [environments]
+        legacy = ["legacy", "lint"]
+    "#;
+
+    fn feature_dependencies() -> FeatureDependencies {
+        let project = Project::from_str(Path::new("pixi.toml"), MANIFEST).unwrap();
+        let environment = project.environment("legacy").unwrap();
+        FeatureDependencies::from_features(environment.features(), Platform::Linux64)
+    }
+
+    #[test]
+    fn test_conflicting_packages() {
+        let deps = feature_dependencies();
+        let message = "The following packages are incompatible\n├─ numpy >=2 can be installed\n└─ numpy <2 cannot be installed";
+        let conflicting = deps.conflicting_packages(message);
+        assert_eq!(
+            conflicting
+                .iter()
+                .map(|name| name.as_normalized())
+                .collect_vec(),
+            vec!["numpy"]
+        );
+
+        let numpy = PackageName::new_unchecked("numpy");
+        let origins = deps
+            .origins(&numpy)
+            .map(|(feature, _)| feature.as_str())
+            .sorted()
+            .collect_vec();
+        assert_eq!(origins, vec!["default", "legacy"]);
+
+        let help = deps.help(message, None).unwrap();
+        assert!(help.contains("numpy = \"<2\""));
+        assert!(help.contains("try relaxing the requirement on 'numpy'"));
+        assert!(!help.contains("ruff"));
+    }
+
+    #[tokio::test]
+    async fn test_find_conflicting_features() {
+        // Features 1 and 3 conflict with each other.
+        let conflicting = find_conflicting_features(4, |subset: Vec<usize>| async move {
+            !(subset.contains(&1) && subset.contains(&3))
+        })
+        .await;
+        assert_eq!(conflicting, Some(vec![1, 3]));
+
+        // Feature 2 cannot be solved on its own.
+        let conflicting =
+            find_conflicting_features(3, |subset: Vec<usize>| async move { !subset.contains(&2) })
+                .await;
+        assert_eq!(conflicting, Some(vec![2]));
+
+        // Every subset can be solved, only all features together conflict.
+        let conflicting =
+            find_conflicting_features(3, |subset: Vec<usize>| async move { subset.len() < 3 })
+                .await;
+        assert_eq!(conflicting, None);
+    }
+}
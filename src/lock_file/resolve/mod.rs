@@ -3,6 +3,7 @@
 //! See [`resolve_pypi`] and [`resolve_conda`] for more information.
 
 pub(crate) mod conda;
+pub(crate) mod diagnostics;
 pub(crate) mod pypi;
 mod resolver_provider;
 pub(crate) mod uv_resolution_context;
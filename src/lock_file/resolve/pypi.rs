@@ -159,6 +159,8 @@ pub async fn resolve_pypi(
     context: UvResolutionContext,
     pypi_options: &PypiOptions,
     dependencies: IndexMap<uv_normalize::PackageName, IndexSet<PyPiRequirement>>,
+    pypi_overrides: IndexMap<uv_normalize::PackageName, IndexSet<PyPiRequirement>>,
+    pypi_constraints: IndexMap<uv_normalize::PackageName, IndexSet<PyPiRequirement>>,
     system_requirements: SystemRequirements,
     locked_pixi_records: &[PixiRecord],
     locked_pypi_packages: &[PypiRecord],
@@ -218,14 +220,23 @@ pub async fn resolve_pypi(
         tracing::info!("there are no python packages installed by conda");
     }
 
-    let requirements = dependencies
-        .into_iter()
-        .flat_map(|(name, req)| {
-            req.into_iter()
-                .map(move |r| as_uv_req(&r, name.as_ref(), project_root))
-        })
-        .collect::<Result<Vec<_>, _>>()
-        .into_diagnostic()?;
+    let to_uv_requirements =
+        |requirements: IndexMap<uv_normalize::PackageName, IndexSet<PyPiRequirement>>| {
+            requirements
+                .into_iter()
+                .flat_map(|(name, req)| {
+                    req.into_iter()
+                        .map(move |r| as_uv_req(&r, name.as_ref(), project_root))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .into_diagnostic()
+        };
+    let requirements = to_uv_requirements(dependencies)?;
+
+    // The overrides replace the requirements on the same packages, the
+    // constraints are added to the constraints from the conda packages.
+    let overrides = Overrides::from_requirements(to_uv_requirements(pypi_overrides)?);
+    let pypi_constraints = to_uv_requirements(pypi_constraints)?;
 
     use pixi_consts::consts::PROJECT_MANIFEST;
     // Determine the python interpreter that is installed as part of the conda
@@ -413,12 +424,13 @@ pub async fn resolve_pypi(
 
     let resolver_env = ResolverEnvironment::specific(marker_environment.clone().into());
 
-    let constraints = Constraints::from_requirements(constraints.iter().cloned());
+    let constraints =
+        Constraints::from_requirements(constraints.iter().cloned().chain(pypi_constraints));
     let lookahead_index = InMemoryIndex::default();
     let lookaheads = LookaheadResolver::new(
         &requirements,
         &constraints,
-        &Overrides::default(),
+        &overrides,
         &[],
         &context.hash_strategy,
         &lookahead_index,
@@ -438,7 +450,7 @@ pub async fn resolve_pypi(
     let manifest = Manifest::new(
         requirements,
         constraints,
-        Overrides::default(),
+        overrides,
         Default::default(),
         Preferences::from_iter(preferences, &resolver_env),
         None,
@@ -7,7 +7,7 @@ use uv_distribution_types::IndexCapabilities;
 use uv_types::{HashStrategy, InFlight};
 
 use crate::Project;
-use pixi_config::{self, get_cache_dir};
+use pixi_config::{self, get_cache_dir, Config};
 use pixi_consts::consts;
 use pixi_uv_conversions::{to_uv_trusted_host, ConversionError};
 
@@ -28,6 +28,12 @@ pub struct UvResolutionContext {
 
 impl UvResolutionContext {
     pub(crate) fn from_project(project: &Project) -> miette::Result<Self> {
+        Self::from_config(project.config(), project.client().clone())
+    }
+
+    /// Constructs a context from the given configuration, this is used when
+    /// there is no project, e.g. for `pixi exec`.
+    pub(crate) fn from_config(config: &Config, client: reqwest::Client) -> miette::Result<Self> {
         let uv_cache = get_cache_dir()?.join(consts::PYPI_CACHE_DIR);
         if !uv_cache.exists() {
             std::fs::create_dir_all(&uv_cache)
@@ -37,7 +43,7 @@ impl UvResolutionContext {
 
         let cache = Cache::from_path(uv_cache);
 
-        let keyring_provider = match project.config().pypi_config().use_keyring() {
+        let keyring_provider = match config.pypi_config().use_keyring() {
             pixi_config::KeyringProvider::Subprocess => {
                 tracing::info!("using uv keyring (subprocess) provider");
                 uv_configuration::KeyringProviderType::Subprocess
@@ -49,8 +55,7 @@ impl UvResolutionContext {
         };
 
         let in_flight = Arc::new(InFlight::default());
-        let allow_insecure_host = project
-            .config()
+        let allow_insecure_host = config
             .pypi_config
             .allow_insecure_host
             .iter()
@@ -68,7 +73,7 @@ impl UvResolutionContext {
             cache,
             in_flight,
             hash_strategy: HashStrategy::None,
-            client: project.client().clone(),
+            client,
             build_options: BuildOptions::default(),
             keyring_provider,
             concurrency: Concurrency::default(),
@@ -11,7 +11,7 @@ use itertools::{Either, Itertools};
 use miette::Diagnostic;
 use pep440_rs::VersionSpecifiers;
 use pixi_glob::{GlobHashCache, GlobHashError, GlobHashKey};
-use pixi_manifest::FeaturesExt;
+use pixi_manifest::{FeaturesExt, PyPiDependencies};
 use pixi_record::{ParseLockFileError, PixiRecord, SourceMismatchError};
 use pixi_spec::{PixiSpec, SourceSpec, SpecConversionError};
 use pixi_uv_conversions::{
@@ -155,6 +155,12 @@ pub enum PlatformUnsat {
     #[error("the requirement '{0}' failed to parse")]
     FailedToParseMatchSpec(String, #[source] ParseMatchSpecError),
 
+    #[error("the locked package '{1}' does not satisfy the constraint '{0}'")]
+    UnsatisfiedConstraint(MatchSpec, String),
+
+    #[error("the locked package '{1}' does not satisfy the pypi constraint '{0}'")]
+    UnsatisfiedPypiConstraint(Box<uv_pypi_types::Requirement>, String),
+
     #[error("there are more conda packages in the lock-file than are used by the environment")]
     TooManyCondaPackages,
 
@@ -777,24 +783,20 @@ pub(crate) async fn verify_package_platform_satisfiability(
         return Err(Box::new(PlatformUnsat::TooManyCondaPackages));
     }
 
+    // The overrides replace all requirements on the same package, like they do
+    // when the environment is solved.
+    let pypi_overrides = as_uv_requirements(&environment.pypi_overrides(), project_root)?
+        .into_iter()
+        .into_group_map_by(|requirement| requirement.name.clone());
+    let pypi_constraints = as_uv_requirements(&environment.pypi_constraints(), project_root)?;
+
     // Transform from PyPiPackage name into UV Requirement type
-    let pypi_requirements = environment
-        .pypi_dependencies(Some(platform))
-        .iter()
-        .flat_map(|(name, reqs)| {
-            reqs.iter().map(move |req| {
-                Ok::<Dependency, Box<PlatformUnsat>>(Dependency::PyPi(
-                    as_uv_req(req, name.as_source(), project_root).map_err(|e| {
-                        Box::new(PlatformUnsat::AsPep508Error(
-                            name.as_normalized().clone(),
-                            e,
-                        ))
-                    })?,
-                    "<environment>".into(),
-                ))
-            })
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+    let pypi_requirements =
+        as_uv_requirements(&environment.pypi_dependencies(Some(platform)), project_root)?
+            .into_iter()
+            .flat_map(|requirement| apply_pypi_overrides(requirement, &pypi_overrides))
+            .map(|requirement| Dependency::PyPi(requirement, "<environment>".into()))
+            .collect_vec();
 
     if pypi_requirements.is_empty() && !locked_pypi_environment.is_empty() {
         return Err(Box::new(PlatformUnsat::TooManyPypiPackages(
@@ -1085,10 +1087,66 @@ pub(crate) async fn verify_package_platform_satisfiability(
                         continue;
                     }
 
-                    pypi_queue.push(Dependency::PyPi(
-                        requirement.clone(),
-                        record.0.name.as_ref().to_string().into(),
-                    ));
+                    for requirement in apply_pypi_overrides(requirement, &pypi_overrides) {
+                        pypi_queue.push(Dependency::PyPi(
+                            requirement,
+                            record.0.name.as_ref().to_string().into(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    // Constraints don't require packages to be locked, but the packages that are
+    // locked have to satisfy them.
+    for (name, constraint) in environment.constraints().into_specs() {
+        let Some(record) = locked_pixi_records.by_name(&name) else {
+            continue;
+        };
+        let Ok(Either::Right(spec)) = constraint.into_named_source_or_binary(name, &channel_config)
+        else {
+            continue;
+        };
+        if !spec.matches(record.package_record()) {
+            return Err(Box::new(PlatformUnsat::UnsatisfiedConstraint(
+                spec,
+                format!(
+                    "{} {}",
+                    record.package_record().name.as_source(),
+                    record.package_record().version
+                ),
+            )));
+        }
+    }
+
+    if let Some(marker_environment) = marker_environment.as_ref() {
+        for constraint in pypi_constraints {
+            if !constraint.evaluate_markers(Some(marker_environment), &[]) {
+                continue;
+            }
+            if let Some((identifier, _, _)) = locked_conda_pypi_packages.get(&constraint.name) {
+                if !identifier
+                    .satisfies(&constraint)
+                    .map_err(From::from)
+                    .map_err(Box::new)?
+                {
+                    return Err(Box::new(PlatformUnsat::UnsatisfiedPypiConstraint(
+                        Box::new(constraint),
+                        format!("{} {}", identifier.name.as_source(), identifier.version),
+                    )));
+                }
+            } else if let Some(record) = locked_pypi_environment.by_name(
+                &to_normalize(&constraint.name)
+                    .map_err(ConversionError::NameConversion)
+                    .map_err(From::from)
+                    .map_err(Box::new)?,
+            ) {
+                if pypi_satifisfies_requirement(&constraint, &record.0, project_root).is_err() {
+                    return Err(Box::new(PlatformUnsat::UnsatisfiedPypiConstraint(
+                        Box::new(constraint),
+                        format!("{} {}", record.0.name, record.0.version),
+                    )));
                 }
             }
         }
@@ -1199,6 +1257,37 @@ enum FoundPackage {
     PyPi(usize, Vec<uv_pep508::ExtraName>),
 }
 
+/// Converts the pypi requirements of the environment into uv requirements.
+fn as_uv_requirements(
+    dependencies: &PyPiDependencies,
+    project_root: &Path,
+) -> Result<Vec<uv_pypi_types::Requirement>, Box<PlatformUnsat>> {
+    dependencies
+        .iter()
+        .flat_map(|(name, reqs)| {
+            reqs.iter().map(move |req| {
+                as_uv_req(req, name.as_source(), project_root).map_err(|e| {
+                    Box::new(PlatformUnsat::AsPep508Error(
+                        name.as_normalized().clone(),
+                        e,
+                    ))
+                })
+            })
+        })
+        .collect()
+}
+
+/// Replaces a requirement by the overrides of its package, if there are any.
+fn apply_pypi_overrides(
+    requirement: uv_pypi_types::Requirement,
+    overrides: &HashMap<uv_normalize::PackageName, Vec<uv_pypi_types::Requirement>>,
+) -> Vec<uv_pypi_types::Requirement> {
+    match overrides.get(&requirement.name) {
+        Some(overrides) => overrides.clone(),
+        None => vec![requirement],
+    }
+}
+
 fn find_matching_package(
     locked_pixi_records: &PixiRecordsByName,
     virtual_packages: &HashMap<rattler_conda_types::PackageName, GenericVirtualPackage>,
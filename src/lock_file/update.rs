@@ -22,7 +22,7 @@ use miette::{Diagnostic, IntoDiagnostic, LabeledSpan, MietteDiagnostic, WrapErr}
 use pixi_build_frontend::ToolContext;
 use pixi_config::get_cache_dir;
 use pixi_consts::consts;
-use pixi_manifest::{EnvironmentName, FeaturesExt, HasFeaturesIter};
+use pixi_manifest::{EnvironmentName, FeaturesExt, HasFeaturesIter, PyPiDependencies};
 use pixi_progress::global_multi_progress;
 use pixi_record::{ParseLockFileError, PixiRecord};
 use pixi_uv_conversions::{
@@ -44,8 +44,10 @@ use tracing::Instrument;
 use uv_normalize::ExtraName;
 
 use super::{
-    outdated::OutdatedEnvironments, utils::IoConcurrencyLimit, PixiRecordsByName,
-    PypiRecordsByName, UvResolutionContext,
+    outdated::OutdatedEnvironments,
+    resolve::diagnostics::{find_conflicting_features, FeatureDependencies, UnsolvableError},
+    utils::IoConcurrencyLimit,
+    PixiRecordsByName, PypiRecordsByName, UvResolutionContext,
 };
 use crate::{
     activation::CurrentEnvVarBehavior,
@@ -1688,6 +1690,9 @@ async fn spawn_solve_conda_environment_task(
     // Get the dependencies for this platform
     let dependencies = group.combined_dependencies(Some(platform));
 
+    // Get the constraints, these don't add packages but restrict their versions
+    let constraints = group.constraints();
+
     // Get the virtual packages for this platform
     let virtual_packages = group.virtual_packages(platform);
 
@@ -1706,6 +1711,14 @@ async fn spawn_solve_conda_environment_task(
     // Get the channel configuration
     let channel_config = group.project().channel_config();
 
+    // The dependencies per feature, used to explain why the environment cannot be
+    // solved.
+    let feature_dependencies = FeatureDependencies::from_features(group.features(), platform);
+    let feature_solve_diagnostics = group
+        .project()
+        .config()
+        .experimental_feature_solve_diagnostics();
+
     tokio::spawn(
         async move {
             // Acquire a permit before we are allowed to solve the environment.
@@ -1733,6 +1746,18 @@ async fn spawn_solve_conda_environment_task(
                         .expect("failed to convert dependency into match spec")
                 });
 
+            // Constraints only refer to binary packages, this is validated when
+            // the manifest is parsed.
+            let constraints = constraints
+                .into_specs()
+                .filter_map(|(name, constraint)| {
+                    constraint
+                        .into_named_source_or_binary(name, &channel_config)
+                        .expect("failed to convert constraint into match spec")
+                        .right()
+                })
+                .collect_vec();
+
             // Collect metadata from all source packages
             let channel_urls = channels
                 .iter()
@@ -1842,16 +1867,66 @@ async fn spawn_solve_conda_environment_task(
                 })
                 .collect();
 
-            let mut records = lock_file::resolve_conda(
+            let records = lock_file::resolve_conda(
                 all_specs,
-                virtual_packages,
+                constraints.clone(),
+                virtual_packages.clone(),
                 locked_records,
-                available_packages,
+                available_packages.clone(),
                 source_repodata,
                 channel_priority,
             )
-            .await
-            .with_context(|| {
+            .await;
+
+            // If the environment cannot be solved, explain which features request the
+            // conflicting packages.
+            let records = match records {
+                Ok(records) => Ok(records),
+                Err(err) => match err.downcast::<UnsolvableError>() {
+                    Ok(unsolvable) => {
+                        let conflicting_features = if feature_solve_diagnostics {
+                            pb.set_message("diagnosing conflict");
+                            find_conflicting_features(
+                                feature_dependencies.feature_count(),
+                                |subset| {
+                                    let specs =
+                                        feature_dependencies.match_specs(&subset, &channel_config);
+                                    let constraints = constraints.clone();
+                                    let virtual_packages = virtual_packages.clone();
+                                    let available_packages = available_packages.clone();
+                                    async move {
+                                        // Source dependencies cannot be solved without their
+                                        // metadata, don't narrow down these subsets.
+                                        let Some(specs) = specs else {
+                                            return true;
+                                        };
+                                        lock_file::resolve_conda(
+                                            specs,
+                                            constraints,
+                                            virtual_packages,
+                                            Vec::new(),
+                                            available_packages,
+                                            Vec::new(),
+                                            channel_priority,
+                                        )
+                                        .await
+                                        .is_ok()
+                                    }
+                                },
+                            )
+                            .await
+                        } else {
+                            None
+                        };
+                        let help = feature_dependencies
+                            .help(unsolvable.message(), conflicting_features.as_deref());
+                        Err(Report::new(unsolvable.with_help(help)))
+                    }
+                    Err(err) => Err(err),
+                },
+            };
+
+            let mut records = records.with_context(|| {
                 format!(
                     "failed to solve the conda requirements of '{}' '{}'",
                     group_name.fancy_display(),
@@ -2091,6 +2166,8 @@ async fn spawn_solve_pypi_task(
 ) -> miette::Result<TaskResult> {
     // Get the Pypi dependencies for this environment
     let dependencies = environment.pypi_dependencies(Some(platform));
+    let pypi_overrides = environment.pypi_overrides();
+    let pypi_constraints = environment.pypi_constraints();
     if dependencies.is_empty() {
         return Ok(TaskResult::PypiGroupSolved(
             environment.name().clone(),
@@ -2146,18 +2223,22 @@ async fn spawn_solve_pypi_task(
 
         let start = Instant::now();
 
-        let dependencies: Vec<(uv_normalize::PackageName, IndexSet<_>)> = dependencies
-            .into_iter()
-            .map(|(name, requirement)| Ok((to_uv_normalize(name.as_normalized())?, requirement)))
-            .collect::<Result<_, ConversionError>>()
-            .into_diagnostic()?;
-
-        let index_map = IndexMap::from_iter(dependencies);
+        let to_uv_dependencies = |dependencies: PyPiDependencies| {
+            dependencies
+                .into_iter()
+                .map(|(name, requirement)| {
+                    Ok((to_uv_normalize(name.as_normalized())?, requirement))
+                })
+                .collect::<Result<IndexMap<_, IndexSet<_>>, ConversionError>>()
+                .into_diagnostic()
+        };
 
         let records = lock_file::resolve_pypi(
             resolution_context,
             &pypi_options,
-            index_map,
+            to_uv_dependencies(dependencies)?,
+            to_uv_dependencies(pypi_overrides)?,
+            to_uv_dependencies(pypi_constraints)?,
             system_requirements,
             &pixi_records,
             &locked_pypi_records,
@@ -6,12 +6,13 @@ use std::{
     sync::Once,
 };
 
-use indexmap::IndexMap;
+use indexmap::IndexSet;
 use itertools::Either;
 use pixi_consts::consts;
 use pixi_manifest::{
-    self as manifest, EnvironmentName, Feature, FeatureName, FeaturesExt, HasFeaturesIter,
-    HasManifestRef, Manifest, SystemRequirements, Task, TaskName,
+    self as manifest, EnvValue, EnvironmentName, Feature, FeatureName, FeaturesExt,
+    HasFeaturesIter, HasManifestRef, Manifest, PrioritizedChannel, SystemRequirements, Task,
+    TaskName,
 };
 use rattler_conda_types::{Arch, Platform};
 
@@ -267,18 +268,28 @@ impl<'p> Environment<'p> {
             .collect()
     }
 
+    /// Returns the activation scripts that are executed with the task shell.
+    ///
+    /// The scripts of all features are combined in the order they are defined
+    /// for the environment.
+    pub(crate) fn activation_task_shell_scripts(&self, platform: Option<Platform>) -> Vec<String> {
+        self.features()
+            .filter_map(|f| f.activation_task_shell_scripts(platform))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
     /// Returns the environment variables that should be set when activating
-    /// this environment.
+    /// this environment, in the order in which they are applied.
     ///
-    /// The environment variables of all features are combined in the order they
-    /// are defined for the environment.
-    pub(crate) fn activation_env(&self, platform: Option<Platform>) -> IndexMap<String, String> {
+    /// The variables of the features are applied in the order they are
+    /// defined for the environment, followed by the default feature, so the
+    /// default feature takes precedence over the other features.
+    pub(crate) fn activation_env(&self, platform: Option<Platform>) -> Vec<(String, EnvValue)> {
         self.features()
-            .map(|f| f.activation_env(platform))
-            .fold(IndexMap::new(), |mut acc, env| {
-                acc.extend(env.iter().map(|(k, v)| (k.clone(), v.clone())));
-                acc
-            })
+            .flat_map(|f| f.activation_env(platform))
+            .collect()
     }
 
     /// Validates that the given platform is supported by this environment.
@@ -330,6 +341,26 @@ impl<'p> HasFeaturesIter<'p> for Environment<'p> {
             Either::Left(environment_features.chain([self.manifest().default_feature()]))
         }
     }
+
+    /// Returns the system requirements of the environment, including those of
+    /// its solve group, so that all environments in a solve group select the
+    /// same targets as the solve.
+    fn target_system_requirements(&self) -> SystemRequirements {
+        self.system_requirements()
+    }
+
+    /// Returns the platforms that are set on the environment.
+    fn platforms_override(&self) -> Option<HashSet<Platform>> {
+        self.environment
+            .platforms
+            .as_ref()
+            .map(|platforms| platforms.value.iter().copied().collect())
+    }
+
+    /// Returns the channels that are set on the environment.
+    fn channels_override(&self) -> Option<&'p IndexSet<PrioritizedChannel>> {
+        self.environment.channels.as_ref()
+    }
 }
 
 impl<'p> Hash for Environment<'p> {
@@ -342,7 +373,6 @@ impl<'p> Hash for Environment<'p> {
 mod tests {
     use std::{collections::HashSet, path::Path};
 
-    use indexmap::indexmap;
     use insta::assert_snapshot;
     use itertools::Itertools;
     use pixi_manifest::CondaDependencies;
@@ -502,6 +532,71 @@ mod tests {
         assert_snapshot!(format_dependencies(deps));
     }
 
+    #[test]
+    fn test_constraints() {
+        let manifest = Project::from_str(
+            Path::new("pixi.toml"),
+            r#"
+        [project]
+        name = "foobar"
+        channels = []
+        platforms = ["linux-64"]
+
+        [constraints]
+        foo = "<3.0"
+
+        [pypi-overrides]
+        requests = "==2.31"
+
+        [feature.x.constraints]
+        foo = ">=1.0"
+        bar = ">=2.0"
+
+        [feature.x.pypi-constraints]
+        urllib3 = "<2"
+
+        [environments]
+        x = ["x"]
+        "#,
+        )
+        .unwrap();
+
+        let env = manifest.environment("x").unwrap();
+        assert_eq!(
+            format_dependencies(env.constraints()),
+            "foo = \">=1.0\"\nfoo = \"<3.0\"\nbar = \">=2.0\""
+        );
+        assert_eq!(
+            env.pypi_overrides()
+                .iter()
+                .map(|(name, _)| name.as_source())
+                .collect_vec(),
+            vec!["requests"]
+        );
+        assert_eq!(
+            env.pypi_constraints()
+                .iter()
+                .map(|(name, _)| name.as_source())
+                .collect_vec(),
+            vec!["urllib3"]
+        );
+
+        // Constraints can't refer to source packages.
+        assert!(Project::from_str(
+            Path::new("pixi.toml"),
+            r#"
+        [project]
+        name = "foobar"
+        channels = []
+        platforms = ["linux-64"]
+
+        [constraints]
+        foo = { path = "./foo" }
+        "#,
+        )
+        .is_err());
+    }
+
     #[test]
     fn test_activation() {
         let manifest = Project::from_str(
@@ -567,17 +662,17 @@ mod tests {
         let foo_env = manifest.environment("foo").unwrap();
         assert_eq!(
             foo_env.activation_env(None),
-            indexmap! {
-                "FOO_VAR".to_string() => "1".to_string(),
-                "DEFAULT_VAR".to_string() => "1".to_string(),
-            }
+            vec![
+                ("DEFAULT_VAR".to_string(), EnvValue::Set("1".to_string())),
+                ("FOO_VAR".to_string(), EnvValue::Set("1".to_string())),
+            ]
         );
         assert_eq!(
             default_env.activation_env(Some(Platform::Linux64)),
-            indexmap! {
-                "LINUX_VAR".to_string() => "1".to_string(),
-                "DEFAULT_VAR".to_string() => "1".to_string(),
-            }
+            vec![
+                ("DEFAULT_VAR".to_string(), EnvValue::Set("1".to_string())),
+                ("LINUX_VAR".to_string(), EnvValue::Set("1".to_string())),
+            ]
         );
     }
 
@@ -5,13 +5,15 @@ use crate::{
     Project,
 };
 use fancy_display::FancyDisplay;
+use indexmap::IndexSet;
 use itertools::Either;
 use pixi_consts::consts;
 use pixi_manifest::{
-    EnvironmentName, Feature, HasFeaturesIter, HasManifestRef, Manifest, SystemRequirements,
+    EnvironmentName, Feature, HasFeaturesIter, HasManifestRef, Manifest, PrioritizedChannel,
+    SystemRequirements,
 };
 use rattler_conda_types::{GenericVirtualPackage, Platform};
-use std::path::PathBuf;
+use std::{collections::HashSet, path::PathBuf};
 
 /// Either a solve group or an individual environment without a solve group.
 ///
@@ -134,6 +136,25 @@ impl<'p> HasFeaturesIter<'p> for GroupedEnvironment<'p> {
             GroupedEnvironment::Environment(env) => Either::Right(env.features()),
         }
     }
+
+    /// Returns the system requirements of the group.
+    fn target_system_requirements(&self) -> SystemRequirements {
+        self.system_requirements()
+    }
+
+    fn platforms_override(&self) -> Option<HashSet<Platform>> {
+        match self {
+            GroupedEnvironment::Group(group) => group.platforms_override(),
+            GroupedEnvironment::Environment(env) => env.platforms_override(),
+        }
+    }
+
+    fn channels_override(&self) -> Option<&'p IndexSet<PrioritizedChannel>> {
+        match self {
+            GroupedEnvironment::Group(group) => group.channels_override(),
+            GroupedEnvironment::Environment(env) => env.channels_override(),
+        }
+    }
 }
 
 /// A name of a [`GroupedEnvironment`].
@@ -2,6 +2,7 @@ mod environment;
 pub mod errors;
 pub mod grouped_environment;
 mod has_project_ref;
+pub(crate) mod remote_includes;
 mod repodata;
 mod solve_group;
 pub mod virtual_packages;
@@ -79,6 +80,7 @@ pub struct EnvironmentVars {
     clean: Arc<AsyncCell<HashMap<String, String>>>,
     pixi_only: Arc<AsyncCell<HashMap<String, String>>>,
     full: Arc<AsyncCell<HashMap<String, String>>>,
+    task_shell: Arc<AsyncCell<IndexMap<String, String>>>,
 }
 
 impl EnvironmentVars {
@@ -88,6 +90,7 @@ impl EnvironmentVars {
             clean: Arc::new(AsyncCell::new()),
             pixi_only: Arc::new(AsyncCell::new()),
             full: Arc::new(AsyncCell::new()),
+            task_shell: Arc::new(AsyncCell::new()),
         }
     }
 
@@ -105,6 +108,11 @@ impl EnvironmentVars {
     pub(crate) fn full(&self) -> &Arc<AsyncCell<HashMap<String, String>>> {
         &self.full
     }
+
+    /// Get the variables exported by the task shell activation scripts
+    pub(crate) fn task_shell(&self) -> &Arc<AsyncCell<IndexMap<String, String>>> {
+        &self.task_shell
+    }
 }
 
 /// List of packages that are not following the semver versioning scheme
@@ -188,7 +196,9 @@ impl Project {
 
     /// Constructs a project from a manifest.
     pub fn from_str(manifest_path: &Path, content: &str) -> miette::Result<Self> {
-        let manifest = Manifest::from_str(manifest_path, content)?;
+        let manifest = remote_includes::load_manifest(|remote_includes| {
+            Manifest::from_str_with_remote_includes(manifest_path, content, remote_includes)
+        })?;
         Ok(Self::from_manifest(manifest))
     }
 
@@ -197,6 +207,12 @@ impl Project {
     /// environment. This will also set the current working directory to the
     /// project root.
     pub(crate) fn discover() -> miette::Result<Self> {
+        Self::from_path(&Self::discover_manifest_path()?)
+    }
+
+    /// Returns the path of the manifest that [`Project::discover`] would load,
+    /// without parsing it.
+    pub(crate) fn discover_manifest_path() -> miette::Result<PathBuf> {
         let project_toml = find_project_manifest(std::env::current_dir().into_diagnostic()?);
 
         if let Some(project_toml) = project_toml {
@@ -211,11 +227,11 @@ impl Project {
                     }
                 }
             }
-            return Self::from_path(&project_toml);
+            return Ok(project_toml);
         }
 
         if let Ok(env_manifest_path) = std::env::var("PIXI_PROJECT_MANIFEST") {
-            return Self::from_path(Path::new(env_manifest_path.as_str()));
+            return Ok(PathBuf::from(env_manifest_path));
         }
 
         miette::bail!(
@@ -227,7 +243,9 @@ impl Project {
 
     /// Loads a project from manifest file.
     pub fn from_path(manifest_path: &Path) -> miette::Result<Self> {
-        let manifest = Manifest::from_path(manifest_path)?;
+        let manifest = remote_includes::load_manifest(|remote_includes| {
+            Manifest::from_path_with_remote_includes(manifest_path, remote_includes)
+        })?;
         Ok(Project::from_manifest(manifest))
     }
 
@@ -425,6 +443,19 @@ impl Project {
             .ok_or_else(|| miette::miette!("unknown environment '{environment_name}'"))
     }
 
+    /// Returns the cached activation variables of the environment.
+    pub(crate) fn environment_vars(
+        &self,
+        environment: &Environment<'_>,
+    ) -> miette::Result<&EnvironmentVars> {
+        self.env_vars.get(environment.name()).ok_or_else(|| {
+            miette::miette!(
+                "{} environment should be already created during project creation",
+                environment.name()
+            )
+        })
+    }
+
     /// Get or initialize the activated environment variables
     pub async fn get_activated_environment_variables(
         &self,
@@ -434,12 +465,7 @@ impl Project {
         force_activate: bool,
         experimental_cache: bool,
     ) -> miette::Result<&HashMap<String, String>> {
-        let vars = self.env_vars.get(environment.name()).ok_or_else(|| {
-            miette::miette!(
-                "{} environment should be already created during project creation",
-                environment.name()
-            )
-        })?;
+        let vars = self.environment_vars(environment)?;
         match current_env_var_behavior {
             CurrentEnvVarBehavior::Clean => {
                 vars.clean()
@@ -1024,9 +1050,14 @@ impl<'source> HasManifestRef<'source> for &'source Project {
 /// Iterates over the current directory and all its parent directories and
 /// returns the manifest path in the first directory path that contains the
 /// [`consts::PROJECT_MANIFEST`] or [`consts::PYPROJECT_MANIFEST`].
+///
+/// A [`consts::PROJECT_MANIFEST`] without a `[workspace]` section belongs to a
+/// member of a workspace, in that case the search continues with the parent
+/// directories to find the workspace.
 pub(crate) fn find_project_manifest(current_dir: PathBuf) -> Option<PathBuf> {
     let manifests = [consts::PROJECT_MANIFEST, consts::PYPROJECT_MANIFEST];
 
+    let mut member_manifest = None;
     for dir in current_dir.ancestors() {
         for manifest in &manifests {
             let path = dir.join(manifest);
@@ -1035,7 +1066,12 @@ pub(crate) fn find_project_manifest(current_dir: PathBuf) -> Option<PathBuf> {
             }
 
             match *manifest {
-                consts::PROJECT_MANIFEST => return Some(path),
+                consts::PROJECT_MANIFEST => {
+                    if is_workspace_manifest(&path) {
+                        return Some(path);
+                    }
+                    member_manifest.get_or_insert(path);
+                }
                 consts::PYPROJECT_MANIFEST => {
                     if let Ok(content) = std::fs::read_to_string(&path) {
                         if content.contains("[tool.pixi") {
@@ -1048,7 +1084,20 @@ pub(crate) fn find_project_manifest(current_dir: PathBuf) -> Option<PathBuf> {
         }
     }
 
-    None
+    member_manifest
+}
+
+/// Returns true if the [`consts::PROJECT_MANIFEST`] at `path` defines a
+/// workspace, i.e. it's not the manifest of a member of a workspace.
+fn is_workspace_manifest(path: &Path) -> bool {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return true;
+    };
+    contents
+        .parse::<toml_edit::DocumentMut>()
+        .map_or(true, |document| {
+            document.contains_key("workspace") || document.contains_key("project")
+        })
 }
 
 /// Create a symlink from the directory to the custom target directory
@@ -0,0 +1,89 @@
+//! Remote files that are included by a manifest are fetched with the client
+//! of the project and cached by their hash in the pixi cache directory. The
+//! manifest is loaded with the cached copies, a file that isn't cached yet
+//! is reported as an [`UnresolvedRemoteInclude`] error so it can be fetched.
+
+use std::path::PathBuf;
+
+use miette::{Context, IntoDiagnostic};
+use pixi_config::Config;
+use pixi_consts::consts;
+use pixi_manifest::{Manifest, RemoteInclude, RemoteIncludes, UnresolvedRemoteInclude};
+use pixi_utils::reqwest::build_reqwest_clients;
+use rattler_digest::{compute_bytes_digest, Sha256, Sha256Hash};
+
+/// Loads a manifest with `load`, providing the cached copies of the remote
+/// files it includes.
+pub(crate) fn load_manifest(
+    load: impl Fn(&RemoteIncludes) -> miette::Result<Manifest>,
+) -> miette::Result<Manifest> {
+    let mut remote_includes = RemoteIncludes::new();
+    loop {
+        let err = match load(&remote_includes) {
+            Ok(manifest) => return Ok(manifest),
+            Err(err) => err,
+        };
+        let Some(unresolved) = err.downcast_ref::<UnresolvedRemoteInclude>() else {
+            return Err(err);
+        };
+        let include = &unresolved.include;
+        match read_cached(include)? {
+            Some(contents) if !remote_includes.contains_key(&include.sha256) => {
+                remote_includes.insert(include.sha256, contents);
+            }
+            _ => return Err(err),
+        }
+    }
+}
+
+/// Fetches the remote file of `unresolved` into the cache, using the client
+/// configured for the project that includes it. The file is rejected if it
+/// doesn't match the pinned hash.
+pub(crate) async fn fetch(unresolved: &UnresolvedRemoteInclude) -> miette::Result<()> {
+    let RemoteInclude { url, sha256 } = &unresolved.include;
+    let (_, client) = build_reqwest_clients(Some(&Config::load(&unresolved.root)));
+    let contents = client
+        .get(url.clone())
+        .send()
+        .await
+        .into_diagnostic()?
+        .error_for_status()
+        .into_diagnostic()?
+        .bytes()
+        .await
+        .into_diagnostic()
+        .wrap_err_with(|| format!("failed to fetch the included file '{url}'"))?;
+
+    let digest = compute_bytes_digest::<Sha256>(&contents);
+    if digest != *sha256 {
+        miette::bail!(
+            help = "update the `sha256` of the include if the file was changed on purpose",
+            "the sha256 hash of the included file '{url}' is {digest:x}, but {sha256:x} is expected"
+        );
+    }
+
+    let path = cache_path(sha256)?;
+    let parent = path.parent().expect("the path always has a parent");
+    fs_err::tokio::create_dir_all(parent)
+        .await
+        .into_diagnostic()?;
+    fs_err::tokio::write(&path, &contents)
+        .await
+        .into_diagnostic()?;
+    Ok(())
+}
+
+/// Returns the contents of the cached copy of a remote file, or `None` if it
+/// isn't cached or the cached copy doesn't match the pinned hash.
+fn read_cached(include: &RemoteInclude) -> miette::Result<Option<String>> {
+    Ok(std::fs::read_to_string(cache_path(&include.sha256)?)
+        .ok()
+        .filter(|contents| compute_bytes_digest::<Sha256>(contents) == include.sha256))
+}
+
+/// Returns the path of the cached copy of the remote file with the given hash.
+fn cache_path(sha256: &Sha256Hash) -> miette::Result<PathBuf> {
+    Ok(pixi_config::get_cache_dir()?
+        .join(consts::REMOTE_INCLUDES_CACHE_DIR)
+        .join(format!("{sha256:x}.toml")))
+}
@@ -1,8 +1,12 @@
-use std::{hash::Hash, path::PathBuf};
+use std::{collections::HashSet, hash::Hash, path::PathBuf};
 
+use indexmap::IndexSet;
 use itertools::Itertools;
 use pixi_manifest as manifest;
-use pixi_manifest::{FeaturesExt, HasFeaturesIter, HasManifestRef, Manifest, SystemRequirements};
+use pixi_manifest::{
+    FeaturesExt, HasFeaturesIter, HasManifestRef, Manifest, PrioritizedChannel, SystemRequirements,
+};
+use rattler_conda_types::Platform;
 
 use super::{Environment, HasProjectRef, Project};
 
@@ -83,6 +87,23 @@ impl<'p> HasFeaturesIter<'p> for SolveGroup<'p> {
             .flat_map(|env: Environment<'p>| env.features().collect_vec().into_iter())
             .unique_by(|feat| &feat.name)
     }
+
+    /// Returns the platforms of the environments in the group, if all of them
+    /// set their platforms. The group is solved for each of these platforms.
+    fn platforms_override(&self) -> Option<HashSet<Platform>> {
+        self.environments()
+            .map(|env| env.platforms_override())
+            .collect::<Option<Vec<_>>>()
+            .map(|platforms| platforms.into_iter().flatten().collect())
+    }
+
+    /// Returns the channels that are set on the environments in the group,
+    /// these have been validated to be the same for all environments.
+    fn channels_override(&self) -> Option<&'p IndexSet<PrioritizedChannel>> {
+        self.environments()
+            .next()
+            .and_then(|env| env.channels_override())
+    }
 }
 
 impl<'p> HasProjectRef<'p> for SolveGroup<'p> {
@@ -2,12 +2,10 @@ use crate::project::errors::UnsupportedPlatformError;
 use crate::project::Environment;
 use itertools::Itertools;
 use miette::Diagnostic;
-use pixi_default_versions::{default_glibc_version, default_linux_version, default_mac_os_version};
-use pixi_manifest::{LibCSystemRequirement, SystemRequirements};
+use pixi_manifest::SystemRequirements;
 use rattler_conda_types::{GenericVirtualPackage, Platform, Version};
 use rattler_virtual_packages::{
-    Archspec, Cuda, DetectVirtualPackageError, LibC, Linux, Osx, VirtualPackage,
-    VirtualPackageOverrides,
+    DetectVirtualPackageError, VirtualPackage, VirtualPackageOverrides,
 };
 use std::collections::HashMap;
 use thiserror::Error;
@@ -15,61 +13,13 @@ use thiserror::Error;
 use pixi_manifest::FeaturesExt;
 
 /// Returns a reasonable modern set of virtual packages that should be safe enough to assume.
-/// At the time of writing, this is in sync with the conda-lock set of minimal virtual packages.
-/// <https://github.com/conda/conda-lock/blob/3d36688278ebf4f65281de0846701d61d6017ed2/conda_lock/virtual_package.py#L175>
 ///
 /// The method also takes into account system requirements specified in the project manifest.
 pub(crate) fn get_minimal_virtual_packages(
     platform: Platform,
     system_requirements: &SystemRequirements,
 ) -> Vec<VirtualPackage> {
-    // TODO: How to add a default cuda requirements
-    let mut virtual_packages: Vec<VirtualPackage> = vec![];
-
-    // Match high level platforms
-    if platform.is_unix() {
-        virtual_packages.push(VirtualPackage::Unix);
-    }
-    if platform.is_linux() {
-        let version = system_requirements
-            .linux
-            .clone()
-            .unwrap_or(default_linux_version());
-        virtual_packages.push(VirtualPackage::Linux(Linux { version }));
-
-        let (family, version) = system_requirements
-            .libc
-            .as_ref()
-            .map(LibCSystemRequirement::family_and_version)
-            .map(|(family, version)| (family.to_string(), version.clone()))
-            .unwrap_or(("glibc".parse().unwrap(), default_glibc_version()));
-        virtual_packages.push(VirtualPackage::LibC(LibC { family, version }));
-    }
-
-    if platform.is_windows() {
-        virtual_packages.push(VirtualPackage::Win);
-    }
-
-    // Add platform specific packages
-    if platform.is_osx() {
-        let version = system_requirements
-            .macos
-            .clone()
-            .unwrap_or_else(|| default_mac_os_version(platform));
-        virtual_packages.push(VirtualPackage::Osx(Osx { version }));
-    }
-
-    // Cuda
-    if let Some(version) = system_requirements.cuda.clone() {
-        virtual_packages.push(VirtualPackage::Cuda(Cuda { version }));
-    }
-
-    // Archspec is only based on the platform for now
-    if let Some(spec) = Archspec::from_platform(platform) {
-        virtual_packages.push(VirtualPackage::Archspec(spec));
-    }
-
-    virtual_packages
+    system_requirements.minimal_virtual_packages(platform)
 }
 
 impl Environment<'_> {
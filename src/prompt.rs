@@ -89,3 +89,31 @@ pub(crate) fn get_nu_prompt(env_name: &str) -> String {
 pub(crate) fn get_cmd_prompt(env_name: &str) -> String {
     format!(r"@PROMPT ({}) $P$G", env_name)
 }
+
+/// Hook for the bash shell that activates the environment of the workspace in
+/// the current directory, `switch_command` prints the required changes.
+pub(crate) fn get_bash_auto_hook(switch_command: &str) -> String {
+    include_str!("shell_snippets/auto/pixi-auto.bash")
+        .replace("__PIXI_AUTO_SWITCH__", switch_command)
+}
+
+/// Hook for the zsh shell that activates the environment of the workspace in
+/// the current directory, `switch_command` prints the required changes.
+pub(crate) fn get_zsh_auto_hook(switch_command: &str) -> String {
+    include_str!("shell_snippets/auto/pixi-auto.zsh")
+        .replace("__PIXI_AUTO_SWITCH__", switch_command)
+}
+
+/// Hook for the fish shell that activates the environment of the workspace in
+/// the current directory, `switch_command` prints the required changes.
+pub(crate) fn get_fish_auto_hook(switch_command: &str) -> String {
+    include_str!("shell_snippets/auto/pixi-auto.fish")
+        .replace("__PIXI_AUTO_SWITCH__", switch_command)
+}
+
+/// Hook for the Nu shell that activates the environment of the workspace in
+/// the current directory, `switch_command` prints the required changes as
+/// JSON.
+pub(crate) fn get_nu_auto_hook(switch_command: &str) -> String {
+    include_str!("shell_snippets/auto/pixi-auto.nu").replace("__PIXI_AUTO_SWITCH__", switch_command)
+}
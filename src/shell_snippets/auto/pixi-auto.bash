@@ -0,0 +1,13 @@
+_pixi_auto_hook() {
+    local previous_exit_status=$?
+    if [ "$PWD" != "${_PIXI_AUTO_PWD:-}" ]; then
+        _PIXI_AUTO_PWD="$PWD"
+        eval "$(__PIXI_AUTO_SWITCH__)"
+    fi
+    return $previous_exit_status
+}
+
+case ";${PROMPT_COMMAND:-};" in
+    *";_pixi_auto_hook;"*) ;;
+    *) PROMPT_COMMAND="_pixi_auto_hook${PROMPT_COMMAND:+;$PROMPT_COMMAND}" ;;
+esac
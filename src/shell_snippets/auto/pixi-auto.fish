@@ -0,0 +1,5 @@
+function _pixi_auto_hook --on-variable PWD
+    __PIXI_AUTO_SWITCH__ | source
+end
+
+_pixi_auto_hook
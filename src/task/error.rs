@@ -11,6 +11,26 @@ use pixi_manifest::{EnvironmentName, TaskName};
 #[error("could not find the task '{0}'", task_name.fancy_display())]
 pub struct MissingTaskError {
     pub task_name: TaskName,
+    #[help]
+    pub suggestion: Option<String>,
+}
+
+impl MissingTaskError {
+    /// Creates the error and suggests the known tasks that are named like the
+    /// missing task.
+    pub fn new<'a>(
+        task_name: TaskName,
+        known_tasks: impl IntoIterator<Item = &'a TaskName>,
+    ) -> Self {
+        let suggestion = pixi_manifest::did_you_mean(&pixi_manifest::similar_names(
+            task_name.as_str(),
+            known_tasks.into_iter().map(TaskName::as_str),
+        ));
+        Self {
+            task_name,
+            suggestion,
+        }
+    }
 }
 
 // TODO: We should make this error much better
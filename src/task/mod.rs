@@ -1,12 +1,14 @@
 mod error;
 mod executable_task;
 mod file_hashes;
+mod run_status;
 mod task_environment;
 mod task_graph;
 mod task_hash;
 
 pub use file_hashes::{FileHashes, FileHashesError};
 pub use pixi_manifest::{Task, TaskName};
+pub use run_status::{TaskRunOutcome, TaskRunStatus};
 pub use task_hash::{ComputationHash, EnvironmentHash, InputHashes, TaskHash};
 
 pub use executable_task::{
@@ -0,0 +1,186 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use pixi_manifest::{EnvironmentName, TaskName};
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::Project;
+
+/// The name of the directory inside the task cache that stores the status of
+/// the last run of every task.
+const RUN_STATUS_DIR: &str = "runs";
+
+/// How the last run of a task ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "kebab-case")]
+pub enum TaskRunOutcome {
+    /// The task finished with a zero exit code.
+    Succeeded,
+
+    /// The task finished with a non-zero exit code.
+    Failed { exit_code: i32 },
+
+    /// The task was skipped because its inputs and outputs didn't change.
+    Skipped,
+}
+
+/// The status of the last run of a task in a specific environment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskRunStatus {
+    #[serde(flatten)]
+    pub outcome: TaskRunOutcome,
+
+    /// When the run finished.
+    pub finished_at: DateTime<Utc>,
+}
+
+impl TaskRunStatus {
+    /// Creates a new status for a run that just finished.
+    pub fn new(outcome: TaskRunOutcome) -> Self {
+        Self {
+            outcome,
+            finished_at: Utc::now(),
+        }
+    }
+
+    /// Returns the path of the file that stores the status of the last run of
+    /// the task in the environment.
+    pub(crate) fn path(
+        project: &Project,
+        environment: &EnvironmentName,
+        task: &TaskName,
+    ) -> PathBuf {
+        project
+            .task_cache_folder()
+            .join(RUN_STATUS_DIR)
+            .join(Self::relative_path(environment, task))
+    }
+
+    /// Returns the path of the status file relative to the status directory.
+    /// The task name is hashed because it can contain characters that are not
+    /// allowed in file names.
+    fn relative_path(environment: &EnvironmentName, task: &TaskName) -> PathBuf {
+        let mut hasher = Xxh3::new();
+        hasher.update(task.as_str().as_bytes());
+        PathBuf::from(environment.as_str()).join(format!("{:x}.json", hasher.digest()))
+    }
+
+    /// Reads the status of the last run of the task in the environment.
+    /// Returns `None` if the task was never run or the status can't be read.
+    pub(crate) fn read(
+        project: &Project,
+        environment: &EnvironmentName,
+        task: &TaskName,
+    ) -> Option<Self> {
+        let contents = fs_err::read_to_string(Self::path(project, environment, task)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Writes the status of the run of the task in the environment.
+    pub(crate) async fn write(
+        &self,
+        project: &Project,
+        environment: &EnvironmentName,
+        task: &TaskName,
+    ) -> Result<(), std::io::Error> {
+        let path = Self::path(project, environment, task);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let contents = serde_json::to_string(self)?;
+        tokio::fs::write(path, contents).await
+    }
+}
+
+impl Display for TaskRunStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let age = (Utc::now() - self.finished_at)
+            .to_std()
+            .unwrap_or(Duration::ZERO);
+        match self.outcome {
+            TaskRunOutcome::Succeeded => write!(
+                f,
+                "{} {} ago",
+                console::style(console::Emoji("✔", "ok")).green(),
+                format_age(age)
+            ),
+            TaskRunOutcome::Failed { exit_code } => write!(
+                f,
+                "{} exit code {exit_code}, {} ago",
+                console::style(console::Emoji("✘", "failed")).red(),
+                format_age(age)
+            ),
+            TaskRunOutcome::Skipped => write!(
+                f,
+                "{} cached {} ago",
+                console::style(console::Emoji("⇢", "skipped")).cyan(),
+                format_age(age)
+            ),
+        }
+    }
+}
+
+/// Formats a duration using only its largest unit, e.g. `5m` or `3d`.
+fn format_age(age: Duration) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+
+    let seconds = age.as_secs();
+    if seconds < MINUTE {
+        format!("{seconds}s")
+    } else if seconds < HOUR {
+        format!("{}m", seconds / MINUTE)
+    } else if seconds < DAY {
+        format!("{}h", seconds / HOUR)
+    } else {
+        format!("{}d", seconds / DAY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(Duration::from_secs(12)), "12s");
+        assert_eq!(format_age(Duration::from_secs(5 * 60 + 30)), "5m");
+        assert_eq!(format_age(Duration::from_secs(2 * 60 * 60)), "2h");
+        assert_eq!(format_age(Duration::from_secs(3 * 24 * 60 * 60 + 1)), "3d");
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let path = |environment: &str, task: &str| {
+            TaskRunStatus::relative_path(
+                &EnvironmentName::Named(environment.to_string()),
+                &TaskName::from(task),
+            )
+        };
+
+        assert_ne!(path("a-b", "c"), path("a", "b-c"));
+        assert_eq!(path("a-b", "c"), path("a-b", "c"));
+        assert!(path("test", "lint:fix")
+            .to_str()
+            .unwrap()
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '/' | '\\')));
+    }
+
+    #[test]
+    fn test_run_status_serialization() {
+        let status = TaskRunStatus::new(TaskRunOutcome::Failed { exit_code: 2 });
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["outcome"], "failed");
+        assert_eq!(json["exit_code"], 2);
+
+        let roundtrip: TaskRunStatus = serde_json::from_value(json).unwrap();
+        assert_eq!(roundtrip, status);
+    }
+}
@@ -1,5 +1,6 @@
+use itertools::Itertools;
 use miette::Diagnostic;
-use pixi_manifest::{Task, TaskName};
+use pixi_manifest::{FeatureName, Manifest, Task, TaskName};
 use rattler_conda_types::Platform;
 use thiserror::Error;
 
@@ -49,6 +50,9 @@ pub struct SearchEnvironments<'p, D: TaskDisambiguation<'p> = NoDisambiguation>
     pub platform: Option<Platform>,
     pub disambiguate: D,
     pub ignore_system_requirements: bool,
+    /// The member of the workspace whose tasks take precedence over the tasks
+    /// of the workspace.
+    pub member: Option<&'p Manifest>,
 }
 
 /// Information about an task that was found when searching for a task
@@ -99,6 +103,7 @@ impl<'p> SearchEnvironments<'p, NoDisambiguation> {
             platform,
             disambiguate: NoDisambiguation,
             ignore_system_requirements: false,
+            member: None,
         }
     }
 }
@@ -118,9 +123,16 @@ impl<'p, D: TaskDisambiguation<'p>> SearchEnvironments<'p, D> {
             platform: self.platform,
             disambiguate: DisambiguateFn(func),
             ignore_system_requirements: false,
+            member: self.member,
         }
     }
 
+    /// Look for tasks in the given member of the workspace before looking in
+    /// the workspace itself.
+    pub(crate) fn with_member(self, member: Option<&'p Manifest>) -> Self {
+        Self { member, ..self }
+    }
+
     /// Ignore system requirements when looking for tasks.
     #[cfg(test)]
     pub(crate) fn with_ignore_system_requirements(self, ignore: bool) -> Self {
@@ -137,6 +149,21 @@ impl<'p, D: TaskDisambiguation<'p>> SearchEnvironments<'p, D> {
         name: TaskName,
         source: FindTaskSource<'p>,
     ) -> Result<TaskAndEnvironment<'p>, FindTaskError> {
+        // The tasks of a member run in the explicit or the default environment of
+        // the workspace.
+        if let Some(task) = self.member.and_then(|member| {
+            member
+                .tasks(self.platform, &FeatureName::Default)
+                .ok()
+                .and_then(|tasks| tasks.get(&name).copied())
+        }) {
+            let environment = self
+                .explicit_environment
+                .clone()
+                .unwrap_or_else(|| self.project.default_environment());
+            return Ok((environment, task));
+        }
+
         // If no explicit environment was specified
         if self.explicit_environment.is_none() {
             let default_env = self.project.default_environment();
@@ -192,9 +219,18 @@ impl<'p, D: TaskDisambiguation<'p>> SearchEnvironments<'p, D> {
         }
 
         match tasks.len() {
-            0 => Err(FindTaskError::MissingTask(MissingTaskError {
-                task_name: name,
-            })),
+            0 => {
+                let known_tasks = environments
+                    .iter()
+                    .filter_map(|env| env.tasks(self.platform).ok())
+                    .flat_map(|tasks| tasks.into_keys())
+                    .sorted()
+                    .collect_vec();
+                Err(FindTaskError::MissingTask(MissingTaskError::new(
+                    name,
+                    known_tasks,
+                )))
+            }
             1 => {
                 let (env, task) = tasks.remove(0);
                 Ok((env.clone(), task))
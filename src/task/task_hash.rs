@@ -1,4 +1,4 @@
-use crate::project;
+use crate::project::{self, HasProjectRef};
 use crate::task::{ExecutableTask, FileHashes, FileHashesError, InvalidWorkingDirectory};
 use miette::Diagnostic;
 use rattler_lock::LockFile;
@@ -60,18 +60,22 @@ impl EnvironmentHash {
         for script in activation_scripts {
             script.hash(&mut hasher);
         }
-
-        // Hash the environment variables
-        let project_activation_env =
-            run_environment.activation_env(Some(run_environment.best_platform()));
-        let mut env_vars: Vec<_> = project_activation_env.iter().collect();
-        env_vars.sort_by_key(|(key, _)| *key);
-
-        for (key, value) in env_vars {
-            key.hash(&mut hasher);
-            value.hash(&mut hasher);
+        let task_shell_scripts =
+            run_environment.activation_task_shell_scripts(Some(run_environment.best_platform()));
+        // The variables exported by the task shell scripts are part of the
+        // activation, so their contents are hashed as well.
+        let root = run_environment.project().root();
+        for script in task_shell_scripts {
+            fs_err::read(root.join(&script)).ok().hash(&mut hasher);
+            script.hash(&mut hasher);
         }
 
+        // Hash the environment variables, the order in which they are applied
+        // is deterministic and matters for the result.
+        run_environment
+            .activation_env(Some(run_environment.best_platform()))
+            .hash(&mut hasher);
+
         // Hash the packages
         let mut urls = Vec::new();
         if let Some(env) = lock_file.environment(run_environment.name().as_str()) {
@@ -93,3 +93,59 @@ def test_smokey(pixi: Path, tmp_path: Path) -> None:
     metadata = json.loads(conda_meta.read_text())
 
     assert metadata["name"] == "smokey"
+
+
+def test_workspace_member_source_dependencies(pixi: Path, tmp_path: Path) -> None:
+    """
+    One member of a workspace depends on another member as a source dependency,
+    both are solved into the lock file of the workspace
+    """
+    test_data = get_data_dir("rattler-build-backend")
+    workspace = tmp_path / "workspace"
+    smokey = workspace / "packages" / "smokey"
+    app = workspace / "packages" / "app"
+    smokey.mkdir(parents=True)
+    app.mkdir(parents=True)
+
+    (workspace / "pixi.toml").write_text(
+        """
+        [workspace]
+        name = "workspace"
+        channels = ["conda-forge"]
+        platforms = ["osx-arm64", "linux-64", "osx-64", "win-64"]
+        preview = ["pixi-build"]
+        members = ["packages/*"]
+        """
+    )
+    (smokey / "pixi.toml").write_text(
+        """
+        [package]
+        name = "smokey"
+        version = "1.2.3"
+
+        [build-system]
+        build-backend = "pixi-build-rattler-build"
+        channels = [
+          "https://prefix.dev/pixi-build-backends",
+          "https://prefix.dev/conda-forge",
+        ]
+        dependencies = ["pixi-build-rattler-build"]
+        """
+    )
+    shutil.copyfile(test_data / "recipes/smokey/recipe.yaml", smokey / "recipe.yaml")
+    (app / "pixi.toml").write_text(
+        """
+        [dependencies]
+        smokey = { path = "../smokey" }
+        """
+    )
+
+    verify_cli_command([pixi, "install", "--manifest-path", workspace / "pixi.toml"])
+
+    # the source dependency is locked in the lock file of the workspace
+    assert not (app / "pixi.lock").exists()
+    assert "packages/smokey" in (workspace / "pixi.lock").read_text()
+
+    conda_meta = next((workspace / ".pixi/envs/default/conda-meta").glob("smokey-*.json"))
+    metadata = json.loads(conda_meta.read_text())
+    assert metadata["name"] == "smokey"